@@ -3,15 +3,22 @@ use std::io::{self, BufRead};
 
 fn print_game_info(game: &mut chess_engine::chess_game::Game) {
     game.print_board();
-    if game.game_is_over() {
-        let winner = game.get_winner();
+    let status = game.game_status();
+    if status.is_over() {
         println!("Game is over!");
-        if winner == None {
-            println!("Draw");
-        } else if winner.unwrap() == ChessPieceColor::White {
-            println!("White wins!");
-        } else if winner.unwrap() == ChessPieceColor::Black {
-            println!("Black wins!");
+        match status {
+            GameStatus::Checkmate { winner } => println!("{:?} wins by checkmate!", winner),
+            GameStatus::Resignation { winner } => println!("{:?} wins by resignation!", winner),
+            GameStatus::Timeout { winner } => println!("{:?} wins on time!", winner),
+            GameStatus::TimeoutVsInsufficientMaterial => {
+                println!("Draw, the time ran out but the opponent could not mate")
+            }
+            GameStatus::Stalemate => println!("Draw by stalemate"),
+            GameStatus::FiftyMove => println!("Draw by the fifty move rule"),
+            GameStatus::Repetition => println!("Draw by repetition"),
+            GameStatus::InsufficientMaterial => println!("Draw by insufficient material"),
+            GameStatus::Agreement => println!("Draw by agreement"),
+            GameStatus::Ongoing => {}
         }
         return;
     }
@@ -29,6 +36,8 @@ fn print_game_info(game: &mut chess_engine::chess_game::Game) {
     println!("Algebraic notation, ex Na3 to move knight to a3");
     println!("Algebraic notation, specifying move exactly ex. Nb1b3 will show debug messages");
     println!("Or, print possible moves: ex 'moves a2' to print all moves for a2");
+    println!("Or, take back and replay moves with 'undo' and 'redo'");
+    println!("Or, claim a draw when a position has occurred three times with 'draw'");
 }
 
 fn main() {
@@ -40,7 +49,25 @@ fn main() {
         for line in stdin.lock().lines().map(|l| l.unwrap()) {
             let user_input: Vec<String> =
                 line.split_whitespace().map(|num| num.to_string()).collect();
-            if user_input.len() == 1 {
+            if user_input.len() == 1 && (user_input[0] == "undo" || user_input[0] == "redo") {
+                // Take back or replay a move
+                let result = if user_input[0] == "undo" {
+                    game.undo_move()
+                } else {
+                    game.redo_move()
+                };
+                if result.is_none() {
+                    println!("No move to {}", user_input[0]);
+                }
+                print_game_info(&mut game);
+            } else if user_input.len() == 1 && user_input[0] == "draw" {
+                // Claim a draw by threefold repetition
+                let result = game.claim_draw();
+                if result.is_err() {
+                    println!("{}", result.err().unwrap());
+                }
+                print_game_info(&mut game);
+            } else if user_input.len() == 1 {
                 // Treat it as a algebraic move
                 let result = game.algebraic_notation_move(user_input[0].clone());
                 if result.is_ok() {
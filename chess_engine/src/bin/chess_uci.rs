@@ -0,0 +1,264 @@
+// A chess engine that talks the Universal Chess Interface on stdin and stdout,
+// so it can be used by chess GUIs and tournament programs
+extern crate chess_engine;
+use chess_engine::chess_game::*;
+use chess_engine::search::{get_mate_in, SearchLimits, SearchResult, Searcher};
+use std::io::{self, BufRead};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+const DEFAULT_HASH_MB: usize = 16;
+
+// Print the result of a finished depth
+fn print_info(result: &SearchResult) {
+    let time = result.time.as_millis().max(1);
+    let score_text = match get_mate_in(result.score) {
+        Some(moves) => format!("mate {}", moves),
+        None => format!("cp {}", result.score),
+    };
+    let pv_text: Vec<String> = result
+        .pv
+        .iter()
+        .map(|chess_move| chess_move.to_long_algebraic_notation())
+        .collect();
+    println!(
+        "info depth {} score {} nodes {} nps {} time {} pv {}",
+        result.depth,
+        score_text,
+        result.nodes,
+        result.nodes as u128 * 1000 / time,
+        time,
+        pv_text.join(" ")
+    );
+}
+
+struct Engine {
+    game: Game,
+    stop: Arc<AtomicBool>,
+    searcher: Option<Searcher>, // None while it is searching
+    search_thread: Option<JoinHandle<Searcher>>, // Gives back the searcher when done
+    hash_mb: usize,             // Size of the transposition table
+    move_overhead: u64,         // Milliseconds kept for sending the move
+    chess960: bool,             // Castling is sent as the king taking its own rook
+}
+
+impl Engine {
+    fn new() -> Self {
+        let mut game = Game::new();
+        game.set_up_board();
+        return Self {
+            game,
+            stop: Arc::new(AtomicBool::new(false)),
+            searcher: Some(Searcher::new(DEFAULT_HASH_MB)),
+            search_thread: None,
+            hash_mb: DEFAULT_HASH_MB,
+            move_overhead: 30,
+            chess960: false,
+        };
+    }
+
+    // Handle one line from the GUI, returns false when the engine should quit
+    fn handle_command(&mut self, line: &str) -> bool {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if words.is_empty() {
+            return true;
+        }
+        match words[0] {
+            "uci" => {
+                println!("id name vicmil-chess");
+                println!("id author vnomarrow");
+                println!(
+                    "option name Hash type spin default {} min 1 max 4096",
+                    DEFAULT_HASH_MB
+                );
+                println!("option name Move Overhead type spin default 30 min 0 max 5000");
+                println!("option name UCI_Chess960 type check default false");
+                println!("uciok");
+            }
+            "isready" => println!("readyok"),
+            "ucinewgame" => {
+                self.stop_search();
+                self.game = Game::new();
+                self.game.set_up_board();
+                self.searcher.as_mut().unwrap().clear();
+            }
+            "position" => {
+                self.stop_search();
+                self.set_position(&words[1..]);
+            }
+            "go" => {
+                self.stop_search();
+                self.start_search(&words[1..]);
+            }
+            "stop" => self.stop_search(),
+            "setoption" => {
+                self.stop_search();
+                self.set_option(&words[1..]);
+            }
+            "quit" => {
+                self.stop_search();
+                return false;
+            }
+            _ => println!("info string Unknown command: {}", words[0]),
+        }
+        return true;
+    }
+
+    // "position startpos moves e2e4 e7e5" or "position fen <fen> moves ..."
+    fn set_position(&mut self, words: &[&str]) {
+        let moves_index = words
+            .iter()
+            .position(|word| *word == "moves")
+            .unwrap_or(words.len());
+        let game = match words.first() {
+            Some(&"startpos") => Game::from_fen(STARTING_FEN),
+            Some(&"fen") => Game::from_fen(&words[1..moves_index].join(" ")),
+            _ => Err("Expected startpos or fen".to_string()),
+        };
+        match game {
+            Ok(mut game) => {
+                if self.chess960 {
+                    game.set_chess960(true);
+                }
+                self.game = game;
+            }
+            Err(error) => {
+                println!("info string Invalid position: {}", error);
+                return;
+            }
+        }
+
+        for text in words.iter().skip(moves_index + 1) {
+            let result = ChessMove::from_long_algebraic_notation(text).and_then(|chess_move| {
+                self.game
+                    .play_move(chess_move.board_move, chess_move.promote_piece)
+            });
+            if result.is_err() {
+                println!(
+                    "info string Invalid move {}: {}",
+                    text,
+                    result.err().unwrap()
+                );
+                return;
+            }
+        }
+    }
+
+    // "go depth 6", "go movetime 1000", "go wtime 60000 btime 60000 winc 0 binc 0", ...
+    fn start_search(&mut self, words: &[&str]) {
+        let mut limits = SearchLimits::default();
+        let mut infinite = false;
+        let mut time_left: Option<u64> = None;
+        let mut increment: u64 = 0;
+        let mut moves_to_go: u64 = 30;
+        let white = self.game.turn == ChessPieceColor::White;
+        let mut i = 0;
+        while i < words.len() {
+            let value = words.get(i + 1).and_then(|word| word.parse::<u64>().ok());
+            match words[i] {
+                "depth" => limits.depth = value.map(|depth| depth as u32),
+                "nodes" => limits.nodes = value,
+                "movetime" => limits.time = value.map(Duration::from_millis),
+                "wtime" if white => time_left = value,
+                "btime" if !white => time_left = value,
+                "winc" if white => increment = value.unwrap_or(0),
+                "binc" if !white => increment = value.unwrap_or(0),
+                "movestogo" => moves_to_go = value.unwrap_or(30).max(1),
+                "infinite" => infinite = true,
+                _ => {}
+            }
+            i += 1;
+        }
+
+        // Use a part of the time left, and never more than there is
+        if let (None, Some(time_left)) = (limits.time, time_left) {
+            let time_left = time_left.saturating_sub(self.move_overhead);
+            let time = (time_left / moves_to_go + increment * 3 / 4).min(time_left);
+            limits.time = Some(Duration::from_millis(time.max(1)));
+        }
+        if infinite {
+            limits.time = None;
+        }
+
+        self.stop = Arc::new(AtomicBool::new(false));
+        limits.stop = Some(self.stop.clone());
+        let stop = self.stop.clone();
+        let mut searcher = self.searcher.take().unwrap();
+        let game = self.game.clone();
+        self.search_thread = Some(thread::spawn(move || {
+            let result = searcher.search_with_info(&game, &limits, print_info);
+            // When searching infinitely the best move is only sent after "stop"
+            while infinite && !stop.load(Ordering::Relaxed) {
+                thread::sleep(Duration::from_millis(5));
+            }
+            match result.best_move {
+                Some(chess_move) => {
+                    println!("bestmove {}", chess_move.to_long_algebraic_notation())
+                }
+                None => println!("bestmove 0000"),
+            }
+            return searcher;
+        }));
+    }
+
+    // Stop the search if there is one, it prints its best move before this returns
+    fn stop_search(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(search_thread) = self.search_thread.take() {
+            match search_thread.join() {
+                Ok(searcher) => self.searcher = Some(searcher),
+                Err(_) => {
+                    // The search panicked before it sent a move, the GUI still waits for one
+                    println!("info string The search failed");
+                    println!("bestmove 0000");
+                    self.searcher = Some(Searcher::new(self.hash_mb));
+                }
+            }
+        }
+    }
+
+    // "setoption name Move Overhead value 100"
+    fn set_option(&mut self, words: &[&str]) {
+        let value_index = words
+            .iter()
+            .position(|word| *word == "value")
+            .unwrap_or(words.len());
+        let name = words[1.min(value_index)..value_index].join(" ");
+        let value = words.get(value_index + 1..).unwrap_or(&[]).join(" ");
+        match name.to_lowercase().as_str() {
+            "hash" => match value.parse() {
+                Ok(size_mb) => {
+                    self.hash_mb = size_mb;
+                    self.searcher.as_mut().unwrap().resize_table(size_mb);
+                }
+                Err(_) => println!("info string Invalid value for {}: {}", name, value),
+            },
+            "move overhead" => match value.parse() {
+                Ok(move_overhead) => self.move_overhead = move_overhead,
+                Err(_) => println!("info string Invalid value for {}: {}", name, value),
+            },
+            "uci_chess960" => match value.parse() {
+                Ok(chess960) => self.chess960 = chess960,
+                Err(_) => println!("info string Invalid value for {}: {}", name, value),
+            },
+            _ => println!("info string Unknown option: {}", name),
+        }
+    }
+}
+
+fn main() {
+    let mut engine = Engine::new();
+    let stdin = io::stdin();
+    for line in stdin.lock().lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+        if !engine.handle_command(&line) {
+            return;
+        }
+    }
+    engine.stop_search();
+}
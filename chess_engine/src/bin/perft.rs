@@ -0,0 +1,55 @@
+// Count the positions a number of moves ahead, ex. "perft 4" or "perft 3 <fen>"
+// Add --divide to print the count after every first move
+extern crate chess_engine;
+use chess_engine::chess_game::*;
+use std::time::Instant;
+
+fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let divide = args.iter().any(|arg| arg == "--divide");
+    args.retain(|arg| arg != "--divide");
+    if args.is_empty() {
+        eprintln!("Usage: perft <depth> [fen] [--divide]");
+        std::process::exit(2);
+    }
+
+    let depth: u32 = match args[0].parse() {
+        Ok(depth) => depth,
+        Err(_) => {
+            eprintln!("Invalid depth: {}", args[0]);
+            std::process::exit(2);
+        }
+    };
+    let fen = if args.len() > 1 {
+        args[1..].join(" ")
+    } else {
+        STARTING_FEN.to_string()
+    };
+    let mut game = match Game::from_fen(&fen) {
+        Ok(game) => game,
+        Err(error) => {
+            eprintln!("Invalid FEN: {}", error);
+            std::process::exit(2);
+        }
+    };
+
+    let start = Instant::now();
+    let nodes: u64 = if divide {
+        let counts = game.divide(depth);
+        for (chess_move, count) in counts.iter() {
+            println!("{}: {}", chess_move.to_long_algebraic_notation(), count);
+        }
+        println!();
+        println!("Moves: {}", counts.len());
+        counts.iter().map(|(_, count)| count).sum()
+    } else {
+        game.perft(depth)
+    };
+    let seconds = start.elapsed().as_secs_f64();
+    println!("Nodes: {}", nodes);
+    println!(
+        "Time: {:.3} s ({:.0} nodes/s)",
+        seconds,
+        nodes as f64 / seconds.max(0.001)
+    );
+}
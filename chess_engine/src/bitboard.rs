@@ -0,0 +1,267 @@
+// Bitboards keep one bit per square, using the same index as the board array in Game,
+// so square = x + 8 * y and bit 0 is a8. White pawns move towards lower squares.
+use crate::chess_game::{BoardPosition, ChessPiece, ChessPieceColor, ChessPieceId};
+
+pub type Bitboard = u64;
+
+const FILE_A: Bitboard = 0x0101_0101_0101_0101;
+const FILE_H: Bitboard = FILE_A << 7;
+
+// Steps in (x, y) and the matching step in square index, for the eight directions
+const DIRECTIONS: [(i32, i32); 8] = [
+    (1, 0),
+    (0, 1),
+    (1, 1),
+    (-1, 1),
+    (-1, 0),
+    (0, -1),
+    (-1, -1),
+    (1, -1),
+];
+const STRAIGHT: [usize; 4] = [0, 1, 4, 5];
+const DIAGONAL: [usize; 4] = [2, 3, 6, 7];
+
+const KNIGHT_OFFSETS: [(i32, i32); 8] = [
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+];
+
+const KNIGHT_ATTACKS: [Bitboard; 64] = make_offset_table(&KNIGHT_OFFSETS);
+const KING_ATTACKS: [Bitboard; 64] = make_offset_table(&DIRECTIONS);
+const RAYS: [[Bitboard; 64]; 8] = make_ray_table();
+
+const fn make_offset_table(offsets: &[(i32, i32); 8]) -> [Bitboard; 64] {
+    let mut table = [0; 64];
+    let mut square = 0;
+    while square < 64 {
+        let mut i = 0;
+        while i < 8 {
+            let x = (square % 8) as i32 + offsets[i].0;
+            let y = (square / 8) as i32 + offsets[i].1;
+            if x >= 0 && x < 8 && y >= 0 && y < 8 {
+                table[square] |= 1 << (x + 8 * y);
+            }
+            i += 1;
+        }
+        square += 1;
+    }
+    return table;
+}
+
+// All squares from a square to the edge of the board in each direction
+const fn make_ray_table() -> [[Bitboard; 64]; 8] {
+    let mut table = [[0; 64]; 8];
+    let mut direction = 0;
+    while direction < 8 {
+        let mut square = 0;
+        while square < 64 {
+            let mut x = (square % 8) as i32 + DIRECTIONS[direction].0;
+            let mut y = (square / 8) as i32 + DIRECTIONS[direction].1;
+            while x >= 0 && x < 8 && y >= 0 && y < 8 {
+                table[direction][square] |= 1 << (x + 8 * y);
+                x += DIRECTIONS[direction].0;
+                y += DIRECTIONS[direction].1;
+            }
+            square += 1;
+        }
+        direction += 1;
+    }
+    return table;
+}
+
+pub fn get_square(pos: BoardPosition) -> usize {
+    return (pos.x + 8 * pos.y) as usize;
+}
+
+pub fn get_position(square: usize) -> BoardPosition {
+    return BoardPosition::new((square % 8) as u8, (square / 8) as u8);
+}
+
+// All squares with an x coordinate, ex. 0 for the a-file
+pub fn get_file(x: usize) -> Bitboard {
+    return FILE_A << x;
+}
+
+// All squares with a y coordinate, ex. 0 for the 8th rank
+pub fn get_row(y: usize) -> Bitboard {
+    return 0xff << (8 * y);
+}
+
+pub fn get_bit(pos: BoardPosition) -> Bitboard {
+    return 1 << get_square(pos);
+}
+
+// Iterate the squares of all set bits, from the lowest square
+pub fn get_squares(mut bitboard: Bitboard) -> impl Iterator<Item = usize> {
+    return std::iter::from_fn(move || {
+        if bitboard == 0 {
+            return None;
+        }
+        let square = bitboard.trailing_zeros() as usize;
+        bitboard &= bitboard - 1;
+        return Some(square);
+    });
+}
+
+pub fn knight_attacks(square: usize) -> Bitboard {
+    return KNIGHT_ATTACKS[square];
+}
+
+pub fn king_attacks(square: usize) -> Bitboard {
+    return KING_ATTACKS[square];
+}
+
+// The squares a pawn on a square can take on
+pub fn pawn_attacks(square: usize, color: ChessPieceColor) -> Bitboard {
+    let bit: Bitboard = 1 << square;
+    if color == ChessPieceColor::White {
+        return ((bit & !FILE_A) >> 9) | ((bit & !FILE_H) >> 7);
+    } else {
+        return ((bit & !FILE_H) << 9) | ((bit & !FILE_A) << 7);
+    }
+}
+
+pub fn rook_attacks(square: usize, occupied: Bitboard) -> Bitboard {
+    let mut attacks = 0;
+    for direction in STRAIGHT.iter() {
+        attacks |= ray_attacks(*direction, square, occupied);
+    }
+    return attacks;
+}
+
+pub fn bishop_attacks(square: usize, occupied: Bitboard) -> Bitboard {
+    let mut attacks = 0;
+    for direction in DIAGONAL.iter() {
+        attacks |= ray_attacks(*direction, square, occupied);
+    }
+    return attacks;
+}
+
+pub fn queen_attacks(square: usize, occupied: Bitboard) -> Bitboard {
+    return rook_attacks(square, occupied) | bishop_attacks(square, occupied);
+}
+
+// Follow a ray until the first piece, which is included since it can be taken
+fn ray_attacks(direction: usize, square: usize, occupied: Bitboard) -> Bitboard {
+    let ray = RAYS[direction][square];
+    let blockers = ray & occupied;
+    if blockers == 0 {
+        return ray;
+    }
+    // Directions that increase the square find the closest blocker at the lowest bit
+    let (dx, dy) = DIRECTIONS[direction];
+    let blocker = if dx + 8 * dy > 0 {
+        blockers.trailing_zeros() as usize
+    } else {
+        63 - blockers.leading_zeros() as usize
+    };
+    return ray ^ RAYS[direction][blocker];
+}
+
+// The squares of every piece type and color
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct Bitboards {
+    pieces: [Bitboard; 6], // Indexed by ChessPieceId
+    colors: [Bitboard; 2], // Indexed by ChessPieceColor
+}
+
+impl Bitboards {
+    pub fn new() -> Self {
+        return Self::default();
+    }
+
+    pub fn from_board(board: &[Option<ChessPiece>; 8 * 8]) -> Self {
+        let mut bitboards = Self::new();
+        for (square, piece) in board.iter().enumerate() {
+            if let Some(piece) = piece {
+                bitboards.add_piece(square, piece.id, piece.color);
+            }
+        }
+        return bitboards;
+    }
+
+    pub fn add_piece(&mut self, square: usize, id: ChessPieceId, color: ChessPieceColor) {
+        self.pieces[id as usize] |= 1 << square;
+        self.colors[color as usize] |= 1 << square;
+    }
+
+    pub fn remove_piece(&mut self, square: usize) {
+        let mask: Bitboard = !(1 << square);
+        for bitboard in self.pieces.iter_mut().chain(self.colors.iter_mut()) {
+            *bitboard &= mask;
+        }
+    }
+
+    // Move whatever is on a square to another square, taking what was there
+    pub fn move_piece(&mut self, from: usize, to: usize) {
+        self.remove_piece(to);
+        let from_bit: Bitboard = 1 << from;
+        let to_bit: Bitboard = 1 << to;
+        for bitboard in self.pieces.iter_mut().chain(self.colors.iter_mut()) {
+            if *bitboard & from_bit != 0 {
+                *bitboard ^= from_bit | to_bit;
+            }
+        }
+    }
+
+    pub fn get_pieces(&self, id: ChessPieceId, color: ChessPieceColor) -> Bitboard {
+        return self.pieces[id as usize] & self.colors[color as usize];
+    }
+
+    // Get the type of the piece on a square, if there is one
+    pub fn get_piece_id(&self, square: usize) -> Option<ChessPieceId> {
+        let bit: Bitboard = 1 << square;
+        for id in [
+            ChessPieceId::Pawn,
+            ChessPieceId::Knight,
+            ChessPieceId::Rook,
+            ChessPieceId::King,
+            ChessPieceId::Queen,
+            ChessPieceId::Bishop,
+        ]
+        .iter()
+        {
+            if self.pieces[*id as usize] & bit != 0 {
+                return Some(*id);
+            }
+        }
+        return None;
+    }
+
+    pub fn get_color(&self, color: ChessPieceColor) -> Bitboard {
+        return self.colors[color as usize];
+    }
+
+    pub fn get_occupied(&self) -> Bitboard {
+        return self.colors[0] | self.colors[1];
+    }
+
+    // Get the pieces of a color that can take on a square
+    pub fn get_attackers(&self, square: usize, by_color: ChessPieceColor) -> Bitboard {
+        let occupied = self.get_occupied();
+        // A pawn attacks a square if a pawn of the other color on that square would attack it
+        let pawn_color = if by_color == ChessPieceColor::White {
+            ChessPieceColor::Black
+        } else {
+            ChessPieceColor::White
+        };
+        let queens = self.get_pieces(ChessPieceId::Queen, by_color);
+        return (pawn_attacks(square, pawn_color) & self.get_pieces(ChessPieceId::Pawn, by_color))
+            | (knight_attacks(square) & self.get_pieces(ChessPieceId::Knight, by_color))
+            | (king_attacks(square) & self.get_pieces(ChessPieceId::King, by_color))
+            | (rook_attacks(square, occupied)
+                & (self.get_pieces(ChessPieceId::Rook, by_color) | queens))
+            | (bishop_attacks(square, occupied)
+                & (self.get_pieces(ChessPieceId::Bishop, by_color) | queens));
+    }
+
+    pub fn is_attacked(&self, square: usize, by_color: ChessPieceColor) -> bool {
+        return self.get_attackers(square, by_color) != 0;
+    }
+}
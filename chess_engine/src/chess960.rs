@@ -0,0 +1,76 @@
+// Chess960 start positions. The 960 back ranks are numbered with the Scharnagl scheme,
+// where the standard starting position is number 518
+use crate::chess_game::ChessPieceId;
+
+pub const POSITION_COUNT: u32 = 960;
+pub const STANDARD_POSITION: u32 = 518;
+
+// The squares of the two knights among the five squares left after the bishops and queen
+const KNIGHT_SQUARES: [(usize, usize); 10] = [
+    (0, 1),
+    (0, 2),
+    (0, 3),
+    (0, 4),
+    (1, 2),
+    (1, 3),
+    (1, 4),
+    (2, 3),
+    (2, 4),
+    (3, 4),
+];
+
+// Get the back rank of a start position from the a-file to the h-file, the same for both colors
+pub fn get_back_rank(index: u32) -> Result<[ChessPieceId; 8], String> {
+    if index >= POSITION_COUNT {
+        return Err(format!(
+            "Chess960 position must be below {}, found {}",
+            POSITION_COUNT, index
+        ));
+    }
+    let mut back_rank: [Option<ChessPieceId>; 8] = [None; 8];
+    let mut n = index as usize;
+
+    // One bishop on a light square (b, d, f or h) and one on a dark square (a, c, e or g)
+    back_rank[(n % 4) * 2 + 1] = Some(ChessPieceId::Bishop);
+    n /= 4;
+    back_rank[(n % 4) * 2] = Some(ChessPieceId::Bishop);
+    n /= 4;
+
+    // The queen and the knights are placed on the squares that are left, counted from the a-file
+    let queen = n % 6;
+    n /= 6;
+    place_on_empty(&mut back_rank, queen, ChessPieceId::Queen);
+    let (first_knight, second_knight) = KNIGHT_SQUARES[n];
+    place_on_empty(&mut back_rank, second_knight, ChessPieceId::Knight);
+    place_on_empty(&mut back_rank, first_knight, ChessPieceId::Knight);
+
+    // The king always stands between the rooks
+    place_on_empty(&mut back_rank, 0, ChessPieceId::Rook);
+    place_on_empty(&mut back_rank, 0, ChessPieceId::King);
+    place_on_empty(&mut back_rank, 0, ChessPieceId::Rook);
+
+    let mut pieces = [ChessPieceId::Pawn; 8];
+    for (x, piece) in back_rank.iter().enumerate() {
+        pieces[x] = piece.unwrap();
+    }
+    return Ok(pieces);
+}
+
+// Get a start position from a seed, the same seed always gives the same position
+pub fn get_random_index(seed: u64) -> u32 {
+    // splitmix64, so seeds next to each other give unrelated positions
+    let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^= z >> 31;
+    return (z % POSITION_COUNT as u64) as u32;
+}
+
+// Place a piece on the empty square with a number, counting only empty squares
+fn place_on_empty(back_rank: &mut [Option<ChessPieceId>; 8], number: usize, id: ChessPieceId) {
+    let x = (0..8)
+        .filter(|x| back_rank[*x].is_none())
+        .nth(number)
+        .unwrap();
+    back_rank[x] = Some(id);
+}
@@ -0,0 +1,371 @@
+// Time controls and a chess clock for both players. The clock does not read the time itself,
+// every call gets the current time, so a server can use the time a move arrived
+use crate::chess_game::{ChessPieceColor, Game};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+// What a player gets back for a move
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Bonus {
+    None,
+    Increment(Duration), // Fischer, added after every move
+    Bronstein(Duration), // The time used for the move is added back, at most this much
+    Delay(Duration),     // Simple delay, the clock waits this long before it counts down
+}
+
+// A part of a time control, ex. 40 moves in 90 minutes
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Stage {
+    pub moves: Option<u32>, // None for the rest of the game
+    pub time: Duration,
+    pub bonus: Bonus,
+}
+
+// The stages are played in order. When the last stage has a move count it is repeated,
+// ex. 40 moves in 2 hours, then 40 more moves in 2 hours and so on
+#[derive(Clone, PartialEq, Debug)]
+pub struct TimeControl {
+    pub stages: Vec<Stage>,
+}
+
+impl TimeControl {
+    pub fn sudden_death(time: Duration) -> Self {
+        return TimeControl::single(time, Bonus::None);
+    }
+
+    pub fn fischer(time: Duration, increment: Duration) -> Self {
+        return TimeControl::single(time, Bonus::Increment(increment));
+    }
+
+    pub fn bronstein(time: Duration, delay: Duration) -> Self {
+        return TimeControl::single(time, Bonus::Bronstein(delay));
+    }
+
+    pub fn simple_delay(time: Duration, delay: Duration) -> Self {
+        return TimeControl::single(time, Bonus::Delay(delay));
+    }
+
+    fn single(time: Duration, bonus: Bonus) -> Self {
+        return TimeControl {
+            stages: vec![Stage {
+                moves: None,
+                time,
+                bonus,
+            }],
+        };
+    }
+
+    // Read a time control written as in the PGN TimeControl tag, with the stages separated
+    // by ':' and the times in seconds, ex. "300", "180+2" or "40/5400+30:1800+30".
+    // A delay is written with 'd' instead of '+', and a Bronstein delay with 'b', ex. "300d5"
+    pub fn parse(text: &str) -> Result<TimeControl, String> {
+        let mut stages = Vec::new();
+        for stage_text in text.trim().split(':') {
+            let (moves, rest) = match stage_text.find('/') {
+                Some(index) => {
+                    let moves = parse_number(&stage_text[..index], stage_text)?;
+                    if moves == 0 {
+                        return Err(format!(
+                            "A stage must have at least one move: {}",
+                            stage_text
+                        ));
+                    }
+                    (Some(moves as u32), &stage_text[index + 1..])
+                }
+                None => (None, stage_text),
+            };
+            let (time, bonus) = match rest.find(&['+', 'd', 'b'][..]) {
+                Some(index) => {
+                    let seconds =
+                        Duration::from_secs(parse_number(&rest[index + 1..], stage_text)?);
+                    let bonus = match &rest[index..index + 1] {
+                        "+" => Bonus::Increment(seconds),
+                        "d" => Bonus::Delay(seconds),
+                        _ => Bonus::Bronstein(seconds),
+                    };
+                    (&rest[..index], bonus)
+                }
+                None => (rest, Bonus::None),
+            };
+            let time = Duration::from_secs(parse_number(time, stage_text)?);
+            if time == Duration::from_secs(0) && bonus == Bonus::None {
+                return Err(format!("A stage must give some time: {}", stage_text));
+            }
+            stages.push(Stage { moves, time, bonus });
+        }
+
+        // Only the last stage can go on for the rest of the game
+        for stage in stages.iter().take(stages.len() - 1) {
+            if stage.moves.is_none() {
+                return Err(format!(
+                    "Only the last stage can be without a move count: {}",
+                    text
+                ));
+            }
+        }
+        return Ok(TimeControl { stages });
+    }
+
+    // Get the time both players start with
+    pub fn get_start_time(&self) -> Duration {
+        return self.stages[0].time;
+    }
+}
+
+fn parse_number(text: &str, stage_text: &str) -> Result<u64, String> {
+    return text
+        .parse()
+        .map_err(|_| format!("Invalid time control stage: {}", stage_text));
+}
+
+impl fmt::Display for TimeControl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut stages = Vec::new();
+        for stage in self.stages.iter() {
+            let mut text = String::new();
+            if let Some(moves) = stage.moves {
+                text.push_str(&format!("{}/", moves));
+            }
+            text.push_str(&stage.time.as_secs().to_string());
+            match stage.bonus {
+                Bonus::None => {}
+                Bonus::Increment(time) => text.push_str(&format!("+{}", time.as_secs())),
+                Bonus::Delay(time) => text.push_str(&format!("d{}", time.as_secs())),
+                Bonus::Bronstein(time) => text.push_str(&format!("b{}", time.as_secs())),
+            }
+            stages.push(text);
+        }
+        return write!(f, "{}", stages.join(":"));
+    }
+}
+
+// Why the clock could not be pressed or started
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ClockError {
+    NotRunning,
+    WrongTurn,
+    Flagged { color: ChessPieceColor },
+}
+
+impl fmt::Display for ClockError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClockError::NotRunning => return write!(f, "The clock is not running"),
+            ClockError::WrongTurn => return write!(f, "It is not the turn of that player"),
+            ClockError::Flagged { color } => {
+                return write!(f, "{:?} has already run out of time", color)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ClockError {}
+
+#[derive(Clone, Debug)]
+pub struct Clock {
+    time_control: TimeControl,
+    remaining: [Duration; 2], // Indexed by ChessPieceColor, at the start of the turn
+    stages: [usize; 2],       // The stage each player is in
+    stage_moves: [u32; 2],    // Moves played in the current stage
+    turn: ChessPieceColor,
+    turn_start: Option<Instant>, // None while the clock is stopped
+    used: Duration,              // Time used this turn before the clock was last started
+    flagged: Option<ChessPieceColor>,
+}
+
+impl Clock {
+    // Create a stopped clock where White moves first
+    pub fn new(time_control: TimeControl) -> Self {
+        let start_time = time_control.get_start_time();
+        return Clock {
+            time_control,
+            remaining: [start_time; 2],
+            stages: [0; 2],
+            stage_moves: [0; 2],
+            turn: ChessPieceColor::White,
+            turn_start: None,
+            used: Duration::from_secs(0),
+            flagged: None,
+        };
+    }
+
+    pub fn get_time_control(&self) -> &TimeControl {
+        return &self.time_control;
+    }
+
+    pub fn get_turn(&self) -> ChessPieceColor {
+        return self.turn;
+    }
+
+    pub fn is_running(&self) -> bool {
+        return self.turn_start.is_some();
+    }
+
+    // Get the player that ran out of time, found by check_flag
+    pub fn get_flagged(&self) -> Option<ChessPieceColor> {
+        return self.flagged;
+    }
+
+    // Start or continue the clock of the player to move
+    pub fn start(&mut self, now: Instant) -> Result<(), ClockError> {
+        if let Some(color) = self.flagged {
+            return Err(ClockError::Flagged { color });
+        }
+        if self.turn_start.is_none() {
+            self.turn_start = Some(now);
+        }
+        return Ok(());
+    }
+
+    // Stop the clock, ex. when the game is over or paused. A delay is not given again when
+    // the clock is started, the time used before the stop still counts
+    pub fn stop(&mut self, now: Instant) {
+        self.used = self.get_used(now);
+        self.turn_start = None;
+    }
+
+    // Get the time left of a player, counting the running time of the player to move
+    pub fn get_remaining(&self, color: ChessPieceColor, now: Instant) -> Duration {
+        let remaining = self.remaining[color as usize];
+        if color != self.turn {
+            return remaining;
+        }
+        let used = self.get_used(now);
+        return match self.get_stage(color).bonus {
+            Bonus::Delay(delay) => remaining.saturating_sub(used.saturating_sub(delay)),
+            _ => remaining.saturating_sub(used),
+        };
+    }
+
+    // Get the moves left before the next stage gives more time, None in the last stage
+    // if it is for the rest of the game
+    pub fn get_moves_to_go(&self, color: ChessPieceColor) -> Option<u32> {
+        let moves = self.get_stage(color).moves?;
+        return Some(moves - self.stage_moves[color as usize]);
+    }
+
+    // The player to move is done with a move. The clock adds the bonus and the time of the
+    // next stage, and starts the clock of the other player
+    pub fn press(&mut self, color: ChessPieceColor, now: Instant) -> Result<(), ClockError> {
+        if self.check_flag(now).is_some() {
+            return Err(ClockError::Flagged {
+                color: self.flagged.unwrap(),
+            });
+        }
+        if self.turn_start.is_none() {
+            return Err(ClockError::NotRunning);
+        }
+        if color != self.turn {
+            return Err(ClockError::WrongTurn);
+        }
+
+        let used = self.get_used(now);
+        let stage = *self.get_stage(color);
+        let mut remaining = self.get_remaining(color, now);
+        match stage.bonus {
+            Bonus::Increment(increment) => remaining += increment,
+            Bonus::Bronstein(delay) => remaining += used.min(delay),
+            _ => {}
+        }
+
+        let index = color as usize;
+        self.stage_moves[index] += 1;
+        if stage.moves == Some(self.stage_moves[index]) {
+            // The last stage is played again if it has a move count
+            self.stages[index] = (self.stages[index] + 1).min(self.time_control.stages.len() - 1);
+            self.stage_moves[index] = 0;
+            remaining += self.get_stage(color).time;
+        }
+        self.remaining[index] = remaining;
+
+        self.switch_turn(get_opponent(color), now);
+        return Ok(());
+    }
+
+    // Give the turn to a player without a bonus or a move being counted,
+    // ex. when a move is taken back
+    pub fn set_turn(&mut self, color: ChessPieceColor, now: Instant) {
+        if color == self.turn {
+            return;
+        }
+        self.remaining[self.turn as usize] = self.get_remaining(self.turn, now);
+        self.switch_turn(color, now);
+    }
+
+    // Set the time left of a player, ex. to the time of the clock on the server
+    pub fn set_remaining(&mut self, color: ChessPieceColor, remaining: Duration, now: Instant) {
+        self.remaining[color as usize] = remaining;
+        if color == self.turn {
+            self.used = Duration::from_secs(0);
+            if self.turn_start.is_some() {
+                self.turn_start = Some(now);
+            }
+        }
+    }
+
+    // Returns the player that ran out of time, and stops the clock when that happens
+    pub fn check_flag(&mut self, now: Instant) -> Option<ChessPieceColor> {
+        if self.flagged.is_none()
+            && self.turn_start.is_some()
+            && self.get_remaining(self.turn, now) == Duration::from_secs(0)
+        {
+            self.stop(now);
+            self.remaining[self.turn as usize] = Duration::from_secs(0);
+            self.flagged = Some(self.turn);
+        }
+        return self.flagged;
+    }
+
+    // Ends the game if the player to move ran out of time, returns true if it did
+    pub fn check_timeout(&mut self, game: &mut Game, now: Instant) -> bool {
+        let flagged = match self.check_flag(now) {
+            Some(color) => color,
+            None => return false,
+        };
+        return game.time_out(flagged).is_ok();
+    }
+
+    fn switch_turn(&mut self, color: ChessPieceColor, now: Instant) {
+        self.turn = color;
+        self.used = Duration::from_secs(0);
+        if self.turn_start.is_some() {
+            self.turn_start = Some(now);
+        }
+    }
+
+    fn get_used(&self, now: Instant) -> Duration {
+        return match self.turn_start {
+            Some(turn_start) => self.used + now.saturating_duration_since(turn_start),
+            None => self.used,
+        };
+    }
+
+    fn get_stage(&self, color: ChessPieceColor) -> &Stage {
+        return &self.time_control.stages[self.stages[color as usize]];
+    }
+}
+
+fn get_opponent(color: ChessPieceColor) -> ChessPieceColor {
+    if color == ChessPieceColor::White {
+        return ChessPieceColor::Black;
+    } else {
+        return ChessPieceColor::White;
+    }
+}
+
+// Write a time as shown on a clock, "1:30:00", "4:59", and with tenths under ten seconds, "0:09.5"
+pub fn format_time(time: Duration) -> String {
+    let seconds = time.as_secs();
+    if seconds >= 3600 {
+        return format!(
+            "{}:{:02}:{:02}",
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        );
+    }
+    if seconds < 10 {
+        return format!("0:0{}.{}", seconds, time.subsec_millis() / 100);
+    }
+    return format!("{}:{:02}", seconds / 60, seconds % 60);
+}
@@ -0,0 +1,483 @@
+// Static evaluation of a position, in centipawns. Every term has a middlegame and an endgame
+// value, which are mixed depending on how much material is left on the board
+use crate::bitboard::{self, Bitboard, Bitboards};
+use crate::chess_game::{ChessPieceColor, ChessPieceId, Game};
+
+// The phase at the start of the game, a knight or bishop counts 1, a rook 2 and a queen 4
+pub const MAX_PHASE: i32 = 24;
+const PHASE_VALUES: [i32; 6] = [0, 1, 2, 0, 4, 1]; // Indexed by ChessPieceId
+
+const PIECES: [ChessPieceId; 6] = [
+    ChessPieceId::Pawn,
+    ChessPieceId::Knight,
+    ChessPieceId::Rook,
+    ChessPieceId::King,
+    ChessPieceId::Queen,
+    ChessPieceId::Bishop,
+];
+
+// A middlegame and an endgame value
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct Term {
+    pub middlegame: i32,
+    pub endgame: i32,
+}
+
+impl Term {
+    pub const fn new(middlegame: i32, endgame: i32) -> Self {
+        return Self {
+            middlegame,
+            endgame,
+        };
+    }
+
+    // Mix the values, phase is MAX_PHASE in the middlegame and 0 in the endgame
+    pub fn taper(&self, phase: i32) -> i32 {
+        return (self.middlegame * phase + self.endgame * (MAX_PHASE - phase)) / MAX_PHASE;
+    }
+}
+
+impl std::ops::Add for Term {
+    type Output = Term;
+    fn add(self, other: Term) -> Term {
+        return Term::new(
+            self.middlegame + other.middlegame,
+            self.endgame + other.endgame,
+        );
+    }
+}
+
+impl std::ops::AddAssign for Term {
+    fn add_assign(&mut self, other: Term) {
+        *self = *self + other;
+    }
+}
+
+impl std::ops::Sub for Term {
+    type Output = Term;
+    fn sub(self, other: Term) -> Term {
+        return Term::new(
+            self.middlegame - other.middlegame,
+            self.endgame - other.endgame,
+        );
+    }
+}
+
+impl std::ops::Mul<i32> for Term {
+    type Output = Term;
+    fn mul(self, factor: i32) -> Term {
+        return Term::new(self.middlegame * factor, self.endgame * factor);
+    }
+}
+
+// The weights of the evaluation. Tables are indexed by ChessPieceId and square,
+// and are seen from white, so the first square is a8. Black uses them mirrored
+#[derive(Clone, Debug)]
+pub struct EvaluationConfig {
+    pub piece_values: [Term; 6],
+    pub middlegame_tables: [[i32; 64]; 6],
+    pub endgame_tables: [[i32; 64]; 6],
+    pub doubled_pawn: Term, // For every pawn behind another pawn on the same file
+    pub isolated_pawn: Term, // For pawns without pawns of the same color on files next to it
+    pub passed_pawn: [Term; 8], // By the number of rows the pawn has moved
+    pub pawn_shield: Term,  // For pawns of the same color right in front of the king
+    pub king_attack: Term,  // For every square next to the king the opponent attacks
+    pub mobility: [Term; 6], // For every square a piece can move to
+    pub bishop_pair: Term,
+}
+
+#[rustfmt::skip]
+const PAWN_TABLE: [i32; 64] = [
+     0,   0,   0,   0,   0,   0,   0,   0,
+    50,  50,  50,  50,  50,  50,  50,  50,
+    10,  10,  20,  30,  30,  20,  10,  10,
+     5,   5,  10,  25,  25,  10,   5,   5,
+     0,   0,   0,  20,  20,   0,   0,   0,
+     5,  -5, -10,   0,   0, -10,  -5,   5,
+     5,  10,  10, -20, -20,  10,  10,   5,
+     0,   0,   0,   0,   0,   0,   0,   0,
+];
+
+#[rustfmt::skip]
+const PAWN_ENDGAME_TABLE: [i32; 64] = [
+     0,   0,   0,   0,   0,   0,   0,   0,
+    80,  80,  80,  80,  80,  80,  80,  80,
+    50,  50,  50,  50,  50,  50,  50,  50,
+    30,  30,  30,  30,  30,  30,  30,  30,
+    20,  20,  20,  20,  20,  20,  20,  20,
+    10,  10,  10,  10,  10,  10,  10,  10,
+     0,   0,   0,   0,   0,   0,   0,   0,
+     0,   0,   0,   0,   0,   0,   0,   0,
+];
+
+#[rustfmt::skip]
+const KNIGHT_TABLE: [i32; 64] = [
+   -50, -40, -30, -30, -30, -30, -40, -50,
+   -40, -20,   0,   0,   0,   0, -20, -40,
+   -30,   0,  10,  15,  15,  10,   0, -30,
+   -30,   5,  15,  20,  20,  15,   5, -30,
+   -30,   0,  15,  20,  20,  15,   0, -30,
+   -30,   5,  10,  15,  15,  10,   5, -30,
+   -40, -20,   0,   5,   5,   0, -20, -40,
+   -50, -40, -30, -30, -30, -30, -40, -50,
+];
+
+#[rustfmt::skip]
+const BISHOP_TABLE: [i32; 64] = [
+   -20, -10, -10, -10, -10, -10, -10, -20,
+   -10,   0,   0,   0,   0,   0,   0, -10,
+   -10,   0,   5,  10,  10,   5,   0, -10,
+   -10,   5,   5,  10,  10,   5,   5, -10,
+   -10,   0,  10,  10,  10,  10,   0, -10,
+   -10,  10,  10,  10,  10,  10,  10, -10,
+   -10,   5,   0,   0,   0,   0,   5, -10,
+   -20, -10, -10, -10, -10, -10, -10, -20,
+];
+
+#[rustfmt::skip]
+const ROOK_TABLE: [i32; 64] = [
+     0,   0,   0,   0,   0,   0,   0,   0,
+     5,  10,  10,  10,  10,  10,  10,   5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+    -5,   0,   0,   0,   0,   0,   0,  -5,
+     0,   0,   0,   5,   5,   0,   0,   0,
+];
+
+#[rustfmt::skip]
+const QUEEN_TABLE: [i32; 64] = [
+   -20, -10, -10,  -5,  -5, -10, -10, -20,
+   -10,   0,   0,   0,   0,   0,   0, -10,
+   -10,   0,   5,   5,   5,   5,   0, -10,
+    -5,   0,   5,   5,   5,   5,   0,  -5,
+     0,   0,   5,   5,   5,   5,   0,  -5,
+   -10,   5,   5,   5,   5,   5,   0, -10,
+   -10,   0,   5,   0,   0,   0,   0, -10,
+   -20, -10, -10,  -5,  -5, -10, -10, -20,
+];
+
+// The king should stay behind its pawns in the middlegame and go to the center in the endgame
+#[rustfmt::skip]
+const KING_TABLE: [i32; 64] = [
+   -30, -40, -40, -50, -50, -40, -40, -30,
+   -30, -40, -40, -50, -50, -40, -40, -30,
+   -30, -40, -40, -50, -50, -40, -40, -30,
+   -30, -40, -40, -50, -50, -40, -40, -30,
+   -20, -30, -30, -40, -40, -30, -30, -20,
+   -10, -20, -20, -20, -20, -20, -20, -10,
+    20,  20,   0,   0,   0,   0,  20,  20,
+    20,  30,  10,   0,   0,  10,  30,  20,
+];
+
+#[rustfmt::skip]
+const KING_ENDGAME_TABLE: [i32; 64] = [
+   -50, -40, -30, -20, -20, -30, -40, -50,
+   -30, -20, -10,   0,   0, -10, -20, -30,
+   -30, -10,  20,  30,  30,  20, -10, -30,
+   -30, -10,  30,  40,  40,  30, -10, -30,
+   -30, -10,  30,  40,  40,  30, -10, -30,
+   -30, -10,  20,  30,  30,  20, -10, -30,
+   -30, -30,   0,   0,   0,   0, -30, -30,
+   -50, -30, -30, -30, -30, -30, -30, -50,
+];
+
+pub const DEFAULT_CONFIG: EvaluationConfig = EvaluationConfig {
+    piece_values: [
+        Term::new(100, 120),
+        Term::new(320, 300),
+        Term::new(500, 520),
+        Term::new(0, 0),
+        Term::new(900, 920),
+        Term::new(330, 320),
+    ],
+    middlegame_tables: [
+        PAWN_TABLE,
+        KNIGHT_TABLE,
+        ROOK_TABLE,
+        KING_TABLE,
+        QUEEN_TABLE,
+        BISHOP_TABLE,
+    ],
+    endgame_tables: [
+        PAWN_ENDGAME_TABLE,
+        KNIGHT_TABLE,
+        ROOK_TABLE,
+        KING_ENDGAME_TABLE,
+        QUEEN_TABLE,
+        BISHOP_TABLE,
+    ],
+    doubled_pawn: Term::new(-10, -20),
+    isolated_pawn: Term::new(-10, -15),
+    passed_pawn: [
+        Term::new(0, 0),
+        Term::new(5, 10),
+        Term::new(10, 20),
+        Term::new(15, 35),
+        Term::new(25, 60),
+        Term::new(40, 100),
+        Term::new(60, 150),
+        Term::new(0, 0),
+    ],
+    pawn_shield: Term::new(10, 0),
+    king_attack: Term::new(-8, 0),
+    mobility: [
+        Term::new(0, 0),
+        Term::new(4, 4),
+        Term::new(2, 4),
+        Term::new(0, 0),
+        Term::new(1, 2),
+        Term::new(3, 3),
+    ],
+    bishop_pair: Term::new(30, 50),
+};
+
+impl Default for EvaluationConfig {
+    fn default() -> Self {
+        return DEFAULT_CONFIG;
+    }
+}
+
+// Every term of the evaluation, for white minus black
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct EvaluationBreakdown {
+    pub material: Term,
+    pub piece_squares: Term,
+    pub pawn_structure: Term,
+    pub king_safety: Term,
+    pub mobility: Term,
+    pub bishop_pair: Term,
+    pub phase: i32, // MAX_PHASE with all pieces on the board, 0 with only kings and pawns
+    pub total: i32, // For white, the sum of the terms mixed by the phase
+}
+
+impl EvaluationBreakdown {
+    fn get_terms(&self) -> [(&'static str, Term); 6] {
+        return [
+            ("Material", self.material),
+            ("Piece squares", self.piece_squares),
+            ("Pawn structure", self.pawn_structure),
+            ("King safety", self.king_safety),
+            ("Mobility", self.mobility),
+            ("Bishop pair", self.bishop_pair),
+        ];
+    }
+}
+
+impl std::fmt::Display for EvaluationBreakdown {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "{:<16}{:>8}{:>8}{:>8}", "Term", "Middle", "End", "Mixed")?;
+        for (name, term) in self.get_terms().iter() {
+            writeln!(
+                f,
+                "{:<16}{:>8}{:>8}{:>8}",
+                name,
+                term.middlegame,
+                term.endgame,
+                term.taper(self.phase)
+            )?;
+        }
+        writeln!(f, "Phase: {}/{}", self.phase, MAX_PHASE)?;
+        return write!(f, "Total: {} (for white)", self.total);
+    }
+}
+
+// Evaluate the position for the player to move with the default weights
+pub fn evaluate(game: &Game) -> i32 {
+    return evaluate_with_config(game, &DEFAULT_CONFIG);
+}
+
+// Evaluate the position for the player to move
+pub fn evaluate_with_config(game: &Game, config: &EvaluationConfig) -> i32 {
+    let total = get_breakdown(game, config).total;
+    if game.turn == ChessPieceColor::White {
+        return total;
+    } else {
+        return -total;
+    }
+}
+
+// Get every term of the evaluation, to see why a position is evaluated like it is
+pub fn get_breakdown(game: &Game, config: &EvaluationConfig) -> EvaluationBreakdown {
+    let bitboards = game.get_bitboards();
+    let mut breakdown = EvaluationBreakdown::default();
+    for id in PIECES.iter() {
+        let pieces = bitboards.get_pieces(*id, ChessPieceColor::White)
+            | bitboards.get_pieces(*id, ChessPieceColor::Black);
+        breakdown.phase += PHASE_VALUES[*id as usize] * pieces.count_ones() as i32;
+    }
+    breakdown.phase = breakdown.phase.min(MAX_PHASE);
+
+    let white = evaluate_color(&bitboards, ChessPieceColor::White, config);
+    let black = evaluate_color(&bitboards, ChessPieceColor::Black, config);
+    breakdown.material = white.material - black.material;
+    breakdown.piece_squares = white.piece_squares - black.piece_squares;
+    breakdown.pawn_structure = white.pawn_structure - black.pawn_structure;
+    breakdown.king_safety = white.king_safety - black.king_safety;
+    breakdown.mobility = white.mobility - black.mobility;
+    breakdown.bishop_pair = white.bishop_pair - black.bishop_pair;
+
+    let mut sum = Term::default();
+    for (_, term) in breakdown.get_terms().iter() {
+        sum += *term;
+    }
+    breakdown.total = sum.taper(breakdown.phase);
+    return breakdown;
+}
+
+// Get the terms for the pieces of one color, the phase and total are not set
+fn evaluate_color(
+    bitboards: &Bitboards,
+    color: ChessPieceColor,
+    config: &EvaluationConfig,
+) -> EvaluationBreakdown {
+    let mut breakdown = EvaluationBreakdown::default();
+    let own = bitboards.get_color(color);
+    let occupied = bitboards.get_occupied();
+    for id in PIECES.iter() {
+        let pieces = bitboards.get_pieces(*id, color);
+        for square in bitboard::get_squares(pieces) {
+            // The tables are seen from white, so black looks them up with the rows flipped
+            let table_square = if color == ChessPieceColor::White {
+                square
+            } else {
+                square ^ 56
+            };
+            breakdown.material += config.piece_values[*id as usize];
+            breakdown.piece_squares += Term::new(
+                config.middlegame_tables[*id as usize][table_square],
+                config.endgame_tables[*id as usize][table_square],
+            );
+
+            let moves = match id {
+                ChessPieceId::Knight => bitboard::knight_attacks(square),
+                ChessPieceId::Bishop => bitboard::bishop_attacks(square, occupied),
+                ChessPieceId::Rook => bitboard::rook_attacks(square, occupied),
+                ChessPieceId::Queen => bitboard::queen_attacks(square, occupied),
+                _ => 0,
+            };
+            breakdown.mobility +=
+                config.mobility[*id as usize] * (moves & !own).count_ones() as i32;
+        }
+    }
+
+    breakdown.pawn_structure = evaluate_pawns(bitboards, color, config);
+    breakdown.king_safety = evaluate_king_safety(bitboards, color, config);
+    if bitboards
+        .get_pieces(ChessPieceId::Bishop, color)
+        .count_ones()
+        >= 2
+    {
+        breakdown.bishop_pair = config.bishop_pair;
+    }
+    return breakdown;
+}
+
+fn evaluate_pawns(
+    bitboards: &Bitboards,
+    color: ChessPieceColor,
+    config: &EvaluationConfig,
+) -> Term {
+    let mut term = Term::default();
+    let pawns = bitboards.get_pieces(ChessPieceId::Pawn, color);
+    let opponent_pawns = bitboards.get_pieces(ChessPieceId::Pawn, get_opponent(color));
+    for x in 0..8 {
+        let pawns_on_file = (pawns & bitboard::get_file(x)).count_ones() as i32;
+        if pawns_on_file > 1 {
+            term += config.doubled_pawn * (pawns_on_file - 1);
+        }
+        if pawns_on_file > 0 && pawns & get_adjacent_files(x) == 0 {
+            term += config.isolated_pawn * pawns_on_file;
+        }
+    }
+
+    // A pawn is passed if no pawn of the opponent is in front of it on its own or the next files,
+    // only the front pawn of doubled pawns counts
+    for square in bitboard::get_squares(pawns) {
+        let x = square % 8;
+        let y = square / 8;
+        let files = bitboard::get_file(x) | get_adjacent_files(x);
+        let in_front = get_rows_in_front(y, color);
+        if opponent_pawns & files & in_front == 0 && pawns & bitboard::get_file(x) & in_front == 0 {
+            // Pawns can be placed on the first row with FEN, they count as not moved
+            let moved_rows = if color == ChessPieceColor::White {
+                6usize.saturating_sub(y)
+            } else {
+                y.saturating_sub(1)
+            };
+            term += config.passed_pawn[moved_rows.min(7)];
+        }
+    }
+    return term;
+}
+
+fn evaluate_king_safety(
+    bitboards: &Bitboards,
+    color: ChessPieceColor,
+    config: &EvaluationConfig,
+) -> Term {
+    let mut term = Term::default();
+    let kings = bitboards.get_pieces(ChessPieceId::King, color);
+    if kings == 0 {
+        return term;
+    }
+    let king = kings.trailing_zeros() as usize;
+    let x = king % 8;
+    let y = king / 8;
+
+    // Pawns on the two rows in front of the king, on its own and the next files
+    let files = bitboard::get_file(x) | get_adjacent_files(x);
+    let mut shield_rows: Bitboard = 0;
+    for distance in 1..3 {
+        let row = if color == ChessPieceColor::White {
+            y as i32 - distance
+        } else {
+            y as i32 + distance
+        };
+        if (0..8).contains(&row) {
+            shield_rows |= bitboard::get_row(row as usize);
+        }
+    }
+    let shield = bitboards.get_pieces(ChessPieceId::Pawn, color) & files & shield_rows;
+    term += config.pawn_shield * shield.count_ones() as i32;
+
+    let opponent = get_opponent(color);
+    let attacked_squares = bitboard::get_squares(bitboard::king_attacks(king))
+        .filter(|square| bitboards.is_attacked(*square, opponent))
+        .count();
+    term += config.king_attack * attacked_squares as i32;
+    return term;
+}
+
+fn get_adjacent_files(x: usize) -> Bitboard {
+    let mut files = 0;
+    if x > 0 {
+        files |= bitboard::get_file(x - 1);
+    }
+    if x < 7 {
+        files |= bitboard::get_file(x + 1);
+    }
+    return files;
+}
+
+// All rows a pawn on row y has not reached yet
+fn get_rows_in_front(y: usize, color: ChessPieceColor) -> Bitboard {
+    let mut rows = 0;
+    for row in 0..8 {
+        if (color == ChessPieceColor::White && row < y)
+            || (color == ChessPieceColor::Black && row > y)
+        {
+            rows |= bitboard::get_row(row);
+        }
+    }
+    return rows;
+}
+
+fn get_opponent(color: ChessPieceColor) -> ChessPieceColor {
+    if color == ChessPieceColor::White {
+        return ChessPieceColor::Black;
+    } else {
+        return ChessPieceColor::White;
+    }
+}
@@ -1,5 +1,25 @@
+pub mod bitboard;
+pub mod chess960;
+pub mod clock;
+pub mod evaluation;
+pub mod online;
+pub mod pgn;
+pub mod protocol;
+pub mod search;
+pub mod zobrist;
+
 pub mod chess_game {
-    use std::collections::LinkedList;
+    use crate::bitboard::{self, Bitboard, Bitboards};
+    use crate::chess960;
+    use crate::zobrist;
+    use std::cell::Cell;
+
+    const PROMOTION_PIECES: [ChessPieceId; 4] = [
+        ChessPieceId::Queen,
+        ChessPieceId::Rook,
+        ChessPieceId::Bishop,
+        ChessPieceId::Knight,
+    ];
 
     #[derive(Clone, Copy)]
     pub enum ColorTerminal {
@@ -25,7 +45,7 @@ pub mod chess_game {
     }
 
     type BoardPosType = u8;
-    #[derive(Clone, Copy, PartialEq, Eq)]
+    #[derive(Clone, Copy, PartialEq, Eq, Debug)]
     pub struct BoardPosition {
         pub x: BoardPosType,
         pub y: BoardPosType,
@@ -34,9 +54,9 @@ pub mod chess_game {
         pub fn new(x: BoardPosType, y: BoardPosType) -> Self {
             Self { x, y }
         }
-        pub fn from_algebraic_notation(text: &str) -> Result<Self, String> {
+        pub fn from_algebraic_notation(text: &str) -> Result<Self, MoveError> {
             if text.len() != 2 {
-                return Err("Wring size".to_string());
+                return Err(MoveError::InvalidNotation);
             }
             let char_vec: Vec<char> = text.chars().collect();
             let x = Self::get_coordinate_from_letter(char_vec[0])?;
@@ -44,7 +64,7 @@ pub mod chess_game {
             return Ok(Self::new(x, y));
         }
 
-        pub fn get_coordinate_from_letter(letter: char) -> Result<BoardPosType, String> {
+        pub fn get_coordinate_from_letter(letter: char) -> Result<BoardPosType, MoveError> {
             match letter {
                 'a' => return Ok(0),
                 'b' => return Ok(1),
@@ -54,11 +74,11 @@ pub mod chess_game {
                 'f' => return Ok(5),
                 'g' => return Ok(6),
                 'h' => return Ok(7),
-                _ => return Err("Could not parse coordinate letter".to_string()),
+                _ => return Err(MoveError::InvalidNotation),
             }
         }
 
-        pub fn get_coordinate_from_number(number: char) -> Result<BoardPosType, String> {
+        pub fn get_coordinate_from_number(number: char) -> Result<BoardPosType, MoveError> {
             match number {
                 '1' => return Ok(7),
                 '2' => return Ok(6),
@@ -68,12 +88,19 @@ pub mod chess_game {
                 '6' => return Ok(2),
                 '7' => return Ok(1),
                 '8' => return Ok(0),
-                _ => return Err("Could not parse coordinate number".to_string()),
+                _ => return Err(MoveError::InvalidNotation),
             }
         }
+
+        // Get the square name, ex. "e4"
+        pub fn to_algebraic_notation(&self) -> String {
+            let letter = (b'a' + self.x) as char;
+            let number = (b'8' - self.y) as char;
+            return format!("{}{}", letter, number);
+        }
     }
 
-    #[derive(Clone, Copy)]
+    #[derive(Clone, Copy, PartialEq, Debug)]
     pub struct BoardMove {
         pub from_pos: BoardPosition,
         pub to_pos: BoardPosition,
@@ -91,10 +118,70 @@ pub mod chess_game {
                 to_pos: BoardPosition::new(to_x, to_y),
             }
         }
+
+        // Read a move written as two square names, ex. "e2e4"
+        pub fn from_long_algebraic_notation(text: &str) -> Result<Self, MoveError> {
+            if text.len() != 4 || !text.is_ascii() {
+                return Err(MoveError::InvalidNotation);
+            }
+            return Ok(Self {
+                from_pos: BoardPosition::from_algebraic_notation(&text[0..2])?,
+                to_pos: BoardPosition::from_algebraic_notation(&text[2..4])?,
+            });
+        }
+
+        // Get the move as two square names, ex. "e2e4"
+        pub fn to_long_algebraic_notation(&self) -> String {
+            return self.from_pos.to_algebraic_notation() + &self.to_pos.to_algebraic_notation();
+        }
+    }
+
+    // A move from the move generator, promotions to different pieces are different moves
+    #[derive(Clone, Copy, PartialEq, Debug)]
+    pub struct ChessMove {
+        pub board_move: BoardMove,
+        pub promote_piece: Option<ChessPieceId>,
+    }
+
+    impl ChessMove {
+        pub fn new(board_move: BoardMove, promote_piece: Option<ChessPieceId>) -> Self {
+            Self {
+                board_move,
+                promote_piece,
+            }
+        }
+
+        // Read a move written as two square names and the promotion, ex. "e7e8q"
+        pub fn from_long_algebraic_notation(text: &str) -> Result<Self, MoveError> {
+            if (text.len() != 4 && text.len() != 5) || !text.is_ascii() {
+                return Err(MoveError::InvalidNotation);
+            }
+            let board_move = BoardMove::from_long_algebraic_notation(&text[0..4])?;
+            let mut promote_piece = None;
+            if text.len() == 5 {
+                match text.chars().nth(4).unwrap() {
+                    'q' => promote_piece = Some(ChessPieceId::Queen),
+                    'r' => promote_piece = Some(ChessPieceId::Rook),
+                    'b' => promote_piece = Some(ChessPieceId::Bishop),
+                    'n' => promote_piece = Some(ChessPieceId::Knight),
+                    _ => return Err(MoveError::InvalidPromotion),
+                }
+            }
+            return Ok(Self::new(board_move, promote_piece));
+        }
+
+        // Get the move as two square names and the promotion, ex. "e7e8q"
+        pub fn to_long_algebraic_notation(&self) -> String {
+            let mut text = self.board_move.to_long_algebraic_notation();
+            if let Some(promote_piece) = self.promote_piece {
+                text.push(promote_piece.get_letter().to_ascii_lowercase());
+            }
+            return text;
+        }
     }
 
     #[allow(dead_code)]
-    #[derive(Clone, Copy, PartialEq)]
+    #[derive(Clone, Copy, PartialEq, Debug)]
     pub enum ChessPieceId {
         Pawn,
         Knight,
@@ -131,15 +218,141 @@ pub mod chess_game {
             }
             return Err(());
         }
+
+        // Get the letter used for the piece in algebraic notation and FEN
+        pub fn get_letter(&self) -> char {
+            match self {
+                ChessPieceId::Pawn => return 'P',
+                ChessPieceId::Knight => return 'N',
+                ChessPieceId::Rook => return 'R',
+                ChessPieceId::King => return 'K',
+                ChessPieceId::Queen => return 'Q',
+                ChessPieceId::Bishop => return 'B',
+            }
+        }
     }
 
     #[allow(dead_code)]
-    #[derive(Clone, Copy, PartialEq)]
+    #[derive(Clone, Copy, PartialEq, Debug)]
     pub enum ChessPieceColor {
         White,
         Black,
     }
 
+    // Why a move could not be done
+    #[derive(Clone, Copy, PartialEq, Debug)]
+    pub enum MoveError {
+        InvalidNotation,
+        OutsideBoard,
+        NoPiece,
+        OpponentPiece,
+        NullMove,
+        OwnPieceOnTarget,
+        InvalidPieceMove,
+        PathBlocked,
+        NothingToCapture,
+        PieceHasMoved,
+        LeavesKingInCheck,
+        CastleThroughCheck,
+        NoCastlingRook,
+        NoKing,
+        PromotionRequired,
+        InvalidPromotion,
+        AmbiguousMove,
+        NoMatchingPiece,
+        GameOver,
+    }
+
+    impl MoveError {
+        // When a piece can move in several ways and all fail, the error from a way
+        // that matches the shape of the move tells the most about what went wrong
+        fn most_relevant(errors: &[MoveError]) -> MoveError {
+            for error in errors {
+                if *error != MoveError::InvalidPieceMove {
+                    return *error;
+                }
+            }
+            return MoveError::InvalidPieceMove;
+        }
+    }
+
+    impl std::fmt::Display for MoveError {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            let message = match self {
+                MoveError::InvalidNotation => "Could not parse move",
+                MoveError::OutsideBoard => "Outside of the board",
+                MoveError::NoPiece => "No piece on square selected",
+                MoveError::OpponentPiece => "Cannot move opponents pieces",
+                MoveError::NullMove => "Cannot do nothing during your turn",
+                MoveError::OwnPieceOnTarget => "Cannot move piece to piece of same color",
+                MoveError::InvalidPieceMove => "Piece cannot move like that",
+                MoveError::PathBlocked => "Path is blocked",
+                MoveError::NothingToCapture => "No piece to take",
+                MoveError::PieceHasMoved => "Piece has already moved",
+                MoveError::LeavesKingInCheck => "Move leads to check",
+                MoveError::CastleThroughCheck => "Cannot castle on checked square",
+                MoveError::NoCastlingRook => "No rook to castle with",
+                MoveError::NoKing => "Must have king to castle",
+                MoveError::PromotionRequired => {
+                    "Move requires promotion to a queen, rook, bishop or knight"
+                }
+                MoveError::InvalidPromotion => "Cannot promote piece",
+                MoveError::AmbiguousMove => "Unclear which piece is to move",
+                MoveError::NoMatchingPiece => "No piece can do that move",
+                MoveError::GameOver => "The game is over",
+            };
+            return write!(f, "{}", message);
+        }
+    }
+
+    impl std::error::Error for MoveError {}
+
+    // If the game is still running, or how it ended
+    #[derive(Clone, Copy, PartialEq, Debug)]
+    pub enum GameStatus {
+        Ongoing,
+        Checkmate { winner: ChessPieceColor },
+        Stalemate,
+        FiftyMove,
+        Repetition,
+        InsufficientMaterial,
+        Resignation { winner: ChessPieceColor },
+        Timeout { winner: ChessPieceColor },
+        TimeoutVsInsufficientMaterial, // The opponent of the player out of time cannot mate
+        Agreement,
+    }
+
+    impl GameStatus {
+        pub fn is_over(&self) -> bool {
+            return *self != GameStatus::Ongoing;
+        }
+
+        pub fn is_draw(&self) -> bool {
+            return self.is_over() && self.get_winner().is_none();
+        }
+
+        pub fn get_winner(&self) -> Option<ChessPieceColor> {
+            match *self {
+                GameStatus::Checkmate { winner } => return Some(winner),
+                GameStatus::Resignation { winner } => return Some(winner),
+                GameStatus::Timeout { winner } => return Some(winner),
+                _ => return None,
+            }
+        }
+
+        // Get the result as written in PGN, "1-0", "0-1", "1/2-1/2" or "*"
+        pub fn get_result(&self) -> &'static str {
+            if !self.is_over() {
+                return "*";
+            }
+            match self.get_winner() {
+                Some(ChessPieceColor::White) => return "1-0",
+                Some(ChessPieceColor::Black) => return "0-1",
+                None => return "1/2-1/2",
+            }
+        }
+    }
+
     #[allow(dead_code)]
     #[derive(Clone, Copy)]
     pub struct ChessPiece {
@@ -181,14 +394,115 @@ pub mod chess_game {
         }
     }
 
-    #[allow(dead_code)]
+    // A move that has been played, and everything it changed on the board
+    #[derive(Clone, Copy)]
+    pub struct PlayedMove {
+        pub board_move: BoardMove,
+        pub piece: ChessPiece, // The piece before it moved
+        pub promote_piece: Option<ChessPieceId>,
+        pub captured: Option<(BoardPosition, ChessPiece)>, // Not on to_pos when taking en passant
+        pub rook_move: Option<BoardMove>,                  // The rook that moved when casteling
+    }
+
+    impl PlayedMove {
+        pub fn is_capture(&self) -> bool {
+            return self.captured.is_some();
+        }
+
+        pub fn is_passant(&self) -> bool {
+            return self.captured.is_some() && self.captured.unwrap().0 != self.board_move.to_pos;
+        }
+
+        pub fn is_castle(&self) -> bool {
+            return self.rook_move.is_some();
+        }
+
+        // Get the squares the moving piece went between. Castling in Chess960 is written as
+        // the king taking its own rook, so the king does not end up on to_pos then
+        pub fn get_piece_move(&self) -> BoardMove {
+            if self.rook_move.is_none() {
+                return self.board_move;
+            }
+            // The king ends up next to the rook, on the other side of where the rook came from
+            let rook_move = self.rook_move.unwrap();
+            let x = if rook_move.from_pos.x > self.board_move.from_pos.x {
+                rook_move.to_pos.x + 1
+            } else {
+                rook_move.to_pos.x - 1
+            };
+            return BoardMove {
+                from_pos: self.board_move.from_pos,
+                to_pos: BoardPosition::new(x, rook_move.to_pos.y),
+            };
+        }
+
+        // Get the move as two square names and the promotion, ex. "e7e8q"
+        pub fn to_long_algebraic_notation(&self) -> String {
+            let mut text = self.board_move.to_long_algebraic_notation();
+            if let Some(promote_piece) = self.promote_piece {
+                text.push(promote_piece.get_letter().to_ascii_lowercase());
+            }
+            return text;
+        }
+    }
+
+    // The standard starting position in Forsyth-Edwards Notation
+    pub const STARTING_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+    // A move in the history of a game, with everything needed to take it back
     #[derive(Clone, Copy)]
+    pub struct MoveRecord {
+        pub played_move: PlayedMove,
+        last_move: Option<BoardMove>,
+        last_move_passant: bool,
+        move_count_left: i64,
+        full_move_number: u32,
+        hash: u64,
+    }
+
+    impl MoveRecord {
+        // Get the hash of the position before the move
+        pub fn get_hash(&self) -> u64 {
+            return self.hash;
+        }
+    }
+
+    // A game is Clone but not Copy, since it owns the history of its moves. Cloning copies
+    // the whole history, use clone_position to only copy the position
+    #[allow(dead_code)]
+    #[derive(Clone)]
     pub struct Game {
         board: [Option<ChessPiece>; 8 * 8],
         pub turn: ChessPieceColor,
         last_move: Option<BoardMove>,
         last_move_passant: bool,
         move_count_left: i64, // To make sure player does not play more than 50 moves
+        full_move_number: u32, // Starts at 1 and is increased after black moves
+        history: Vec<MoveRecord>,
+        redo_stack: Vec<MoveRecord>, // Moves that have been taken back
+        termination: Option<GameStatus>, // Set if the game was ended by a player or an automatic draw
+        bitboards: Cell<Bitboards>,
+        hash: Cell<Option<u64>>,   // None when it has to be calculated again
+        board_changed: Cell<bool>, // Set when the board changes, to rebuild bitboards and hash
+        chess960: bool,            // Castling is written as the king taking its own rook
+    }
+
+    // Iterates the positions of a game, see Game::get_past_positions
+    pub struct PastPositions {
+        game: Game,
+        started: bool,
+    }
+
+    impl Iterator for PastPositions {
+        type Item = Game;
+
+        fn next(&mut self) -> Option<Game> {
+            if self.started {
+                self.game.redo_move()?;
+            }
+            self.started = true;
+            return Some(self.game.clone_position());
+        }
     }
 
     #[allow(dead_code)]
@@ -201,11 +515,20 @@ pub mod chess_game {
                 last_move: None,
                 last_move_passant: false,
                 move_count_left: 100, // Since it is 50 moves per player
+                full_move_number: 1,
+                history: Vec::new(),
+                redo_stack: Vec::new(),
+                termination: None,
+                bitboards: Cell::new(Bitboards::new()),
+                hash: Cell::new(None),
+                board_changed: Cell::new(true),
+                chess960: false,
             }
         }
 
         pub fn set_up_board(&mut self) {
             self.empty_board();
+            self.chess960 = false;
             self.set_pos(
                 BoardPosition::new(0, 0),
                 ChessPieceId::Rook,
@@ -303,18 +626,420 @@ pub mod chess_game {
             }
         }
 
+        // Set up a Chess960 start position from its number, 0 to 959. Number 518 is the
+        // standard starting position, but castling is still written the Chess960 way
+        pub fn set_up_chess960(&mut self, index: u32) -> Result<(), String> {
+            let back_rank = chess960::get_back_rank(index)?;
+            self.empty_board();
+            for (x, id) in back_rank.iter().enumerate() {
+                let x = x as BoardPosType;
+                self.set_pos(BoardPosition::new(x, 0), *id, ChessPieceColor::Black);
+                self.set_pos(
+                    BoardPosition::new(x, 1),
+                    ChessPieceId::Pawn,
+                    ChessPieceColor::Black,
+                );
+                self.set_pos(
+                    BoardPosition::new(x, 6),
+                    ChessPieceId::Pawn,
+                    ChessPieceColor::White,
+                );
+                self.set_pos(BoardPosition::new(x, 7), *id, ChessPieceColor::White);
+            }
+            self.chess960 = true;
+            return Ok(());
+        }
+
+        // Set up a random Chess960 start position, the same seed gives the same position.
+        // Returns the number of the position
+        pub fn set_up_random_chess960(&mut self, seed: u64) -> u32 {
+            let index = chess960::get_random_index(seed);
+            self.set_up_chess960(index).unwrap();
+            return index;
+        }
+
+        // Returns true if castling follows the Chess960 rules and is written as the king
+        // taking its own rook, ex. "e1h1" instead of "e1g1"
+        pub fn is_chess960(&self) -> bool {
+            return self.chess960;
+        }
+
+        // Switch to the Chess960 castling rules, a standard position can be played with them
+        pub fn set_chess960(&mut self, chess960: bool) {
+            self.chess960 = chess960;
+        }
+
+        // Create a game from a position in Forsyth-Edwards Notation. The castling rights can
+        // also be written as in Shredder-FEN and X-FEN, with the file of the rook, which
+        // turns on the Chess960 rules if they are needed for the position
+        pub fn from_fen(fen: &str) -> Result<Game, String> {
+            let fields: Vec<&str> = fen.split_whitespace().collect();
+            if fields.len() != 4 && fields.len() != 6 {
+                return Err(format!(
+                    "FEN must have 4 or 6 fields separated by spaces, found {}",
+                    fields.len()
+                ));
+            }
+            let mut game = Game::new();
+
+            // Piece placement, starting from rank 8
+            let ranks: Vec<&str> = fields[0].split('/').collect();
+            if ranks.len() != 8 {
+                return Err(format!("FEN must have 8 ranks, found {}", ranks.len()));
+            }
+            for (y, rank) in ranks.iter().enumerate() {
+                let rank_number = 8 - y;
+                let mut x: usize = 0;
+                for letter in rank.chars() {
+                    if x >= 8 {
+                        return Err(format!("Rank {} has more than 8 squares", rank_number));
+                    }
+                    if let Some(empty_squares) = letter.to_digit(10) {
+                        if !(1..=8).contains(&empty_squares) {
+                            return Err(format!(
+                                "Invalid number of empty squares '{}' in rank {}",
+                                letter, rank_number
+                            ));
+                        }
+                        x += empty_squares as usize;
+                        continue;
+                    }
+                    let id = match game.get_piece_type_from_letter(letter.to_ascii_uppercase()) {
+                        Ok(id) => id,
+                        Err(_) => {
+                            return Err(format!(
+                                "Invalid piece letter '{}' in rank {}",
+                                letter, rank_number
+                            ))
+                        }
+                    };
+                    let color = if letter.is_ascii_uppercase() {
+                        ChessPieceColor::White
+                    } else {
+                        ChessPieceColor::Black
+                    };
+                    let mut piece = ChessPiece::new(id, color);
+                    // Pawns that have left their starting rank cannot move two forward
+                    if id == ChessPieceId::Pawn {
+                        piece.moved = !((color == ChessPieceColor::White && y == 6)
+                            || (color == ChessPieceColor::Black && y == 1));
+                    }
+                    // Kings and rooks count as moved unless the castling field says otherwise
+                    if id == ChessPieceId::King || id == ChessPieceId::Rook {
+                        piece.moved = true;
+                    }
+                    game.board[x + 8 * y] = Some(piece);
+                    game.board_changed.set(true);
+                    x += 1;
+                }
+                if x != 8 {
+                    return Err(format!(
+                        "Rank {} has {} squares, expected 8",
+                        rank_number, x
+                    ));
+                }
+            }
+
+            // Side to move
+            match fields[1] {
+                "w" => game.turn = ChessPieceColor::White,
+                "b" => game.turn = ChessPieceColor::Black,
+                _ => {
+                    return Err(format!(
+                        "Invalid side to move '{}', expected 'w' or 'b'",
+                        fields[1]
+                    ))
+                }
+            }
+
+            // Castling rights, "K" and "Q" are the outermost rooks and a file letter is the rook on it
+            if fields[2] != "-" {
+                let mut seen: Vec<(ChessPieceColor, bool)> = Vec::new();
+                for letter in fields[2].chars() {
+                    let color = if letter.is_ascii_uppercase() {
+                        ChessPieceColor::White
+                    } else {
+                        ChessPieceColor::Black
+                    };
+                    let y = if color == ChessPieceColor::White {
+                        7
+                    } else {
+                        0
+                    };
+                    let king_x = (0..8).find(|x| {
+                        game.is_piece_of(BoardPosition::new(*x, y), ChessPieceId::King, color)
+                    });
+                    if king_x.is_none() {
+                        return Err(format!(
+                            "Castling right '{}' requires a king on rank {}",
+                            letter,
+                            8 - y
+                        ));
+                    }
+                    let king_pos = BoardPosition::new(king_x.unwrap(), y);
+                    let is_rook = |game: &mut Game, x: BoardPosType| {
+                        game.is_piece_of(BoardPosition::new(x, y), ChessPieceId::Rook, color)
+                    };
+                    let rook_x = match letter.to_ascii_uppercase() {
+                        'K' => (king_pos.x + 1..8).rev().find(|x| is_rook(&mut game, *x)),
+                        'Q' => (0..king_pos.x).find(|x| is_rook(&mut game, *x)),
+                        'A'..='H' => {
+                            let x = letter.to_ascii_uppercase() as BoardPosType - b'A';
+                            Some(x).filter(|x| is_rook(&mut game, *x))
+                        }
+                        _ => return Err(format!("Invalid castling right '{}'", letter)),
+                    };
+                    if rook_x.is_none() {
+                        return Err(format!(
+                            "Castling right '{}' requires a rook of the same color on rank {}",
+                            letter,
+                            8 - y
+                        ));
+                    }
+                    let rook_pos = BoardPosition::new(rook_x.unwrap(), y);
+                    let king_side = rook_pos.x > king_pos.x;
+                    if seen.contains(&(color, king_side)) {
+                        return Err(format!("Castling right '{}' is repeated", letter));
+                    }
+                    seen.push((color, king_side));
+
+                    // Only the standard positions can be played without the Chess960 rules
+                    if !"KQkq".contains(letter)
+                        || king_pos.x != 4
+                        || (rook_pos.x != 0 && rook_pos.x != 7)
+                    {
+                        game.chess960 = true;
+                    }
+                    game.get_board_ref(king_pos)
+                        .unwrap()
+                        .as_mut()
+                        .unwrap()
+                        .moved = false;
+                    game.get_board_ref(rook_pos)
+                        .unwrap()
+                        .as_mut()
+                        .unwrap()
+                        .moved = false;
+                }
+            }
+
+            // En passant target square
+            if fields[3] != "-" {
+                let target = match BoardPosition::from_algebraic_notation(fields[3]) {
+                    Ok(pos) => pos,
+                    Err(_) => return Err(format!("Invalid en passant square '{}'", fields[3])),
+                };
+                // The pawn that just moved two forward belongs to the player who is not moving
+                let (pawn_color, target_y, direction) = if game.turn == ChessPieceColor::White {
+                    (ChessPieceColor::Black, 2, 1)
+                } else {
+                    (ChessPieceColor::White, 5, -1)
+                };
+                if target.y != target_y {
+                    return Err(format!(
+                        "En passant square '{}' is on the wrong rank for the side to move",
+                        fields[3]
+                    ));
+                }
+                let from_pos =
+                    BoardPosition::new(target.x, (target.y as i32 - direction) as BoardPosType);
+                let to_pos =
+                    BoardPosition::new(target.x, (target.y as i32 + direction) as BoardPosType);
+                if !game.is_piece_of(to_pos, ChessPieceId::Pawn, pawn_color)
+                    || game.get_board_piece_clone(target).is_some()
+                    || game.get_board_piece_clone(from_pos).is_some()
+                {
+                    return Err(format!(
+                        "En passant square '{}' does not follow a pawn moving two forward",
+                        fields[3]
+                    ));
+                }
+                game.last_move = Some(BoardMove { from_pos, to_pos });
+                game.last_move_passant = true;
+            }
+
+            // Halfmove clock and fullmove number are optional
+            if fields.len() == 6 {
+                let half_moves: i64 = match fields[4].parse::<u32>() {
+                    Ok(value) => value as i64,
+                    Err(_) => return Err(format!("Invalid halfmove clock '{}'", fields[4])),
+                };
+                game.move_count_left = 100 - half_moves;
+                game.full_move_number = match fields[5].parse::<u32>() {
+                    Ok(value) if value > 0 => value,
+                    _ => return Err(format!("Invalid fullmove number '{}'", fields[5])),
+                };
+            }
+
+            return Ok(game);
+        }
+
+        // Get the position in Forsyth-Edwards Notation
+        pub fn to_fen(&self) -> String {
+            let mut fen = String::new();
+
+            // Piece placement, starting from rank 8
+            for y in 0..8 {
+                let mut empty_squares = 0;
+                for x in 0..8 {
+                    match self.board[x + 8 * y] {
+                        Some(piece) => {
+                            if empty_squares > 0 {
+                                fen.push_str(&empty_squares.to_string());
+                                empty_squares = 0;
+                            }
+                            let letter = piece.id.get_letter();
+                            if piece.color == ChessPieceColor::White {
+                                fen.push(letter);
+                            } else {
+                                fen.push(letter.to_ascii_lowercase());
+                            }
+                        }
+                        None => empty_squares += 1,
+                    }
+                }
+                if empty_squares > 0 {
+                    fen.push_str(&empty_squares.to_string());
+                }
+                if y < 7 {
+                    fen.push('/');
+                }
+            }
+
+            // Side to move
+            if self.turn == ChessPieceColor::White {
+                fen.push_str(" w ");
+            } else {
+                fen.push_str(" b ");
+            }
+
+            fen.push_str(&self.get_castling_field(false));
+
+            // En passant target square, the square the pawn jumped over
+            fen.push(' ');
+            match self.get_passant_target() {
+                Some(pos) => fen.push_str(&pos.to_algebraic_notation()),
+                None => fen.push('-'),
+            }
+
+            // Halfmove clock and fullmove number
+            fen.push_str(&format!(
+                " {} {}",
+                100 - self.move_count_left,
+                self.full_move_number
+            ));
+            return fen;
+        }
+
+        // Get the position in Shredder-FEN, which is FEN with the castling rights written as
+        // the files of the rooks, ex. "HAha" instead of "KQkq"
+        pub fn to_shredder_fen(&self) -> String {
+            let fen = self.to_fen();
+            let mut fields: Vec<&str> = fen.split(' ').collect();
+            let castling = self.get_castling_field(true);
+            fields[2] = &castling;
+            return fields.join(" ");
+        }
+
+        // Get the castling rights field of FEN. In X-FEN the file of the rook is only
+        // written if there is another rook further out on the same side
+        fn get_castling_field(&self, shredder: bool) -> String {
+            let mut castling = String::new();
+            let castling_rights = self.get_castling_rights();
+            for (i, letter) in ['K', 'Q', 'k', 'q'].iter().enumerate() {
+                if castling_rights & (1 << i) == 0 {
+                    continue;
+                }
+                let color = if i < 2 {
+                    ChessPieceColor::White
+                } else {
+                    ChessPieceColor::Black
+                };
+                let direction = if i % 2 == 0 { 1 } else { -1 };
+                let king_pos = self.get_castling_king(color).unwrap();
+                let rook_pos = self.get_castling_rook(king_pos, direction).unwrap();
+                let is_outermost = (0..8).all(|x| {
+                    let piece = self.board[x + 8 * rook_pos.y as usize];
+                    (x as i32 - rook_pos.x as i32) * direction <= 0
+                        || piece.is_none()
+                        || piece.unwrap().id != ChessPieceId::Rook
+                        || piece.unwrap().color != color
+                });
+                if shredder || !is_outermost {
+                    let file = (b'A' + rook_pos.x) as char;
+                    if color == ChessPieceColor::White {
+                        castling.push(file);
+                    } else {
+                        castling.push(file.to_ascii_lowercase());
+                    }
+                } else {
+                    castling.push(*letter);
+                }
+            }
+            if castling.is_empty() {
+                castling.push('-');
+            }
+            return castling;
+        }
+
+        // Get the square a pawn jumped over if the last move was a pawn moving two forward
+        fn get_passant_target(&self) -> Option<BoardPosition> {
+            if !self.last_move_passant || self.last_move.is_none() {
+                return None;
+            }
+            let last_move = self.last_move.unwrap();
+            return Some(BoardPosition::new(
+                last_move.from_pos.x,
+                (last_move.from_pos.y + last_move.to_pos.y) / 2,
+            ));
+        }
+
         // Move a piece using algebraic notation
-        pub fn algebraic_notation_move(
-            &mut self,
-            text: String,
-        ) -> Result<Option<BoardMove>, String> {
+        pub fn algebraic_notation_move(&mut self, text: String) -> Result<PlayedMove, MoveError> {
             // Make sure input is not too long
             if text.len() < 2 || text.len() > 10 {
-                return Err("Invalid input size".to_string());
+                return Err(MoveError::InvalidNotation);
             }
 
-            // Remove unnecesary letters
-            let text = text.replace(&['(', ')', ',', '\"', '.', ';', 'X', 'x', ':', '='][..], "");
+            // Remove unnecesary letters, including check and annotation symbols
+            let text = text.replace(
+                &[
+                    '(', ')', ',', '\"', '.', ';', 'X', 'x', ':', '=', '+', '#', '!', '?',
+                ][..],
+                "",
+            );
+
+            // Casteling is done by moving the king two squares to the side
+            if text == "O-O" || text == "0-0" || text == "O-O-O" || text == "0-0-0" {
+                let king_pos = self.get_king_position(self.turn);
+                if king_pos.is_none() {
+                    return Err(MoveError::NoKing);
+                }
+                let king_pos = king_pos.unwrap();
+                // With the Chess960 rules the king moves to the rook it castles with
+                if self.chess960 {
+                    let direction = if text.len() == 3 { 1 } else { -1 };
+                    let rook_pos = self.get_castling_rook(king_pos, direction);
+                    if rook_pos.is_none() || self.get_castling_king(self.turn) != Some(king_pos) {
+                        return Err(MoveError::NoCastlingRook);
+                    }
+                    let board_move = BoardMove {
+                        from_pos: king_pos,
+                        to_pos: rook_pos.unwrap(),
+                    };
+                    return self.play_move(board_move, None);
+                }
+                let to_x = if text.len() == 3 {
+                    king_pos.x as i32 + 2
+                } else {
+                    king_pos.x as i32 - 2
+                };
+                if !(0..8).contains(&to_x) {
+                    return Err(MoveError::NoMatchingPiece);
+                }
+                let board_move = BoardMove::new(king_pos.x, king_pos.y, to_x as u8, king_pos.y);
+                return self.play_move(board_move, None);
+            }
 
             #[allow(unused_assignments)]
             let mut piece_type: Option<ChessPieceId> = None;
@@ -328,7 +1053,7 @@ pub mod chess_game {
 
             let mut char_vec: Vec<char> = text.chars().collect();
             if char_vec.len() < 2 {
-                return Err("Could not parse move".to_string());
+                return Err(MoveError::InvalidNotation);
             }
 
             // See if player is trying to promote piece
@@ -339,320 +1064,1412 @@ pub mod chess_game {
                 char_vec.resize(char_vec.len() - 1, ' ');
             }
 
-            // See if player specifies a specific piece to move
-            let move_piece_result = self.get_piece_type_from_letter(char_vec[0]);
-            if move_piece_result.is_ok() {
-                piece_type = Some(move_piece_result.unwrap());
-                char_vec.remove(0);
+            // See if player specifies a specific piece to move
+            let move_piece_result = self.get_piece_type_from_letter(char_vec[0]);
+            if move_piece_result.is_ok() {
+                piece_type = Some(move_piece_result.unwrap());
+                char_vec.remove(0);
+            } else {
+                piece_type = Some(ChessPieceId::Pawn);
+            }
+
+            if char_vec.len() < 2 {
+                return Err(MoveError::InvalidNotation);
+            }
+
+            if char_vec.len() == 2 {
+                to_x_input = Some(BoardPosition::get_coordinate_from_letter(char_vec[0])?);
+                to_y_input = Some(BoardPosition::get_coordinate_from_number(char_vec[1])?);
+            } else if char_vec.len() == 3 {
+                let result_letter = BoardPosition::get_coordinate_from_letter(char_vec[0]);
+                let result_number = BoardPosition::get_coordinate_from_number(char_vec[0]);
+                if result_letter.is_ok() {
+                    #[allow(unused_assignments)]
+                    {
+                        from_x_input = Some(result_letter.unwrap());
+                    }
+                } else if result_number.is_ok() {
+                    #[allow(unused_assignments)]
+                    {
+                        from_y_input = Some(result_number.unwrap());
+                    }
+                }
+                to_x_input = Some(BoardPosition::get_coordinate_from_letter(char_vec[1])?);
+                to_y_input = Some(BoardPosition::get_coordinate_from_number(char_vec[2])?);
+            } else if char_vec.len() == 4 {
+                from_x_input = Some(BoardPosition::get_coordinate_from_letter(char_vec[0])?);
+                from_y_input = Some(BoardPosition::get_coordinate_from_number(char_vec[1])?);
+                to_x_input = Some(BoardPosition::get_coordinate_from_letter(char_vec[2])?);
+                to_y_input = Some(BoardPosition::get_coordinate_from_number(char_vec[3])?);
+            } else {
+                return Err(MoveError::InvalidNotation);
+            }
+
+            let input = (from_x_input, from_y_input, to_x_input, to_y_input);
+            let board_move = if let (Some(from_x), Some(from_y), Some(to_x), Some(to_y)) = input {
+                // It is speciefied exactly which piece should move and where
+                Some(BoardMove::new(from_x, from_y, to_x, to_y))
+            } else {
+                // Find the moving piece, with the limits set by the input
+                let board_moves = self.find_moves_to(
+                    piece_type.unwrap(),
+                    BoardPosition::new(to_x_input.unwrap(), to_y_input.unwrap()),
+                    from_x_input,
+                    from_y_input,
+                    promote_piece,
+                );
+                // Make sure there are not multiple pieces that can do that move
+                if board_moves.len() > 1 {
+                    return Err(MoveError::AmbiguousMove);
+                }
+                board_moves.first().copied()
+            };
+
+            let board_move = match board_move {
+                Some(board_move) => board_move,
+                None => return Err(MoveError::NoMatchingPiece),
+            };
+            if !self.will_require_promotion(board_move) && promote_piece.is_some() {
+                return Err(MoveError::InvalidPromotion);
+            }
+            return self.play_move(board_move, promote_piece);
+        }
+
+        // Move a piece and get a description of what changed on the board
+        pub fn play_move(
+            &mut self,
+            board_move: BoardMove,
+            promote_piece: Option<ChessPieceId>,
+        ) -> Result<PlayedMove, MoveError> {
+            self.move_piece(board_move, true, promote_piece)?;
+            return Ok(self.history.last().unwrap().played_move);
+        }
+
+        // Compare the board before and after a move to find what the move changed
+        fn get_played_move(
+            &self,
+            board_before: &[Option<ChessPiece>; 8 * 8],
+            board_move: BoardMove,
+            promote_piece: Option<ChessPieceId>,
+        ) -> PlayedMove {
+            let color = board_before[Self::get_index(board_move.from_pos)]
+                .unwrap()
+                .color;
+            let mut played_move = PlayedMove {
+                board_move,
+                piece: board_before[Self::get_index(board_move.from_pos)].unwrap(),
+                promote_piece,
+                captured: None,
+                rook_move: None,
+            };
+
+            // Find captured pieces and moved rooks
+            let mut rook_from: Option<BoardPosition> = None;
+            let mut rook_to: Option<BoardPosition> = None;
+            for x in 0..8 {
+                for y in 0..8 {
+                    let pos = BoardPosition::new(x, y);
+                    let before = board_before[Self::get_index(pos)];
+                    let after = self.board[Self::get_index(pos)];
+                    if let Some(before) = before {
+                        if before.color != color && after.is_none_or(|after| after.color == color) {
+                            played_move.captured = Some((pos, before));
+                        }
+                        if pos != board_move.from_pos
+                            && before.id == ChessPieceId::Rook
+                            && before.color == color
+                            && after.is_none()
+                        {
+                            rook_from = Some(pos);
+                        }
+                    }
+                    if pos != board_move.to_pos
+                        && before.is_none()
+                        && after.is_some_and(|after| after.id == ChessPieceId::Rook)
+                    {
+                        rook_to = Some(pos);
+                    }
+                }
+            }
+            if let (Some(from_pos), Some(to_pos)) = (rook_from, rook_to) {
+                played_move.rook_move = Some(BoardMove { from_pos, to_pos });
+            }
+            return played_move;
+        }
+
+        // Take back the last move, returns None if there is nothing to take back
+        pub fn undo_move(&mut self) -> Option<PlayedMove> {
+            let record = self.history.pop()?;
+            self.unmake_move(record);
+            self.termination = None;
+            self.redo_stack.push(record);
+            return Some(record.played_move);
+        }
+
+        // Play a move that was taken back again, returns None if there is nothing to play
+        pub fn redo_move(&mut self) -> Option<PlayedMove> {
+            let record = self.redo_stack.pop()?;
+            let redo_stack = std::mem::take(&mut self.redo_stack);
+            let played_move = record.played_move;
+            let result = self.move_piece(played_move.board_move, false, played_move.promote_piece);
+            self.redo_stack = redo_stack;
+            if result.is_err() {
+                return None;
+            }
+            return Some(played_move);
+        }
+
+        // Play a legal move in place, without validating it or adding it to the history.
+        // Returns a record that unmake_move takes the move back with, so a search can
+        // try out moves without copying the game. A missing promotion piece means a queen
+        pub fn make_move(&mut self, chess_move: ChessMove) -> MoveRecord {
+            let board_move = chess_move.board_move;
+            let from = Self::get_index(board_move.from_pos);
+            let to = Self::get_index(board_move.to_pos);
+            let piece = self.board[from].unwrap();
+            let mut bitboards = self.get_bitboards();
+            let castling_rights = self.get_castling_rights();
+            let passant_key = self.get_passant_key();
+            let mut record = MoveRecord {
+                played_move: PlayedMove {
+                    board_move,
+                    piece,
+                    promote_piece: None,
+                    captured: None,
+                    rook_move: None,
+                },
+                last_move: self.last_move,
+                last_move_passant: self.last_move_passant,
+                move_count_left: self.move_count_left,
+                full_move_number: self.full_move_number,
+                hash: self.hash(),
+            };
+
+            record.played_move.rook_move = self.get_castle_rook_move(board_move);
+            if let Some(rook_move) = record.played_move.rook_move {
+                // The king and rook can end on each others squares in Chess960,
+                // so both are taken off the board before they are put back
+                let king_move = record.played_move.get_piece_move();
+                let rook_from = Self::get_index(rook_move.from_pos);
+                let mut rook = self.board[rook_from].take().unwrap();
+                let mut king = self.board[from].take().unwrap();
+                bitboards.remove_piece(rook_from);
+                bitboards.remove_piece(from);
+                rook.moved = true;
+                king.moved = true;
+                let rook_to = Self::get_index(rook_move.to_pos);
+                let king_to = Self::get_index(king_move.to_pos);
+                self.board[rook_to] = Some(rook);
+                self.board[king_to] = Some(king);
+                bitboards.add_piece(rook_to, rook.id, rook.color);
+                bitboards.add_piece(king_to, king.id, king.color);
+            } else {
+                // A pawn moving diagonally to an empty square takes en passant
+                let mut captured_pos = board_move.to_pos;
+                if piece.id == ChessPieceId::Pawn
+                    && board_move.from_pos.x != board_move.to_pos.x
+                    && self.board[to].is_none()
+                {
+                    captured_pos = BoardPosition::new(board_move.to_pos.x, board_move.from_pos.y);
+                }
+                let captured_index = Self::get_index(captured_pos);
+                if self.board[captured_index].is_some() {
+                    let captured = self.board[captured_index].take().unwrap();
+                    record.played_move.captured = Some((captured_pos, captured));
+                    bitboards.remove_piece(captured_index);
+                }
+                self.shift_piece(board_move, &mut bitboards);
+            }
+
+            if piece.id == ChessPieceId::Pawn
+                && board_move.to_pos.y == Self::get_promotion_row(piece.color)
+            {
+                let promote_piece = chess_move.promote_piece.unwrap_or(ChessPieceId::Queen);
+                self.board[to].as_mut().unwrap().id = promote_piece;
+                bitboards.remove_piece(to);
+                bitboards.add_piece(to, promote_piece, piece.color);
+                record.played_move.promote_piece = Some(promote_piece);
+            }
+
+            self.last_move = Some(board_move);
+            self.last_move_passant = piece.id == ChessPieceId::Pawn
+                && (board_move.to_pos.y as i32 - board_move.from_pos.y as i32).abs() == 2;
+            if piece.id == ChessPieceId::Pawn || record.played_move.captured.is_some() {
+                self.reset_move_count_left();
+            }
+            self.end_turn();
+            self.bitboards.set(bitboards);
+            self.update_hash(&record, castling_rights, passant_key);
+            return record;
+        }
+
+        // Take back a move done with make_move, the record must be from the last move made
+        pub fn unmake_move(&mut self, record: MoveRecord) {
+            let played_move = record.played_move;
+            let board_move = played_move.board_move;
+            let from = Self::get_index(board_move.from_pos);
+            let board_changed = self.board_changed.get();
+            let mut bitboards = self.get_bitboards();
+
+            // Castling only moves unmoved rooks, so the rook gets its moved flag reset.
+            // The rook is put back after the king is taken off, since in Chess960 the
+            // king can end on the square the rook came from
+            let mut rook = None;
+            if let Some(rook_move) = played_move.rook_move {
+                let rook_to = Self::get_index(rook_move.to_pos);
+                rook = self.board[rook_to].take();
+                rook.as_mut().unwrap().moved = false;
+                bitboards.remove_piece(rook_to);
+            }
+            let to = Self::get_index(played_move.get_piece_move().to_pos);
+            self.board[to] = None;
+            bitboards.remove_piece(to);
+            if rook.is_some() {
+                let rook_from = Self::get_index(played_move.rook_move.unwrap().from_pos);
+                self.board[rook_from] = rook;
+                bitboards.add_piece(rook_from, ChessPieceId::Rook, played_move.piece.color);
+            }
+            self.board[from] = Some(played_move.piece);
+            bitboards.add_piece(from, played_move.piece.id, played_move.piece.color);
+            if let Some((captured_pos, captured_piece)) = played_move.captured {
+                let captured_index = Self::get_index(captured_pos);
+                self.board[captured_index] = Some(captured_piece);
+                bitboards.add_piece(captured_index, captured_piece.id, captured_piece.color);
+            }
+
+            self.bitboards.set(bitboards);
+            // If the board was changed after the move the hash has to be calculated again
+            if !board_changed {
+                self.hash.set(Some(record.hash));
+            }
+            self.turn = played_move.piece.color;
+            self.last_move = record.last_move;
+            self.last_move_passant = record.last_move_passant;
+            self.move_count_left = record.move_count_left;
+            self.full_move_number = record.full_move_number;
+        }
+
+        // Move a piece on the board and the bitboards, used by make_move
+        fn shift_piece(&mut self, board_move: BoardMove, bitboards: &mut Bitboards) {
+            let from = Self::get_index(board_move.from_pos);
+            let to = Self::get_index(board_move.to_pos);
+            self.board[to] = self.board[from].take();
+            self.board[to].as_mut().unwrap().moved = true;
+            bitboards.move_piece(from, to);
+        }
+
+        // Get all moves played so far, the first move is first
+        pub fn get_history(&self) -> &[MoveRecord] {
+            return &self.history;
+        }
+
+        // Iterate all positions of the game, from the first position to the current one.
+        // The positions do not have the moves that led to them
+        pub fn get_past_positions(&self) -> PastPositions {
+            let mut game = self.clone();
+            game.redo_stack.clear();
+            while game.undo_move().is_some() {}
+            return PastPositions {
+                game,
+                started: false,
+            };
+        }
+
+        // Find all moves a piece type can do to a position
+        // If from_x or from_y is specified, only pieces on that file or rank are considered
+        fn find_moves_to(
+            &mut self,
+            piece_type: ChessPieceId,
+            to_pos: BoardPosition,
+            from_x_input: Option<BoardPosType>,
+            from_y_input: Option<BoardPosType>,
+            promote_piece: Option<ChessPieceId>,
+        ) -> Vec<BoardMove> {
+            let mut board_moves: Vec<BoardMove> = Vec::new();
+            for chess_move in self.generate_legal_moves() {
+                let from_pos = chess_move.board_move.from_pos;
+                // If from position is speciefied, make sure it follows that
+                if from_x_input.is_some() && from_x_input.unwrap() != from_pos.x {
+                    continue;
+                }
+                if from_y_input.is_some() && from_y_input.unwrap() != from_pos.y {
+                    continue;
+                }
+                // Castling in Chess960 is written as the king taking its own rook, but it is
+                // only found by writing it as castling
+                if self.is_pieces_same_color(from_pos, to_pos) {
+                    continue;
+                }
+                // Promotions are separate moves, so pick the one with the right piece
+                if chess_move.board_move.to_pos == to_pos
+                    && self.is_piece_of(from_pos, piece_type, self.turn)
+                    && (chess_move.promote_piece.is_none()
+                        || chess_move.promote_piece == promote_piece)
+                {
+                    board_moves.push(chess_move.board_move);
+                }
+            }
+            return board_moves;
+        }
+
+        // Get the move in standard algebraic notation, ex. "Nbd7", "exd6", "O-O" or "e8=Q+"
+        // The move should be valid in the current position
+        pub fn move_to_san(
+            &mut self,
+            board_move: BoardMove,
+            promote_piece: Option<ChessPieceId>,
+        ) -> String {
+            let from_piece = self.get_board_piece_clone(board_move.from_pos);
+            let to_piece = self.get_board_piece_clone(board_move.to_pos);
+            let from_text = board_move.from_pos.to_algebraic_notation();
+            let to_text = board_move.to_pos.to_algebraic_notation();
+            if from_piece.is_none() {
+                return from_text + to_text.as_str();
+            }
+            let piece = from_piece.unwrap();
+            let dx = board_move.to_pos.x as i32 - board_move.from_pos.x as i32;
+            let is_capture = (to_piece.is_some() && to_piece.unwrap().color != piece.color)
+                || (piece.id == ChessPieceId::Pawn && dx != 0);
+
+            let mut san = String::new();
+            if self.get_castle_rook_move(board_move).is_some() {
+                // Castling
+                if dx > 0 {
+                    san.push_str("O-O");
+                } else {
+                    san.push_str("O-O-O");
+                }
+            } else if piece.id == ChessPieceId::Pawn {
+                if is_capture {
+                    san.push(from_text.chars().next().unwrap());
+                    san.push('x');
+                }
+                san.push_str(&to_text);
+                if let Some(promote_piece) = promote_piece {
+                    if self.will_require_promotion(board_move) {
+                        san.push('=');
+                        san.push(promote_piece.get_letter());
+                    }
+                }
+            } else {
+                san.push(piece.id.get_letter());
+
+                // Specify the file, rank or both if other pieces of the same type can move there
+                let other_moves: Vec<BoardMove> = self
+                    .find_moves_to(piece.id, board_move.to_pos, None, None, promote_piece)
+                    .into_iter()
+                    .filter(|other| other.from_pos != board_move.from_pos)
+                    .collect();
+                if !other_moves.is_empty() {
+                    let same_file = other_moves
+                        .iter()
+                        .any(|other| other.from_pos.x == board_move.from_pos.x);
+                    let same_rank = other_moves
+                        .iter()
+                        .any(|other| other.from_pos.y == board_move.from_pos.y);
+                    if !same_file {
+                        san.push(from_text.chars().next().unwrap());
+                    } else if !same_rank {
+                        san.push(from_text.chars().nth(1).unwrap());
+                    } else {
+                        san.push_str(&from_text);
+                    }
+                }
+
+                if is_capture {
+                    san.push('x');
+                }
+                san.push_str(&to_text);
+            }
+
+            // Add check or check mate, the move is tried out in place and taken back
+            let is_legal = self
+                .generate_legal_moves_from(board_move.from_pos)
+                .iter()
+                .any(|chess_move| chess_move.board_move == board_move);
+            if is_legal {
+                let record = self.make_move(ChessMove::new(board_move, promote_piece));
+                if self.is_check_mate() {
+                    san.push('#');
+                } else if self.is_check().is_some() {
+                    san.push('+');
+                }
+                self.unmake_move(record);
+            }
+            return san;
+        }
+
+        // Set all postitions on the board to none
+        pub fn empty_board(&mut self) {
+            for i in 0..self.board.len() {
+                self.board[i] = None;
+            }
+            self.board_changed.set(true);
+            // Moves played on the old board cannot be taken back
+            self.history.clear();
+            self.redo_stack.clear();
+            self.termination = None;
+        }
+
+        pub fn print_board(&mut self) {
+            self.print_board_with_possible_moves(None);
+        }
+
+        pub fn print_board_with_possible_moves(
+            &mut self,
+            possible_moves_from_pos: Option<BoardPosition>,
+        ) {
+            let mut possible_moves: Vec<ChessMove> = Vec::new();
+            if let Some(from_pos) = possible_moves_from_pos {
+                possible_moves = self.generate_legal_moves_from(from_pos);
+            }
+            println!("  a b c d e f g h");
+            for y in 0..8 {
+                print!("{} ", 8 - y);
+                for x in 0..8 {
+                    let board_move;
+                    if possible_moves_from_pos.is_some() {
+                        board_move = BoardMove::new(
+                            possible_moves_from_pos.unwrap().x,
+                            possible_moves_from_pos.unwrap().y,
+                            x,
+                            y,
+                        );
+                    } else {
+                        board_move = BoardMove::new(0, 0, 0, 0);
+                    }
+
+                    let background_color;
+                    if possible_moves_from_pos.is_some()
+                        && possible_moves
+                            .iter()
+                            .any(|chess_move| chess_move.board_move == board_move)
+                    {
+                        // Color square red if piece can move there
+                        background_color = ColorTerminal::Red;
+                    } else if possible_moves_from_pos.is_some()
+                        && board_move.from_pos.x == x
+                        && board_move.from_pos.y == y
+                    {
+                        background_color = ColorTerminal::Green;
+                    } else if (x + y) % 2 == 0 {
+                        background_color = ColorTerminal::LightBlue;
+                    } else {
+                        background_color = ColorTerminal::Blue;
+                    }
+                    // Get a reference to a position on the board
+                    let board_ref = *self.get_board_ref(BoardPosition::new(x, y)).unwrap();
+                    if board_ref.is_none() {
+                        // If there is nothing there, just print two spaces
+                        print_color("  ", ColorTerminal::White, background_color);
+                    } else {
+                        if board_ref.as_ref().unwrap().color == ChessPieceColor::Black {
+                            print_color(
+                                board_ref
+                                    .as_ref()
+                                    .unwrap()
+                                    .unicode_char
+                                    .to_string()
+                                    .as_str(),
+                                ColorTerminal::Black,
+                                background_color,
+                            );
+                            print_color(" ", ColorTerminal::Black, background_color);
+                        } else {
+                            print_color(
+                                board_ref
+                                    .as_ref()
+                                    .unwrap()
+                                    .unicode_char
+                                    .to_string()
+                                    .as_str(),
+                                ColorTerminal::White,
+                                background_color,
+                            );
+                            print_color(" ", ColorTerminal::White, background_color);
+                        }
+                    }
+                }
+                print!("{} ", 8 - y);
+                println!();
+            }
+            println!("  a b c d e f g h");
+        }
+
+        // Get a reference to a coordinate on the board
+        pub fn get_board_ref(
+            &mut self,
+            pos: BoardPosition,
+        ) -> Result<&mut Option<ChessPiece>, MoveError> {
+            if self.inside_board(pos).is_err() {
+                return Err(MoveError::OutsideBoard);
+            }
+            self.board_changed.set(true);
+            return Ok(&mut self.board[Self::get_index(pos)]);
+        }
+
+        // Get the index of a position in the board array
+        fn get_index(pos: BoardPosition) -> usize {
+            return (pos.x + 8 * pos.y) as usize;
+        }
+
+        // Copy the position without the history, ex. to search it
+        pub fn clone_position(&self) -> Game {
+            return Game {
+                board: self.board,
+                turn: self.turn,
+                last_move: self.last_move,
+                last_move_passant: self.last_move_passant,
+                move_count_left: self.move_count_left,
+                full_move_number: self.full_move_number,
+                history: Vec::new(),
+                redo_stack: Vec::new(),
+                termination: self.termination,
+                bitboards: self.bitboards.clone(),
+                hash: self.hash.clone(),
+                board_changed: self.board_changed.clone(),
+                chess960: self.chess960,
+            };
+        }
+
+        // Get the position of an index in the board array
+        fn get_position(index: usize) -> BoardPosition {
+            return BoardPosition::new((index % 8) as BoardPosType, (index / 8) as BoardPosType);
+        }
+
+        // Get a clone of a position on the board
+        pub fn get_board_piece_clone(&mut self, pos: BoardPosition) -> Option<ChessPiece> {
+            self.inside_board(pos).unwrap();
+            return self.board[Self::get_index(pos)];
+        }
+
+        // Set a position on the board
+        pub fn set_pos(&mut self, pos: BoardPosition, id: ChessPieceId, color: ChessPieceColor) {
+            *self.get_board_ref(pos).unwrap() = Some(ChessPiece::new(id, color));
+        }
+
+        pub fn set_pos_to_none(&mut self, pos: BoardPosition) {
+            *self.get_board_ref(pos).unwrap() = None;
+        }
+
+        // Returns if the game is still running, or how it ended
+        pub fn game_status(&mut self) -> GameStatus {
+            if let Some(termination) = self.termination {
+                return termination;
+            }
+            if self.generate_legal_moves().is_empty() {
+                if self.is_check().is_some() {
+                    return GameStatus::Checkmate {
+                        winner: self.get_opponent_color(),
+                    };
+                }
+                return GameStatus::Stalemate;
+            }
+            if self.is_insufficient_material() {
+                return GameStatus::InsufficientMaterial;
+            }
+            if self.is_fivefold_repetition() {
+                return GameStatus::Repetition;
+            }
+            if self.max_move_count_reached() {
+                return GameStatus::FiftyMove;
+            }
+            return GameStatus::Ongoing;
+        }
+
+        // Returns true if the game is over
+        pub fn game_is_over(&mut self) -> bool {
+            return self.game_status().is_over();
+        }
+
+        // The player of a color gives up, the other player wins
+        pub fn resign(&mut self, color: ChessPieceColor) -> Result<(), String> {
+            let winner = if color == ChessPieceColor::White {
+                ChessPieceColor::Black
+            } else {
+                ChessPieceColor::White
+            };
+            return self.terminate(GameStatus::Resignation { winner });
+        }
+
+        // The player of a color ran out of time, the other player wins if it can still mate
+        pub fn time_out(&mut self, color: ChessPieceColor) -> Result<(), String> {
+            let winner = if color == ChessPieceColor::White {
+                ChessPieceColor::Black
+            } else {
+                ChessPieceColor::White
+            };
+            if !self.can_mate(winner) {
+                return self.terminate(GameStatus::TimeoutVsInsufficientMaterial);
+            }
+            return self.terminate(GameStatus::Timeout { winner });
+        }
+
+        // Both players agreed to a draw
+        pub fn agree_draw(&mut self) -> Result<(), String> {
+            return self.terminate(GameStatus::Agreement);
+        }
+
+        fn terminate(&mut self, status: GameStatus) -> Result<(), String> {
+            if self.game_is_over() {
+                return Err("The game is already over".to_string());
+            }
+            self.termination = Some(status);
+            return Ok(());
+        }
+
+        fn get_opponent_color(&self) -> ChessPieceColor {
+            if self.turn == ChessPieceColor::White {
+                return ChessPieceColor::Black;
+            } else {
+                return ChessPieceColor::White;
+            }
+        }
+
+        // Returns true if neither player can checkmate, whatever moves are played.
+        // That is the case with only kings and at most one knight or bishop,
+        // or with only kings and bishops that all stand on the same square color
+        pub fn is_insufficient_material(&self) -> bool {
+            let mut minor_pieces = 0;
+            let mut knights = 0;
+            let mut bishop_square_colors = [false; 2];
+            for x in 0..8 {
+                for y in 0..8 {
+                    let piece = self.board[x + 8 * y];
+                    if piece.is_none() {
+                        continue;
+                    }
+                    match piece.unwrap().id {
+                        ChessPieceId::King => {}
+                        ChessPieceId::Knight => {
+                            minor_pieces += 1;
+                            knights += 1;
+                        }
+                        ChessPieceId::Bishop => {
+                            minor_pieces += 1;
+                            bishop_square_colors[(x + y) % 2] = true;
+                        }
+                        _ => return false,
+                    }
+                }
+            }
+            if minor_pieces <= 1 {
+                return true;
+            }
+            return knights == 0 && !(bishop_square_colors[0] && bishop_square_colors[1]);
+        }
+
+        // Returns true if a color can mate by some series of legal moves, with the help of
+        // the opponent's pieces. A king with a knight needs an opponent piece other than a queen
+        // to block the king in, and bishops on one square color need an opponent knight, pawn
+        // or a bishop on the other square color
+        pub fn can_mate(&self, color: ChessPieceColor) -> bool {
+            let mut knights = 0;
+            let mut bishops = 0;
+            let mut bishop_square_colors = [false; 2];
+            let mut opponent_can_block = false;
+            let mut opponent_knights_or_pawns = false;
+            for x in 0..8 {
+                for y in 0..8 {
+                    let piece = match self.board[x + 8 * y] {
+                        Some(piece) => piece,
+                        None => continue,
+                    };
+                    if piece.id == ChessPieceId::Bishop {
+                        bishop_square_colors[(x + y) % 2] = true;
+                    }
+                    if piece.color == color {
+                        match piece.id {
+                            ChessPieceId::King => {}
+                            ChessPieceId::Knight => knights += 1,
+                            ChessPieceId::Bishop => bishops += 1,
+                            _ => return true,
+                        }
+                    } else {
+                        match piece.id {
+                            ChessPieceId::King | ChessPieceId::Queen => {}
+                            ChessPieceId::Knight | ChessPieceId::Pawn => {
+                                opponent_can_block = true;
+                                opponent_knights_or_pawns = true;
+                            }
+                            _ => opponent_can_block = true,
+                        }
+                    }
+                }
+            }
+            if knights == 0 && bishops == 0 {
+                return false;
+            }
+            if knights == 1 && bishops == 0 {
+                return opponent_can_block;
+            }
+            if knights == 0 {
+                return (bishop_square_colors[0] && bishop_square_colors[1])
+                    || opponent_knights_or_pawns;
+            }
+            return true;
+        }
+
+        // Get how many times the current position has occurred, including now
+        pub fn get_repetition_count(&self) -> u32 {
+            let hash = self.hash();
+
+            // Positions before a pawn move or capture can never occur again,
+            // the history holds the hash of the position before each move
+            let reversible_moves = (100 - self.move_count_left).max(0) as usize;
+            let past_positions = self.history.iter().rev().take(reversible_moves);
+            let repetitions = past_positions.filter(|record| record.hash == hash).count();
+            return repetitions as u32 + 1;
+        }
+
+        // Returns true if a player may claim a draw, since the position has occurred three times
+        pub fn is_threefold_repetition(&self) -> bool {
+            return self.get_repetition_count() >= 3;
+        }
+
+        // Returns true if the game is drawn, since the position has occurred five times
+        pub fn is_fivefold_repetition(&self) -> bool {
+            return self.get_repetition_count() >= 5;
+        }
+
+        // Claim a draw by threefold repetition, the player to move may do this
+        pub fn claim_draw(&mut self) -> Result<(), String> {
+            if !self.is_threefold_repetition() {
+                return Err("Position has not occurred three times".to_string());
+            }
+            return self.terminate(GameStatus::Repetition);
+        }
+
+        // Returns if there is a winner, and what color it is
+        pub fn get_winner(&mut self) -> Option<ChessPieceColor> {
+            return self.game_status().get_winner();
+        }
+
+        // Get the number of the current move, starts at 1 and is increased after black moves
+        pub fn get_full_move_number(&self) -> u32 {
+            return self.full_move_number;
+        }
+
+        // Get the number of moves since the last pawn move or capture, counting both players
+        pub fn get_halfmove_clock(&self) -> u32 {
+            return (100 - self.move_count_left) as u32;
+        }
+
+        // Get the position of the king of a color
+        pub fn get_king_position(&mut self, color: ChessPieceColor) -> Option<BoardPosition> {
+            for x in 0..8 {
+                for y in 0..8 {
+                    let pos = BoardPosition::new(x, y);
+                    if self.is_piece_of(pos, ChessPieceId::King, color) {
+                        return Some(pos);
+                    }
+                }
+            }
+            return None;
+        }
+
+        // Get the move of a piece that can take the king of the player to move, if there is one
+        pub fn is_check(&mut self) -> Option<BoardMove> {
+            let opponent = self.get_opponent_color();
+            for i in 0..self.board.len() {
+                let piece = self.board[i];
+                if piece.is_some()
+                    && piece.unwrap().id == ChessPieceId::King
+                    && piece.unwrap().color == self.turn
+                {
+                    let attackers = self.get_bitboards().get_attackers(i, opponent);
+                    if attackers != 0 {
+                        return Some(BoardMove {
+                            from_pos: bitboard::get_position(attackers.trailing_zeros() as usize),
+                            to_pos: bitboard::get_position(i),
+                        });
+                    }
+                }
+            }
+            return None;
+        }
+
+        pub fn is_check_mate(&mut self) -> bool {
+            if self.is_check().is_some() && self.get_possible_moves().is_empty() {
+                return true;
+            } else {
+                return false;
+            }
+        }
+
+        pub fn is_stale_mate(&mut self) -> bool {
+            if self.is_check().is_none() && self.get_possible_moves().is_empty() {
+                return true;
+            } else {
+                return false;
+            }
+        }
+
+        // Get all legal moves, a pawn that promotes is only included once
+        pub fn get_possible_moves(&mut self) -> Vec<BoardMove> {
+            let mut board_moves: Vec<BoardMove> = Vec::new();
+            for chess_move in self.generate_legal_moves() {
+                if chess_move.promote_piece.is_none()
+                    || chess_move.promote_piece == Some(ChessPieceId::Queen)
+                {
+                    board_moves.push(chess_move.board_move);
+                }
+            }
+            return board_moves;
+        }
+
+        // Get all legal moves for the player to move
+        pub fn generate_legal_moves(&self) -> Vec<ChessMove> {
+            let mut moves = self.generate_pseudo_legal_moves();
+            moves.retain(|chess_move| !self.leaves_king_in_check(chess_move.board_move));
+            return moves;
+        }
+
+        // Get all legal moves for the piece on a square
+        pub fn generate_legal_moves_from(&self, pos: BoardPosition) -> Vec<ChessMove> {
+            let mut moves = Vec::new();
+            let piece = self.board[Self::get_index(pos)];
+            if piece.is_some() && piece.unwrap().color == self.turn {
+                self.generate_piece_moves(pos, &mut moves);
+            }
+            moves.retain(|chess_move| !self.leaves_king_in_check(chess_move.board_move));
+            return moves;
+        }
+
+        // Get all moves the pieces of the player to move can do,
+        // without checking if they leave the king in check
+        pub fn generate_pseudo_legal_moves(&self) -> Vec<ChessMove> {
+            let mut moves = Vec::with_capacity(64);
+            for i in 0..self.board.len() {
+                let piece = self.board[i];
+                if piece.is_some() && piece.unwrap().color == self.turn {
+                    self.generate_piece_moves(Self::get_position(i), &mut moves);
+                }
+            }
+            return moves;
+        }
+
+        // Count the positions after every sequence of legal moves of a depth,
+        // used to check the move generator against known numbers
+        pub fn perft(&mut self, depth: u32) -> u64 {
+            if depth == 0 {
+                return 1;
+            }
+            let moves = self.generate_legal_moves();
+            if depth == 1 {
+                return moves.len() as u64;
+            }
+            let mut nodes = 0;
+            for chess_move in moves {
+                let record = self.make_move(chess_move);
+                nodes += self.perft(depth - 1);
+                self.unmake_move(record);
+            }
+            return nodes;
+        }
+
+        // Perft split by the first move, to find which move a wrong count comes from
+        pub fn divide(&mut self, depth: u32) -> Vec<(ChessMove, u64)> {
+            let mut counts = Vec::new();
+            if depth == 0 {
+                return counts;
+            }
+            for chess_move in self.generate_legal_moves() {
+                let record = self.make_move(chess_move);
+                counts.push((chess_move, self.perft(depth - 1)));
+                self.unmake_move(record);
+            }
+            return counts;
+        }
+
+        fn generate_piece_moves(&self, from_pos: BoardPosition, moves: &mut Vec<ChessMove>) {
+            let piece = self.board[Self::get_index(from_pos)].unwrap();
+            let bitboards = self.get_bitboards();
+            let from = bitboard::get_square(from_pos);
+            let occupied = bitboards.get_occupied();
+            let targets = match piece.id {
+                ChessPieceId::Pawn => {
+                    self.generate_pawn_moves(from_pos, piece, &bitboards, moves);
+                    return;
+                }
+                ChessPieceId::Knight => bitboard::knight_attacks(from),
+                ChessPieceId::Bishop => bitboard::bishop_attacks(from, occupied),
+                ChessPieceId::Rook => bitboard::rook_attacks(from, occupied),
+                ChessPieceId::Queen => bitboard::queen_attacks(from, occupied),
+                ChessPieceId::King => bitboard::king_attacks(from),
+            };
+            for to in bitboard::get_squares(targets & !bitboards.get_color(piece.color)) {
+                let board_move = BoardMove {
+                    from_pos,
+                    to_pos: bitboard::get_position(to),
+                };
+                moves.push(ChessMove::new(board_move, None));
+            }
+            if piece.id == ChessPieceId::King {
+                self.generate_castle_moves(from_pos, piece, moves);
+            }
+        }
+
+        fn generate_pawn_moves(
+            &self,
+            from_pos: BoardPosition,
+            piece: ChessPiece,
+            bitboards: &Bitboards,
+            moves: &mut Vec<ChessMove>,
+        ) {
+            let from = bitboard::get_square(from_pos);
+            let empty = !bitboards.get_occupied();
+            let mut targets: Bitboard = 0;
+
+            // Move forward, two squares if the pawn has not moved
+            let direction = Self::get_pawn_direction(piece.color);
+            let one_forward = Self::get_offset_position(from_pos, 0, direction)
+                .map_or(0, bitboard::get_bit)
+                & empty;
+            if one_forward != 0 {
+                targets |= one_forward;
+                let two_forward = Self::get_offset_position(from_pos, 0, 2 * direction)
+                    .map_or(0, bitboard::get_bit)
+                    & empty;
+                if !piece.moved {
+                    targets |= two_forward;
+                }
+            }
+
+            // Take diagonally, or en passant on the square the last pawn jumped over
+            let mut takeable = bitboards.get_color(self.get_opponent_color());
+            let passant_target = self.get_passant_target();
+            if let Some(passant_target) = passant_target {
+                takeable |= bitboard::get_bit(passant_target) & empty;
+            }
+            targets |= bitboard::pawn_attacks(from, piece.color) & takeable;
+
+            // Reaching the last row requires a promotion, which is a separate move for each piece
+            for to in bitboard::get_squares(targets) {
+                let to_pos = bitboard::get_position(to);
+                let board_move = BoardMove { from_pos, to_pos };
+                if to_pos.y == Self::get_promotion_row(piece.color) {
+                    for promote_piece in PROMOTION_PIECES.iter() {
+                        moves.push(ChessMove::new(board_move, Some(*promote_piece)));
+                    }
+                } else {
+                    moves.push(ChessMove::new(board_move, None));
+                }
+            }
+        }
+
+        fn generate_castle_moves(
+            &self,
+            from_pos: BoardPosition,
+            piece: ChessPiece,
+            moves: &mut Vec<ChessMove>,
+        ) {
+            if piece.moved {
+                return;
+            }
+            for direction in [-1, 1].iter() {
+                if self.chess960 {
+                    let rook_pos = self.get_castling_rook(from_pos, *direction);
+                    if let Some(rook_pos) = rook_pos {
+                        let board_move = BoardMove {
+                            from_pos,
+                            to_pos: rook_pos,
+                        };
+                        if self.check_chess960_castle(board_move).is_ok() {
+                            moves.push(ChessMove::new(board_move, None));
+                        }
+                    }
+                } else if self.get_castle_rook(from_pos, *direction).is_ok()
+                    && !self.is_castle_path_attacked(from_pos, *direction)
+                {
+                    let to_pos = Self::get_offset_position(from_pos, 2 * direction, 0).unwrap();
+                    moves.push(ChessMove::new(BoardMove { from_pos, to_pos }, None));
+                }
+            }
+        }
+
+        // Find the rook a king castles with. It must be the first piece from the king
+        // in that direction, three or four squares away, and it cannot have moved
+        fn get_castle_rook(
+            &self,
+            king_pos: BoardPosition,
+            direction: i32,
+        ) -> Result<BoardPosition, MoveError> {
+            let king = self.board[Self::get_index(king_pos)];
+            for i in 1..5 {
+                let pos = Self::get_offset_position(king_pos, i * direction, 0);
+                if pos.is_none() {
+                    break;
+                }
+                let piece = self.board[Self::get_index(pos.unwrap())];
+                if piece.is_none() {
+                    continue;
+                }
+                if i < 3
+                    || piece.unwrap().id != ChessPieceId::Rook
+                    || piece.unwrap().color != king.unwrap().color
+                {
+                    return Err(MoveError::PathBlocked);
+                }
+                if piece.unwrap().moved {
+                    return Err(MoveError::PieceHasMoved);
+                }
+                return Ok(pos.unwrap());
+            }
+            return Err(MoveError::NoCastlingRook);
+        }
+
+        // Returns true if the king is in check, or would pass or end on an attacked square
+        fn is_castle_path_attacked(&self, king_pos: BoardPosition, direction: i32) -> bool {
+            let king = self.board[Self::get_index(king_pos)].unwrap();
+            let opponent = if king.color == ChessPieceColor::White {
+                ChessPieceColor::Black
             } else {
-                piece_type = Some(ChessPieceId::Pawn);
+                ChessPieceColor::White
+            };
+            let mut bitboards = self.get_bitboards();
+            bitboards.remove_piece(bitboard::get_square(king_pos));
+            for i in 0..3 {
+                let pos = Self::get_offset_position(king_pos, i * direction, 0);
+                if pos.is_none()
+                    || bitboards.is_attacked(bitboard::get_square(pos.unwrap()), opponent)
+                {
+                    return true;
+                }
             }
+            return false;
+        }
 
-            if char_vec.len() < 2 {
-                return Err("Could not parse move".to_string());
+        // Returns true if doing the move would leave a king of the player to move attacked
+        fn leaves_king_in_check(&self, board_move: BoardMove) -> bool {
+            // Chess960 castling checks every square the king moves over, including the last
+            if self.chess960 && self.get_castle_rook_move(board_move).is_some() {
+                return false;
             }
+            let mut bitboards = self.get_bitboards();
+            let from = bitboard::get_square(board_move.from_pos);
+            let to = bitboard::get_square(board_move.to_pos);
+            let piece = self.board[from].unwrap();
 
-            if char_vec.len() == 2 {
-                to_x_input = Some(BoardPosition::get_coordinate_from_letter(char_vec[0])?);
-                to_y_input = Some(BoardPosition::get_coordinate_from_number(char_vec[1])?);
-            } else if char_vec.len() == 3 {
-                let result_letter = BoardPosition::get_coordinate_from_letter(char_vec[0]);
-                let result_number = BoardPosition::get_coordinate_from_number(char_vec[0]);
-                if result_letter.is_ok() {
-                    #[allow(unused_assignments)]
-                    {
-                        from_x_input = Some(result_letter.unwrap());
-                    }
-                } else if result_number.is_ok() {
-                    #[allow(unused_assignments)]
-                    {
-                        from_y_input = Some(result_number.unwrap());
-                    }
+            // Taking en passant removes the pawn that jumped, which is not on the target square
+            if let Some(last_move) = self.last_move {
+                if piece.id == ChessPieceId::Pawn
+                    && board_move.from_pos.x != board_move.to_pos.x
+                    && self.board[to].is_none()
+                {
+                    bitboards.remove_piece(bitboard::get_square(last_move.to_pos));
                 }
-                to_x_input = Some(BoardPosition::get_coordinate_from_letter(char_vec[1])?);
-                to_y_input = Some(BoardPosition::get_coordinate_from_number(char_vec[2])?);
-            } else if char_vec.len() == 4 {
-                from_x_input = Some(BoardPosition::get_coordinate_from_letter(char_vec[0])?);
-                from_y_input = Some(BoardPosition::get_coordinate_from_number(char_vec[1])?);
-                to_x_input = Some(BoardPosition::get_coordinate_from_letter(char_vec[2])?);
-                to_y_input = Some(BoardPosition::get_coordinate_from_number(char_vec[3])?);
-            } else {
-                return Err("Could not parse move".to_string());
             }
+            bitboards.move_piece(from, to);
 
-            let mut board_move: Option<BoardMove> = None;
+            // The rook that moves when casteling cannot uncover an attack on the king,
+            // and the squares the king moves over are checked when generating the move
+            let opponent = self.get_opponent_color();
+            let kings = bitboards.get_pieces(ChessPieceId::King, self.turn);
+            return bitboard::get_squares(kings).any(|king| bitboards.is_attacked(king, opponent));
+        }
 
-            if from_x_input.is_some()
-                && from_y_input.is_some()
-                && to_x_input.is_some()
-                && to_y_input.is_some()
-            {
-                // It is speciefied exactly which piece should move and where
-                board_move = Some(BoardMove::new(
-                    from_x_input.unwrap(),
-                    from_y_input.unwrap(),
-                    to_x_input.unwrap(),
-                    to_y_input.unwrap(),
-                ));
-            } else {
-                // Find the moving piece
-                // Try to move there, with the limits set by the input
-                for from_x in 0..8 {
-                    for from_y in 0..8 {
-                        // If from position is speciefied, make sure it follows that
-                        if from_x_input.is_some() && from_x_input.unwrap() != from_x {
-                            continue;
-                        }
-                        if from_y_input.is_some() && from_y_input.unwrap() != from_y {
-                            continue;
-                        }
-                        // Iterate pieces and see if the piece can move there
-                        let from_piece =
-                            self.get_board_piece_clone(BoardPosition::new(from_x, from_y));
-                        if from_piece.is_some()
-                            && from_piece.unwrap().color == self.turn
-                            && from_piece.unwrap().id == piece_type.unwrap()
-                        {
-                            // Make a copy of the board and try to move there
-                            let mut board_copy = self.clone();
-                            let test_move = BoardMove::new(
-                                from_x,
-                                from_y,
-                                to_x_input.unwrap(),
-                                to_y_input.unwrap(),
-                            );
-                            if board_copy
-                                .move_piece(test_move, true, promote_piece)
-                                .is_ok()
-                            {
-                                // Make sure there are not multiple pieces that can do that move
-                                if board_move.is_some() {
-                                    return Err("Unclear which piece is to move".to_string());
-                                }
-                                board_move = Some(test_move);
-                            }
-                        }
-                    }
-                }
+        // Returns true if a piece of a color can take on a square
+        pub fn is_square_attacked(&self, pos: BoardPosition, by_color: ChessPieceColor) -> bool {
+            return self
+                .get_bitboards()
+                .is_attacked(bitboard::get_square(pos), by_color);
+        }
+
+        // Get the squares of all pieces, by type and color
+        pub fn get_bitboards(&self) -> Bitboards {
+            self.update_caches();
+            return self.bitboards.get();
+        }
+
+        // The board can be changed through get_board_ref, so the bitboards and the hash
+        // are rebuilt lazily
+        fn update_caches(&self) {
+            if self.board_changed.get() {
+                self.bitboards.set(Bitboards::from_board(&self.board));
+                self.board_changed.set(false);
+                self.hash.set(None);
+            }
+        }
+
+        // Get the Zobrist hash of the position, from the placement, side to move, castling
+        // rights and en passant square. Positions that count as repeated have the same hash
+        pub fn hash(&self) -> u64 {
+            self.update_caches();
+            if self.hash.get().is_none() {
+                self.hash.set(Some(self.compute_hash()));
             }
+            return self.hash.get().unwrap();
+        }
 
-            if board_move.is_none() {
-                return Err("Could not do move".to_string());
+        // Calculate the hash without using the hash of the position before
+        fn compute_hash(&self) -> u64 {
+            let mut hash = 0;
+            for (square, piece) in self.board.iter().enumerate() {
+                if let Some(piece) = piece {
+                    hash ^= zobrist::piece_key(piece.id, piece.color, square);
+                }
             }
-            if !self.will_require_promotion(board_move.unwrap()) && promote_piece.is_some() {
-                return Err("Cannot promote piece".to_string());
+            if self.turn == ChessPieceColor::Black {
+                hash ^= zobrist::side_key();
             }
-            self.move_piece(board_move.unwrap(), true, promote_piece)?;
-            return Ok(None);
+            hash ^= zobrist::castling_key(self.get_castling_rights());
+            hash ^= self.get_passant_key();
+            return hash;
         }
 
-        // Set all postitions on the board to none
-        pub fn empty_board(&mut self) {
-            for i in 0..self.board.len() {
-                self.board[i] = None;
+        // Update the hash with what a move changed, after the move is done on the board.
+        // The castling rights and en passant key are from before the move
+        fn update_hash(&self, record: &MoveRecord, castling_rights: u8, passant_key: u64) {
+            let played_move = record.played_move;
+            let piece = played_move.piece;
+            let from = Self::get_index(played_move.board_move.from_pos);
+            let to = Self::get_index(played_move.get_piece_move().to_pos);
+            let mut hash = record.hash ^ zobrist::castling_key(castling_rights) ^ passant_key;
+
+            hash ^= zobrist::piece_key(piece.id, piece.color, from);
+            let to_id = played_move.promote_piece.unwrap_or(piece.id);
+            hash ^= zobrist::piece_key(to_id, piece.color, to);
+            if let Some((captured_pos, captured_piece)) = played_move.captured {
+                hash ^= zobrist::piece_key(
+                    captured_piece.id,
+                    captured_piece.color,
+                    Self::get_index(captured_pos),
+                );
+            }
+            if let Some(rook_move) = played_move.rook_move {
+                hash ^= zobrist::piece_key(
+                    ChessPieceId::Rook,
+                    piece.color,
+                    Self::get_index(rook_move.from_pos),
+                );
+                hash ^= zobrist::piece_key(
+                    ChessPieceId::Rook,
+                    piece.color,
+                    Self::get_index(rook_move.to_pos),
+                );
             }
+
+            hash ^= zobrist::side_key();
+            hash ^= zobrist::castling_key(self.get_castling_rights());
+            hash ^= self.get_passant_key();
+            self.hash.set(Some(hash));
         }
 
-        pub fn print_board(&mut self) {
-            self.print_board_with_possible_moves(None);
+        // Get the castling rights as four bits, white king side, white queen side,
+        // black king side and black queen side. They are derived from the moved flag
+        // of kings and rooks
+        fn get_castling_rights(&self) -> u8 {
+            let mut castling_rights = 0;
+            for (i, color) in [ChessPieceColor::White, ChessPieceColor::Black]
+                .iter()
+                .enumerate()
+            {
+                let king_pos = self.get_castling_king(*color);
+                if king_pos.is_none() {
+                    continue;
+                }
+                if self.get_castling_rook(king_pos.unwrap(), 1).is_some() {
+                    castling_rights |= 1 << (2 * i);
+                }
+                if self.get_castling_rook(king_pos.unwrap(), -1).is_some() {
+                    castling_rights |= 1 << (2 * i + 1);
+                }
+            }
+            return castling_rights;
         }
 
-        pub fn print_board_with_possible_moves(
-            &mut self,
-            possible_moves_from_pos: Option<BoardPosition>,
-        ) {
-            println!("  a b c d e f g h");
-            for y in 0..8 {
-                print!("{} ", 8 - y);
-                for x in 0..8 {
-                    let board_move;
-                    if possible_moves_from_pos.is_some() {
-                        board_move = BoardMove::new(
-                            possible_moves_from_pos.unwrap().x,
-                            possible_moves_from_pos.unwrap().y,
-                            x,
-                            y,
-                        );
-                    } else {
-                        board_move = BoardMove::new(0, 0, 0, 0);
-                    }
+        // Get the king of a color if it has not moved from its first rank
+        fn get_castling_king(&self, color: ChessPieceColor) -> Option<BoardPosition> {
+            let y = if color == ChessPieceColor::White {
+                7
+            } else {
+                0
+            };
+            let kings =
+                self.get_bitboards().get_pieces(ChessPieceId::King, color) & bitboard::get_row(y);
+            return bitboard::get_squares(kings)
+                .map(bitboard::get_position)
+                .find(|pos| !self.board[Self::get_index(*pos)].unwrap().moved);
+        }
 
-                    let mut board_copy = self.clone();
-                    let background_color;
-                    if possible_moves_from_pos.is_some()
-                        && board_copy
-                            .move_piece(board_move, true, Some(ChessPieceId::Queen))
-                            .is_ok()
-                    {
-                        // Color square red if piece can move there
-                        background_color = ColorTerminal::Red;
-                    } else if possible_moves_from_pos.is_some()
-                        && board_move.from_pos.x == x
-                        && board_move.from_pos.y == y
-                    {
-                        background_color = ColorTerminal::Green;
-                    } else if (x + y) % 2 == 0 {
-                        background_color = ColorTerminal::LightBlue;
-                    } else {
-                        background_color = ColorTerminal::Blue;
-                    }
-                    // Get a reference to a position on the board
-                    let board_ref = *self.get_board_ref(BoardPosition::new(x, y)).unwrap();
-                    if board_ref.is_none() {
-                        // If there is nothing there, just print two spaces
-                        print_color("  ", ColorTerminal::White, background_color);
-                    } else {
-                        if board_ref.as_ref().unwrap().color == ChessPieceColor::Black {
-                            print_color(
-                                board_ref
-                                    .as_ref()
-                                    .unwrap()
-                                    .unicode_char
-                                    .to_string()
-                                    .as_str(),
-                                ColorTerminal::Black,
-                                background_color,
-                            );
-                            print_color(" ", ColorTerminal::Black, background_color);
-                        } else {
-                            print_color(
-                                board_ref
-                                    .as_ref()
-                                    .unwrap()
-                                    .unicode_char
-                                    .to_string()
-                                    .as_str(),
-                                ColorTerminal::White,
-                                background_color,
-                            );
-                            print_color(" ", ColorTerminal::White, background_color);
-                        }
-                    }
+        // Get the rook a king that has not moved can castle with in a direction, if there
+        // is one. It is the outermost rook that has not moved, since castling rights in
+        // FEN for a rook further in mark the rooks outside it as moved
+        fn get_castling_rook(
+            &self,
+            king_pos: BoardPosition,
+            direction: i32,
+        ) -> Option<BoardPosition> {
+            let mut x = if direction > 0 { 7 } else { 0 };
+            while x != king_pos.x as i32 {
+                let pos = BoardPosition::new(x as BoardPosType, king_pos.y);
+                let piece = self.board[Self::get_index(pos)];
+                if piece.is_some()
+                    && piece.unwrap().id == ChessPieceId::Rook
+                    && piece.unwrap().color == self.board[Self::get_index(king_pos)].unwrap().color
+                    && !piece.unwrap().moved
+                {
+                    return Some(pos);
                 }
-                print!("{} ", 8 - y);
-                println!();
+                x -= direction;
             }
-            println!("  a b c d e f g h");
+            return None;
         }
 
-        // Get a reference to a coordinate on the board
-        pub fn get_board_ref(
-            &mut self,
-            pos: BoardPosition,
-        ) -> Result<&mut Option<ChessPiece>, String> {
-            if self.inside_board(pos).is_err() {
-                return Err("Cannot acces pieces outside board".to_string());
+        // Get the rook move if a move is castling. Castling is written as the king moving
+        // two squares, or with the Chess960 rules as the king taking its own rook
+        fn get_castle_rook_move(&self, board_move: BoardMove) -> Option<BoardMove> {
+            let piece = self.board[Self::get_index(board_move.from_pos)]?;
+            if piece.id != ChessPieceId::King || board_move.from_pos.y != board_move.to_pos.y {
+                return None;
+            }
+            let dx = board_move.to_pos.x as i32 - board_move.from_pos.x as i32;
+            if self.chess960 {
+                let rook = self.board[Self::get_index(board_move.to_pos)]?;
+                if rook.id != ChessPieceId::Rook || rook.color != piece.color {
+                    return None;
+                }
+                // The rook ends on the f-file or the d-file
+                let rook_x = if dx > 0 { 5 } else { 3 };
+                return Some(BoardMove {
+                    from_pos: board_move.to_pos,
+                    to_pos: BoardPosition::new(rook_x, board_move.to_pos.y),
+                });
+            }
+            if dx.abs() != 2 {
+                return None;
             }
-            return Ok(&mut self.board[(pos.x + 8 * pos.y) as usize]);
+            let direction = dx.signum();
+            return Some(BoardMove {
+                from_pos: self.get_castle_rook(board_move.from_pos, direction).ok()?,
+                to_pos: Self::get_offset_position(board_move.from_pos, direction, 0)?,
+            });
         }
 
-        // Get a clone of a position on the board
-        pub fn get_board_piece_clone(&mut self, pos: BoardPosition) -> Option<ChessPiece> {
-            return (*self.get_board_ref(pos).unwrap()).clone();
-        }
+        // Check that a king can castle with a rook by the Chess960 rules, the king ends on
+        // the g-file or the c-file and the rook next to it, wherever they started
+        fn check_chess960_castle(&self, board_move: BoardMove) -> Result<BoardMove, MoveError> {
+            let king = self.board[Self::get_index(board_move.from_pos)];
+            if king.is_none() || king.unwrap().id != ChessPieceId::King {
+                return Err(MoveError::NoKing);
+            }
+            if king.unwrap().moved {
+                return Err(MoveError::PieceHasMoved);
+            }
+            let rook_move = self.get_castle_rook_move(board_move);
+            if rook_move.is_none() {
+                return Err(MoveError::NoCastlingRook);
+            }
+            let rook_move = rook_move.unwrap();
+            let direction = if rook_move.from_pos.x > board_move.from_pos.x {
+                1
+            } else {
+                -1
+            };
+            if self.get_castling_king(king.unwrap().color) != Some(board_move.from_pos)
+                || self.get_castling_rook(board_move.from_pos, direction)
+                    != Some(rook_move.from_pos)
+            {
+                return Err(MoveError::PieceHasMoved);
+            }
 
-        // Set a position on the board
-        pub fn set_pos(&mut self, pos: BoardPosition, id: ChessPieceId, color: ChessPieceColor) {
-            *self.get_board_ref(pos).unwrap() = Some(ChessPiece::new(id, color));
-        }
+            // All squares the king and rook move over must be empty, except for themselves
+            let mut bitboards = self.get_bitboards();
+            bitboards.remove_piece(bitboard::get_square(board_move.from_pos));
+            bitboards.remove_piece(bitboard::get_square(rook_move.from_pos));
+            let king_x = if direction > 0 { 6 } else { 2 };
+            let y = board_move.from_pos.y;
+            let king_squares = Self::get_row_squares(board_move.from_pos.x, king_x, y);
+            let rook_squares = Self::get_row_squares(rook_move.from_pos.x, rook_move.to_pos.x, y);
+            if (king_squares | rook_squares) & bitboards.get_occupied() != 0 {
+                return Err(MoveError::PathBlocked);
+            }
 
-        pub fn set_pos_to_none(&mut self, pos: BoardPosition) {
-            *self.get_board_ref(pos).unwrap() = None;
+            // The king cannot castle out of, through or into check
+            let opponent = if king.unwrap().color == ChessPieceColor::White {
+                ChessPieceColor::Black
+            } else {
+                ChessPieceColor::White
+            };
+            if bitboard::get_squares(king_squares)
+                .any(|square| bitboards.is_attacked(square, opponent))
+            {
+                return Err(MoveError::CastleThroughCheck);
+            }
+            return Ok(rook_move);
         }
 
-        // Returns true if the game is over
-        pub fn game_is_over(&mut self) -> bool {
-            if self.is_check_mate() || self.is_stale_mate() || self.max_move_count_reached() {
-                return true;
+        // All squares on a row between two files, including both
+        fn get_row_squares(x1: BoardPosType, x2: BoardPosType, y: BoardPosType) -> Bitboard {
+            let mut squares = 0;
+            for x in x1.min(x2)..=x1.max(x2) {
+                squares |= bitboard::get_bit(BoardPosition::new(x, y));
             }
-            return false;
+            return squares;
         }
 
-        // Returns if there is a winner, and what color it is
-        pub fn get_winner(&mut self) -> Option<ChessPieceColor> {
-            if self.is_check_mate() {
-                if self.turn == ChessPieceColor::White {
-                    return Some(ChessPieceColor::Black);
-                } else {
-                    return Some(ChessPieceColor::White);
-                }
-            } else {
-                return None;
+        // Get the en passant key of the hash, zero if no pawn can take en passant
+        fn get_passant_key(&self) -> u64 {
+            let target = self.get_passant_capture_target();
+            if target.is_none() {
+                return 0;
             }
+            return zobrist::passant_key(target.unwrap().x as usize);
         }
 
-        pub fn is_check(&mut self) -> Option<BoardMove> {
-            for x in 0..8 {
-                for y in 0..8 {
-                    let piece = self.get_board_piece_clone(BoardPosition::new(x, y));
-                    // If it is a king
-                    if piece.is_some()
-                        && piece.unwrap().id == ChessPieceId::King
-                        && piece.unwrap().color == self.turn
-                    {
-                        // Iterate pieces to see if any can capture the king
-                        for x2 in 0..8 {
-                            for y2 in 0..8 {
-                                let board_move = BoardMove::new(x2, y2, x, y);
-                                let mut board_copy = (*self).clone();
-                                board_copy.end_turn(); // Make it the opponents turn
-
-                                // Checks if the move captures the king
-                                // If it takes the king, you dont have to think about check
-                                if board_copy
-                                    .move_piece(board_move, false, Some(ChessPieceId::Queen))
-                                    .is_ok()
-                                {
-                                    return Some(board_move);
-                                }
-                            }
-                        }
+        // Get the square a pawn can be taken on en passant,
+        // None if there is no such square or the capture is not legal
+        fn get_passant_capture_target(&self) -> Option<BoardPosition> {
+            let passant_target = self.get_passant_target()?;
+            let pawn_pos = self.last_move.unwrap().to_pos;
+            for x in [pawn_pos.x as i32 - 1, pawn_pos.x as i32 + 1].iter() {
+                if !(0..8).contains(x) {
+                    continue;
+                }
+                let from_pos = BoardPosition::new(*x as u8, pawn_pos.y);
+                let piece = self.board[Self::get_index(from_pos)];
+                if piece.is_some()
+                    && piece.unwrap().id == ChessPieceId::Pawn
+                    && piece.unwrap().color == self.turn
+                {
+                    let board_move = BoardMove {
+                        from_pos,
+                        to_pos: passant_target,
+                    };
+                    if !self.leaves_king_in_check(board_move) {
+                        return Some(passant_target);
                     }
                 }
             }
             return None;
         }
 
-        pub fn is_check_mate(&mut self) -> bool {
-            if self.is_check().is_some() && self.get_possible_moves().is_empty() {
-                return true;
-            } else {
-                return false;
+        // Get the position a number of steps away, or None if it is outside the board
+        fn get_offset_position(pos: BoardPosition, dx: i32, dy: i32) -> Option<BoardPosition> {
+            let x = pos.x as i32 + dx;
+            let y = pos.y as i32 + dy;
+            if !(0..8).contains(&x) || !(0..8).contains(&y) {
+                return None;
             }
+            return Some(BoardPosition::new(x as BoardPosType, y as BoardPosType));
         }
 
-        pub fn is_stale_mate(&mut self) -> bool {
-            if self.is_check().is_none() && self.get_possible_moves().is_empty() {
-                return true;
+        // White pawns move towards row 0, and black pawns towards row 7
+        fn get_pawn_direction(color: ChessPieceColor) -> i32 {
+            if color == ChessPieceColor::White {
+                return -1;
             } else {
-                return false;
+                return 1;
             }
         }
 
-        pub fn get_possible_moves(&mut self) -> LinkedList<BoardMove> {
-            let mut board_moves: LinkedList<BoardMove> = Default::default();
-            for x in 0..8 {
-                for y in 0..8 {
-                    let piece = (self.get_board_ref(BoardPosition::new(x, y)).unwrap()).clone();
-                    // If it is a king
-                    if piece.is_some() && piece.unwrap().color == self.turn {
-                        for x2 in 0..8 {
-                            for y2 in 0..8 {
-                                // Iterate spaces to see if it can move there
-                                let board_move = BoardMove::new(x, y, x2, y2);
-                                let mut board_copy = self.clone();
-                                if board_copy
-                                    .move_piece(board_move, true, Some(ChessPieceId::Queen))
-                                    .is_ok()
-                                {
-                                    board_moves.push_back(board_move);
-                                }
-                            }
-                        }
-                    }
-                }
+        fn get_promotion_row(color: ChessPieceColor) -> BoardPosType {
+            if color == ChessPieceColor::White {
+                return 0;
+            } else {
+                return 7;
             }
-            return board_moves;
         }
 
         pub fn move_piece(
@@ -660,34 +2477,58 @@ pub mod chess_game {
             board_move: BoardMove,
             check_for_check: bool,
             promote_piece: Option<ChessPieceId>,
-        ) -> Result<(), String> {
+        ) -> Result<(), MoveError> {
+            // Checking the move marks the board as changed, so get the hash first
+            let hash = self.hash();
+            let castling_rights = self.get_castling_rights();
+            let passant_key = self.get_passant_key();
             self.is_move(board_move)?;
             self.inside_board(board_move.from_pos)?;
             self.inside_board(board_move.to_pos)?;
             let from_piece = self.get_board_ref(board_move.from_pos).unwrap().clone();
             if from_piece.is_none() {
-                return Err("No piece on square selected!".to_string());
+                return Err(MoveError::NoPiece);
             }
-            self.board_move_not_same_color_pieces(board_move)?;
-
-            // Make sure move does not lead to check
-            if check_for_check {
-                let mut self_copy = self.clone();
-                self_copy.move_piece(board_move, false, Some(ChessPieceId::Queen))?;
-                self_copy.turn = self.turn;
-                if self_copy.is_check().is_some() {
-                    return Err("Move leads to check!".to_string());
-                }
+            // Castling in Chess960 is written as the king taking its own rook
+            let castle_rook_move = self.get_castle_rook_move(board_move);
+            let chess960_castle = self.chess960 && castle_rook_move.is_some();
+            if !chess960_castle {
+                self.board_move_not_same_color_pieces(board_move)?;
             }
 
             // make sure you are not moving opponents pieces
             if from_piece.as_ref().unwrap().color != self.turn {
-                return Err("Cannot move opponents pieces".to_string());
+                return Err(MoveError::OpponentPiece);
             }
 
             // make sure move count limit is not reached
             if self.max_move_count_reached() {
-                return Err("Maximum move count reached".to_string());
+                return Err(MoveError::GameOver);
+            }
+
+            // make sure the game has not been ended by a player
+            if self.termination.is_some() {
+                return Err(MoveError::GameOver);
+            }
+
+            // Remember the position so the move can be taken back
+            let board_before = self.board;
+            let mut record = MoveRecord {
+                played_move: PlayedMove {
+                    board_move,
+                    piece: from_piece.unwrap(),
+                    promote_piece: None,
+                    captured: None,
+                    rook_move: None,
+                },
+                last_move: self.last_move,
+                last_move_passant: self.last_move_passant,
+                move_count_left: self.move_count_left,
+                full_move_number: self.full_move_number,
+                hash,
+            };
+            if self.will_require_promotion(board_move) {
+                record.played_move.promote_piece = promote_piece;
             }
 
             // Do move depending on piece
@@ -698,13 +2539,20 @@ pub mod chess_game {
                 ChessPieceId::Rook => {
                     self.rook_move(board_move)?;
                 }
+                ChessPieceId::King if chess960_castle => {
+                    let rook_move = self.check_chess960_castle(board_move)?;
+                    self.chess960_castle(board_move, rook_move);
+                }
                 ChessPieceId::King => {
                     let result1 = self.king_move_one(board_move);
-                    let result2 = self.king_castle(board_move);
-                    if result1.is_err() && result2.is_err() {
-                        return Err(
-                            (result1.err().unwrap() + result2.err().unwrap().as_str()).to_string()
-                        );
+                    if result1.is_err() {
+                        let result2 = self.king_castle(board_move);
+                        if result2.is_err() {
+                            return Err(MoveError::most_relevant(&[
+                                result1.err().unwrap(),
+                                result2.err().unwrap(),
+                            ]));
+                        }
                     }
                 }
                 ChessPieceId::Queen => {
@@ -722,8 +2570,7 @@ pub mod chess_game {
                         {
                             promote = true;
                         } else {
-                            return Err("Move requires promotion, cannot promote to pawn or king"
-                                .to_string());
+                            return Err(MoveError::PromotionRequired);
                         }
                     }
                     loop {
@@ -739,15 +2586,11 @@ pub mod chess_game {
                         if result3.is_ok() {
                             break;
                         }
-                        let mut error_message: String =
-                            "Moving pawn failed!\n one_forward: ".to_string();
-                        error_message += result1.err().unwrap().as_str();
-                        error_message += "\n two_forward: ";
-                        error_message += result2.err().unwrap().as_str();
-                        error_message += "\n take: ";
-                        error_message += result3.err().unwrap().as_str();
-                        error_message += "\n";
-                        return Err(error_message);
+                        return Err(MoveError::most_relevant(&[
+                            result1.err().unwrap(),
+                            result2.err().unwrap(),
+                            result3.err().unwrap(),
+                        ]));
                     }
                     if promote {
                         self.get_board_ref(board_move.to_pos)
@@ -760,27 +2603,57 @@ pub mod chess_game {
                     self.reset_move_count_left();
                 }
             }
+
+            // Make sure move does not lead to check, otherwise put everything back
+            if check_for_check && self.is_check().is_some() {
+                self.board = board_before;
+                self.board_changed.set(true);
+                self.last_move = record.last_move;
+                self.last_move_passant = record.last_move_passant;
+                self.move_count_left = record.move_count_left;
+                return Err(MoveError::LeavesKingInCheck);
+            }
             self.end_turn();
+
+            record.played_move =
+                self.get_played_move(&board_before, board_move, record.played_move.promote_piece);
+            if chess960_castle {
+                // The king and rook can swap squares, which comparing the boards does not find
+                record.played_move.rook_move = castle_rook_move;
+            }
+            self.bitboards.set(Bitboards::from_board(&self.board));
+            self.board_changed.set(false);
+            self.update_hash(&record, castling_rights, passant_key);
+            self.history.push(record);
+            self.redo_stack.clear();
+
+            // Draws that end the game by themselves, so no more moves are accepted.
+            // After a checkmate or stalemate there are no legal moves anyway
+            if self.is_insufficient_material() {
+                self.termination = Some(GameStatus::InsufficientMaterial);
+            } else if self.is_fivefold_repetition() {
+                self.termination = Some(GameStatus::Repetition);
+            }
             return Ok(());
         }
 
-        fn promote(&mut self, pos: BoardPosition, to_id: ChessPieceId) -> Result<(), String> {
+        fn promote(&mut self, pos: BoardPosition, to_id: ChessPieceId) -> Result<(), MoveError> {
             self.inside_board(pos)?;
             if to_id == ChessPieceId::Pawn {
-                return Err("Cannot convert to pawn!".to_string());
+                return Err(MoveError::InvalidPromotion);
             }
             if to_id as u32 == ChessPieceId::King as u32 {
-                return Err("Cannot convert to king!".to_string());
+                return Err(MoveError::InvalidPromotion);
             }
             let piece = (*self.get_board_ref(pos).unwrap()).clone();
             if piece.is_none() {
-                return Err("Cannot convert from nothing!".to_string());
+                return Err(MoveError::NoPiece);
             }
             if piece.unwrap().id != ChessPieceId::Pawn {
-                return Err("Cannot convert something other than pawn!".to_string());
+                return Err(MoveError::InvalidPromotion);
             }
             if piece.unwrap().color != self.turn {
-                return Err("Cannot convert opponents pieces!".to_string());
+                return Err(MoveError::OpponentPiece);
             }
             // Make sure piece is in the right place
             if (piece.unwrap().color == ChessPieceColor::Black && pos.y == 7)
@@ -791,10 +2664,13 @@ pub mod chess_game {
                 self.end_turn();
                 return Ok(());
             } else {
-                return Err("Cannot convert pawn not on other side".to_string());
+                return Err(MoveError::InvalidPromotion);
             }
         }
-        pub fn get_piece_type_from_letter(&mut self, letter: char) -> Result<ChessPieceId, String> {
+        pub fn get_piece_type_from_letter(
+            &mut self,
+            letter: char,
+        ) -> Result<ChessPieceId, MoveError> {
             match letter {
                 'R' => return Ok(ChessPieceId::Rook),
                 'P' => return Ok(ChessPieceId::Pawn),
@@ -802,11 +2678,11 @@ pub mod chess_game {
                 'K' => return Ok(ChessPieceId::King),
                 'N' => return Ok(ChessPieceId::Knight),
                 'B' => return Ok(ChessPieceId::Bishop),
-                _ => return Err("No matching type".to_string()),
+                _ => return Err(MoveError::InvalidNotation),
             }
         }
 
-        fn is_unblocked_straight_line(&mut self, board_move: BoardMove) -> Result<(), String> {
+        fn is_unblocked_straight_line(&mut self, board_move: BoardMove) -> Result<(), MoveError> {
             // Make sure it is a straight line
             if board_move.from_pos.x == board_move.to_pos.x {
                 for y in 1..(board_move.from_pos.y as i32 - board_move.to_pos.y as i32).abs() {
@@ -823,7 +2699,7 @@ pub mod chess_game {
                         .as_ref()
                         .is_some()
                     {
-                        return Err("Straight path is blocked".to_string());
+                        return Err(MoveError::PathBlocked);
                     }
                 }
                 return Ok(());
@@ -842,22 +2718,23 @@ pub mod chess_game {
                         .as_ref()
                         .is_some()
                     {
-                        return Err("Straight path is blocked!".to_string());
+                        return Err(MoveError::PathBlocked);
                     }
                 }
                 return Ok(());
             }
-            return Err("Not a valid straight line!".to_string());
+            return Err(MoveError::InvalidPieceMove);
         }
 
         fn reset_move_count_left(&mut self) {
-            self.move_count_left = 100;
+            // One more than the limit, since end_turn also counts the move that reset it
+            self.move_count_left = 101;
         }
         fn max_move_count_reached(&mut self) -> bool {
             return self.move_count_left < 1;
         }
 
-        fn is_unblocked_diagonal_line(&mut self, board_move: BoardMove) -> Result<(), String> {
+        fn is_unblocked_diagonal_line(&mut self, board_move: BoardMove) -> Result<(), MoveError> {
             // Make sure it is a diagonal
             if (board_move.from_pos.x as i32 - board_move.to_pos.x as i32).abs()
                 == (board_move.from_pos.y as i32 - board_move.to_pos.y as i32).abs()
@@ -877,19 +2754,19 @@ pub mod chess_game {
                         (d_y + board_move.from_pos.y as i32) as u8,
                     );
                     if self.get_board_ref(pos).unwrap().as_ref().is_some() {
-                        return Err("Path blocked!".to_string());
+                        return Err(MoveError::PathBlocked);
                     }
                 }
                 return Ok(());
             }
-            return Err("Not a valid diagonal!".to_string());
+            return Err(MoveError::InvalidPieceMove);
         }
 
-        fn is_move(&mut self, board_move: BoardMove) -> Result<(), String> {
+        fn is_move(&mut self, board_move: BoardMove) -> Result<(), MoveError> {
             if (board_move.from_pos.x == board_move.to_pos.x)
                 && (board_move.from_pos.y == board_move.to_pos.y)
             {
-                return Err("Cannot do nothing during your turn!".to_string());
+                return Err(MoveError::NullMove);
             }
             return Ok(());
         }
@@ -907,16 +2784,26 @@ pub mod chess_game {
             }
         }
 
-        fn is_piece_id(&mut self, pos: BoardPosition, id: ChessPieceId) -> Result<(), String> {
+        fn is_piece_of(
+            &mut self,
+            pos: BoardPosition,
+            id: ChessPieceId,
+            color: ChessPieceColor,
+        ) -> bool {
+            let piece = self.get_board_piece_clone(pos);
+            return piece.is_some() && piece.unwrap().id == id && piece.unwrap().color == color;
+        }
+
+        fn is_piece_id(&mut self, pos: BoardPosition, id: ChessPieceId) -> Result<(), MoveError> {
             if self.get_board_ref(pos)?.is_some() && self.get_board_ref(pos)?.unwrap().id == id {
                 return Ok(());
             } else {
-                return Err("Piece id does not match".to_string());
+                return Err(MoveError::InvalidPieceMove);
             }
         }
-        fn inside_board(&mut self, pos: BoardPosition) -> Result<(), String> {
+        fn inside_board(&mut self, pos: BoardPosition) -> Result<(), MoveError> {
             if pos.x > 7 || pos.y > 7 {
-                return Err("Outside of the board!".to_string());
+                return Err(MoveError::OutsideBoard);
             }
             return Ok(());
         }
@@ -926,6 +2813,7 @@ pub mod chess_game {
                 self.turn = ChessPieceColor::Black;
             } else {
                 self.turn = ChessPieceColor::White;
+                self.full_move_number += 1;
             }
             self.move_count_left = self.move_count_left - 1;
         }
@@ -973,68 +2861,68 @@ pub mod chess_game {
         fn board_move_not_same_color_pieces(
             &mut self,
             board_move: BoardMove,
-        ) -> Result<(), String> {
+        ) -> Result<(), MoveError> {
             if self.is_pieces_same_color(board_move.from_pos, board_move.to_pos) {
-                return Err("Cannot move piece to piece of same color".to_string());
+                return Err(MoveError::OwnPieceOnTarget);
             } else {
                 return Ok(());
             }
         }
-        fn board_move_is_forward(&mut self, board_move: BoardMove) -> Result<(), String> {
+        fn board_move_is_forward(&mut self, board_move: BoardMove) -> Result<(), MoveError> {
             let piece = self.get_board_ref(board_move.from_pos).unwrap();
             if piece.is_none() {
-                return Err("Piece is none".to_string());
+                return Err(MoveError::NoPiece);
             }
             match piece.unwrap().color {
                 ChessPieceColor::Black => {
                     if board_move.from_pos.y < board_move.to_pos.y {
                         return Ok(());
                     } else {
-                        return Err("Not forward move".to_string());
+                        return Err(MoveError::InvalidPieceMove);
                     }
                 }
                 ChessPieceColor::White => {
                     if board_move.from_pos.y > board_move.to_pos.y {
                         return Ok(());
                     } else {
-                        return Err("Not forward move".to_string());
+                        return Err(MoveError::InvalidPieceMove);
                     }
                 }
             }
         }
 
-        fn pawn_one_forward(&mut self, board_move: BoardMove) -> Result<(), String> {
+        fn pawn_one_forward(&mut self, board_move: BoardMove) -> Result<(), MoveError> {
             self.is_piece_id(board_move.from_pos, ChessPieceId::Pawn)?;
             self.board_move_is_forward(board_move)?;
             if (board_move.to_pos.y as i32 - board_move.from_pos.y as i32).abs() != 1 {
-                return Err("Not moving one forward".to_string());
+                return Err(MoveError::InvalidPieceMove);
             }
             if (board_move.to_pos.x as i32 - board_move.from_pos.x as i32).abs() != 0 {
-                return Err("Moving to side".to_string());
+                return Err(MoveError::InvalidPieceMove);
             }
             if self.get_board_ref(board_move.to_pos).unwrap().is_some() {
-                return Err("Path blocked".to_string());
+                return Err(MoveError::PathBlocked);
             }
 
             self.force_move_piece(board_move);
             return Ok(());
         }
 
-        fn pawn_two_forward(&mut self, board_move: BoardMove) -> Result<(), String> {
+        fn pawn_two_forward(&mut self, board_move: BoardMove) -> Result<(), MoveError> {
             self.is_piece_id(board_move.from_pos, ChessPieceId::Pawn)?;
             self.board_move_is_forward(board_move)?;
             self.is_unblocked_straight_line(board_move)?;
 
             // Make sure piece is moving two forward
             if (board_move.to_pos.y as i32 - board_move.from_pos.y as i32).abs() != 2 {
-                return Err("Not moving two forward".to_string());
+                return Err(MoveError::InvalidPieceMove);
             }
             if (board_move.to_pos.x as i32 - board_move.from_pos.x as i32).abs() != 0 {
-                return Err("Moving to side".to_string());
+                return Err(MoveError::InvalidPieceMove);
             }
 
             if self.get_board_ref(board_move.to_pos).unwrap().is_some() {
-                return Err("Path blocked".to_string());
+                return Err(MoveError::PathBlocked);
             }
 
             if self
@@ -1044,7 +2932,7 @@ pub mod chess_game {
                 .moved
                 == true
             {
-                return Err("Cannot move pawn two forward who has already moved".to_string());
+                return Err(MoveError::PieceHasMoved);
             }
 
             // Move piece
@@ -1054,19 +2942,19 @@ pub mod chess_game {
             return Ok(());
         }
 
-        fn pawn_take(&mut self, board_move: BoardMove) -> Result<(), String> {
+        fn pawn_take(&mut self, board_move: BoardMove) -> Result<(), MoveError> {
             self.is_piece_id(board_move.from_pos, ChessPieceId::Pawn)?;
             self.board_move_not_same_color_pieces(board_move)?;
             self.board_move_is_forward(board_move)?;
 
             // Make sure pawn is moving one forward
             if (board_move.to_pos.y as i32 - board_move.from_pos.y as i32).abs() != 1 {
-                return Err("Not moving one forward".to_string());
+                return Err(MoveError::InvalidPieceMove);
             }
 
             // Make sure pawn is moving one to the side
             if (board_move.to_pos.x as i32 - board_move.from_pos.x as i32).abs() != 1 {
-                return Err("Not moving one to the side".to_string());
+                return Err(MoveError::InvalidPieceMove);
             }
 
             // Check for pessant
@@ -1080,7 +2968,7 @@ pub mod chess_game {
                 // Remove last moved pawn due to pessant
                 *self.get_board_ref(self.last_move.unwrap().to_pos).unwrap() = None;
             } else if self.get_board_ref(board_move.to_pos).unwrap().is_none() {
-                return Err("No piece to take".to_string());
+                return Err(MoveError::NothingToCapture);
             }
 
             // Move piece
@@ -1088,7 +2976,7 @@ pub mod chess_game {
             return Ok(());
         }
 
-        fn rook_move(&mut self, board_move: BoardMove) -> Result<(), String> {
+        fn rook_move(&mut self, board_move: BoardMove) -> Result<(), MoveError> {
             self.is_piece_id(board_move.from_pos, ChessPieceId::Rook)?;
             self.is_unblocked_straight_line(board_move)?;
             self.board_move_not_same_color_pieces(board_move)?;
@@ -1097,7 +2985,7 @@ pub mod chess_game {
             return Ok(());
         }
 
-        fn knight_move(&mut self, board_move: BoardMove) -> Result<(), String> {
+        fn knight_move(&mut self, board_move: BoardMove) -> Result<(), MoveError> {
             self.is_piece_id(board_move.from_pos, ChessPieceId::Knight)?;
             self.board_move_not_same_color_pieces(board_move)?;
 
@@ -1106,55 +2994,53 @@ pub mod chess_game {
                 && ((board_move.from_pos.x as i32 - board_move.to_pos.x as i32).abs() != 2
                     || (board_move.from_pos.y as i32 - board_move.to_pos.y as i32).abs() != 1)
             {
-                return Err("invalid move".to_string());
+                return Err(MoveError::InvalidPieceMove);
             }
             self.force_move_piece(board_move);
             return Ok(());
         }
 
-        fn queen_move(&mut self, board_move: BoardMove) -> Result<(), String> {
+        fn queen_move(&mut self, board_move: BoardMove) -> Result<(), MoveError> {
             self.is_piece_id(board_move.from_pos, ChessPieceId::Queen)?;
             self.board_move_not_same_color_pieces(board_move)?;
             let diagonal_result = self.is_unblocked_diagonal_line(board_move);
             let straight_result = self.is_unblocked_straight_line(board_move);
             if diagonal_result.is_err() && straight_result.is_err() {
-                let mut error_message: String = "Queen: ".to_string();
-                error_message += diagonal_result.err().unwrap().as_str();
-                error_message += ", ";
-                error_message += straight_result.err().unwrap().as_str();
-                return Err(error_message);
+                return Err(MoveError::most_relevant(&[
+                    diagonal_result.err().unwrap(),
+                    straight_result.err().unwrap(),
+                ]));
             }
             self.force_move_piece(board_move);
             return Ok(());
         }
 
-        fn king_move_one(&mut self, board_move: BoardMove) -> Result<(), String> {
+        fn king_move_one(&mut self, board_move: BoardMove) -> Result<(), MoveError> {
             self.is_piece_id(board_move.from_pos, ChessPieceId::King)?;
             self.board_move_not_same_color_pieces(board_move)?;
             if (board_move.from_pos.x as i32 - board_move.to_pos.x as i32).abs() > 1
                 || (board_move.from_pos.y as i32 - board_move.to_pos.y as i32).abs() > 1
             {
-                return Err("King: cannot move that far!".to_string());
+                return Err(MoveError::InvalidPieceMove);
             }
             self.force_move_piece(board_move);
             return Ok(());
         }
 
-        fn king_castle(&mut self, board_move: BoardMove) -> Result<(), String> {
+        fn king_castle(&mut self, board_move: BoardMove) -> Result<(), MoveError> {
             let from_piece = self.get_board_piece_clone(board_move.from_pos);
             if from_piece.is_none() || (from_piece.unwrap().id != ChessPieceId::King) {
-                return Err("Must have king to castle".to_string());
+                return Err(MoveError::NoKing);
             }
             if from_piece.unwrap().moved == true {
-                return Err("Cannot castle with moved piece".to_string());
+                return Err(MoveError::PieceHasMoved);
             }
             if (board_move.to_pos.x as i32 - board_move.from_pos.x as i32).abs() != 2 {
-                return Err("Castle requires piece to move two to the side".to_string());
+                return Err(MoveError::InvalidPieceMove);
             }
             if (board_move.to_pos.y as i32 - board_move.from_pos.y as i32).abs() != 0 {
-                return Err("Cannot move forwards while casteling".to_string());
+                return Err(MoveError::InvalidPieceMove);
             }
-            // Find the rook
             let direction: i32;
             if board_move.to_pos.x > board_move.from_pos.x {
                 direction = 1;
@@ -1162,58 +3048,40 @@ pub mod chess_game {
                 direction = -1;
             }
             // Make sure it is not check on any of the squares king is moving on
-            for i in 0..3 {
-                // Make a clone of the board
-                let mut board_copy = self.clone();
-
-                // Move the king and check for check
-                board_copy.set_pos_to_none(board_move.from_pos);
-                let pos = BoardPosition::new(
-                    (board_move.from_pos.x as i32 + i * direction) as BoardPosType,
-                    board_move.to_pos.y,
-                );
-                board_copy.set_pos(pos, ChessPieceId::King, self.turn);
-                if board_copy.is_check().is_some() {
-                    return Err("Cannot castle on checked square".to_string());
-                }
+            if self.is_castle_path_attacked(board_move.from_pos, direction) {
+                return Err(MoveError::CastleThroughCheck);
             }
-            for i in 1..5 {
-                let rook_x = (board_move.from_pos.x as i32 + i * direction) as BoardPosType;
-                let rook_y = board_move.from_pos.y;
-                let rook_pos = BoardPosition::new(rook_x, rook_y);
-                if self.inside_board(rook_pos).is_err() {
-                    break;
-                }
-                if self
-                    .is_piece_id(BoardPosition::new(rook_x, rook_y), ChessPieceId::Rook)
-                    .is_ok()
-                {
-                    if self
-                        .get_board_ref(BoardPosition::new(rook_x, rook_y))
-                        .unwrap()
-                        .unwrap()
-                        .moved
-                        == true
-                    {
-                        return Err("Cannot castle with moved rook".to_string());
-                    }
+            let rook_pos = self.get_castle_rook(board_move.from_pos, direction)?;
 
-                    // Move king and rook
-                    self.force_move_piece(board_move);
-                    let rook_move = BoardMove::new(
-                        rook_x,
-                        rook_y,
-                        (board_move.from_pos.x as i32 + direction) as BoardPosType,
-                        rook_y,
-                    );
-                    self.force_move_piece(rook_move);
-                    return Ok(());
-                }
-            }
-            return Err("No rook to move with".to_string());
+            // Move king and rook
+            self.force_move_piece(board_move);
+            let rook_move = BoardMove::new(
+                rook_pos.x,
+                rook_pos.y,
+                (board_move.from_pos.x as i32 + direction) as BoardPosType,
+                rook_pos.y,
+            );
+            self.force_move_piece(rook_move);
+            return Ok(());
+        }
+
+        // Move the king and rook of a Chess960 castling move that has been checked
+        fn chess960_castle(&mut self, board_move: BoardMove, rook_move: BoardMove) {
+            // The rook ends on the f-file next to the king on the g-file, or on the d-file
+            let king_x = if rook_move.to_pos.x == 5 { 6 } else { 2 };
+            let mut king = self.get_board_ref(board_move.from_pos).unwrap().take();
+            let mut rook = self.get_board_ref(rook_move.from_pos).unwrap().take();
+            king.as_mut().unwrap().moved = true;
+            rook.as_mut().unwrap().moved = true;
+            *self.get_board_ref(rook_move.to_pos).unwrap() = rook;
+            *self
+                .get_board_ref(BoardPosition::new(king_x, board_move.from_pos.y))
+                .unwrap() = king;
+            self.last_move_passant = false;
+            self.last_move = Some(board_move);
         }
 
-        fn bishop_move(&mut self, board_move: BoardMove) -> Result<(), String> {
+        fn bishop_move(&mut self, board_move: BoardMove) -> Result<(), MoveError> {
             self.is_unblocked_diagonal_line(board_move)?;
             self.force_move_piece(board_move);
             return Ok(());
@@ -0,0 +1,466 @@
+// Games played over the network. The server keeps the real games, and only the client
+// sitting on the color to move can play a move. Clients wait in the lobby until they create
+// or join a game, or are paired with another client. A player that leaves a game that has
+// started gives it up, so the seat is never taken over by someone else
+use crate::chess_game::{ChessMove, ChessPieceColor, Game, GameStatus, MoveError, PlayedMove};
+use crate::clock::{Clock, ClockError, TimeControl};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+// Every client connected to a server has its own id
+pub type ClientId = u64;
+pub type GameId = u64;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Seat {
+    White,
+    Black,
+    Spectator,
+}
+
+impl Seat {
+    pub fn from_color(color: ChessPieceColor) -> Seat {
+        if color == ChessPieceColor::White {
+            return Seat::White;
+        } else {
+            return Seat::Black;
+        }
+    }
+
+    // Get the color played from the seat, None for spectators
+    pub fn get_color(&self) -> Option<ChessPieceColor> {
+        match self {
+            Seat::White => return Some(ChessPieceColor::White),
+            Seat::Black => return Some(ChessPieceColor::Black),
+            Seat::Spectator => return None,
+        }
+    }
+}
+
+// Why the server did not play a move from a client
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MoveRejection {
+    Spectator,
+    WrongSeat { turn: ChessPieceColor },
+    Illegal(MoveError),
+    NotStarted, // The game waits for a second player
+    Clock(ClockError),
+}
+
+impl fmt::Display for MoveRejection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MoveRejection::Spectator => return write!(f, "Spectators cannot move"),
+            MoveRejection::WrongSeat { turn } => return write!(f, "It is {:?} to move", turn),
+            MoveRejection::Illegal(error) => return write!(f, "{}", error),
+            MoveRejection::NotStarted => return write!(f, "The game waits for an opponent"),
+            MoveRejection::Clock(error) => return write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for MoveRejection {}
+
+// Which clients play White and Black, and which are watching
+#[derive(Clone, Default, Debug)]
+pub struct Seats {
+    white: Option<ClientId>,
+    black: Option<ClientId>,
+    spectators: Vec<ClientId>,
+}
+
+impl Seats {
+    pub fn new() -> Self {
+        return Seats::default();
+    }
+
+    // Give a client the seat it wants if it is free, else the first free color.
+    // Clients get to spectate when both colors are taken or when they ask for it
+    pub fn join(&mut self, client: ClientId, wanted: Option<Seat>) -> Seat {
+        self.leave(client);
+        let mut order = vec![Seat::White, Seat::Black];
+        match wanted {
+            Some(Seat::Black) => order.reverse(),
+            Some(Seat::Spectator) => order.clear(),
+            _ => {}
+        }
+        for seat in order {
+            if self.get_player(seat.get_color().unwrap()).is_none() {
+                self.set_player(seat, Some(client));
+                return seat;
+            }
+        }
+        self.spectators.push(client);
+        return Seat::Spectator;
+    }
+
+    // Remove a client, its color is free for the next client. Returns the seat it had
+    pub fn leave(&mut self, client: ClientId) -> Option<Seat> {
+        let seat = self.get_seat(client)?;
+        match seat {
+            Seat::Spectator => self.spectators.retain(|spectator| *spectator != client),
+            _ => self.set_player(seat, None),
+        }
+        return Some(seat);
+    }
+
+    // Get where a client sits, None if it has not joined
+    pub fn get_seat(&self, client: ClientId) -> Option<Seat> {
+        if self.white == Some(client) {
+            return Some(Seat::White);
+        }
+        if self.black == Some(client) {
+            return Some(Seat::Black);
+        }
+        if self.spectators.contains(&client) {
+            return Some(Seat::Spectator);
+        }
+        return None;
+    }
+
+    pub fn get_player(&self, color: ChessPieceColor) -> Option<ClientId> {
+        if color == ChessPieceColor::White {
+            return self.white;
+        } else {
+            return self.black;
+        }
+    }
+
+    // Get all clients, the players first
+    pub fn get_clients(&self) -> Vec<ClientId> {
+        let mut clients: Vec<ClientId> = self
+            .white
+            .iter()
+            .chain(self.black.iter())
+            .copied()
+            .collect();
+        clients.extend_from_slice(&self.spectators);
+        return clients;
+    }
+
+    // Play a move from a client if it sits on the color to move
+    pub fn play_move(
+        &self,
+        client: ClientId,
+        game: &mut Game,
+        chess_move: ChessMove,
+    ) -> Result<PlayedMove, MoveRejection> {
+        let color = match self.get_seat(client).and_then(|seat| seat.get_color()) {
+            Some(color) => color,
+            None => return Err(MoveRejection::Spectator),
+        };
+        if color != game.turn {
+            return Err(MoveRejection::WrongSeat { turn: game.turn });
+        }
+        return game
+            .play_move(chess_move.board_move, chess_move.promote_piece)
+            .map_err(MoveRejection::Illegal);
+    }
+
+    fn set_player(&mut self, seat: Seat, client: Option<ClientId>) {
+        match seat {
+            Seat::White => self.white = client,
+            Seat::Black => self.black = client,
+            Seat::Spectator => {}
+        }
+    }
+}
+
+// A game on a server with its players and its clock
+pub struct OnlineGame {
+    pub id: GameId,
+    pub game: Game,
+    pub seats: Seats,
+    clock: Option<Clock>, // None for games without a time control
+    status: GameStatus,   // updated after every move, so the lobby can read it
+}
+
+impl OnlineGame {
+    pub fn new(id: GameId, time_control: Option<TimeControl>) -> Self {
+        let mut game = Game::new();
+        game.set_up_board();
+        return OnlineGame {
+            id,
+            game,
+            seats: Seats::new(),
+            clock: time_control.map(Clock::new),
+            status: GameStatus::Ongoing,
+        };
+    }
+
+    pub fn get_time_control(&self) -> Option<&TimeControl> {
+        return self.clock.as_ref().map(|clock| clock.get_time_control());
+    }
+
+    // Get the time left of both players, and if the clock is running
+    pub fn get_clock_times(&self, now: Instant) -> Option<(Duration, Duration, bool)> {
+        let clock = self.clock.as_ref()?;
+        return Some((
+            clock.get_remaining(ChessPieceColor::White, now),
+            clock.get_remaining(ChessPieceColor::Black, now),
+            clock.is_running(),
+        ));
+    }
+
+    // Get the time left of the player whose clock is running, None while no clock is running
+    pub fn get_time_left(&self, now: Instant) -> Option<Duration> {
+        let clock = self.clock.as_ref()?;
+        if !clock.is_running() {
+            return None;
+        }
+        return Some(clock.get_remaining(clock.get_turn(), now));
+    }
+
+    // Returns true if a color has no player and the game can still be played
+    pub fn is_open(&self) -> bool {
+        return !self.has_started() && !self.status.is_over();
+    }
+
+    // Returns true once both colors have a player, moves are only played then
+    pub fn has_started(&self) -> bool {
+        return self.seats.get_player(ChessPieceColor::White).is_some()
+            && self.seats.get_player(ChessPieceColor::Black).is_some();
+    }
+
+    pub fn get_status(&self) -> GameStatus {
+        return self.status;
+    }
+
+    // Play a move from a client and press the clock for it
+    pub fn play_move(
+        &mut self,
+        client: ClientId,
+        chess_move: ChessMove,
+        now: Instant,
+    ) -> Result<PlayedMove, MoveRejection> {
+        // a player out of time has lost, even if the flag was not seen before the move came
+        self.check_timeout(now);
+        if !self.has_started() && !self.status.is_over() {
+            return Err(MoveRejection::NotStarted);
+        }
+        let color = self.game.turn;
+        let played_move = self.seats.play_move(client, &mut self.game, chess_move)?;
+        if let Some(clock) = self.clock.as_mut() {
+            // the move only counts if the clock could be pressed for it
+            if let Err(error) = clock.press(color, now) {
+                self.game.undo_move();
+                return Err(MoveRejection::Clock(error));
+            }
+        }
+        self.status = self.game.game_status();
+        if let Some(clock) = self.clock.as_mut() {
+            if self.status.is_over() {
+                clock.stop(now);
+            }
+        }
+        return Ok(played_move);
+    }
+
+    // Ends the game if the player to move ran out of time. Returns that player, only once
+    pub fn check_timeout(&mut self, now: Instant) -> Option<ChessPieceColor> {
+        let clock = self.clock.as_mut()?;
+        if clock.check_timeout(&mut self.game, now) {
+            self.status = self.game.game_status();
+            return clock.get_flagged();
+        }
+        return None;
+    }
+
+    fn join(&mut self, client: ClientId, wanted: Option<Seat>, now: Instant) -> Seat {
+        // the color of a player that left a finished game is not played again
+        let wanted = if self.status.is_over() {
+            Some(Seat::Spectator)
+        } else {
+            wanted
+        };
+        let seat = self.seats.join(client, wanted);
+        // the clock starts when both players have sat down
+        let is_full = self.has_started();
+        if let Some(clock) = self.clock.as_mut() {
+            if is_full && !self.status.is_over() {
+                let _clock_error = clock.start(now);
+            }
+        }
+        return seat;
+    }
+
+    // Remove a client, a player leaving a game that has started loses it.
+    // Returns the seat the client had
+    fn leave(&mut self, client: ClientId, now: Instant) -> Option<Seat> {
+        let seat = self.seats.get_seat(client)?;
+        if let Some(color) = seat.get_color() {
+            if self.has_started() && self.game.resign(color).is_ok() {
+                self.status = self.game.game_status();
+                if let Some(clock) = self.clock.as_mut() {
+                    clock.stop(now);
+                }
+            }
+        }
+        return self.seats.leave(client);
+    }
+}
+
+// What a client is told about a game in the lobby
+#[derive(Clone, PartialEq, Debug)]
+pub struct GameInfo {
+    pub id: GameId,
+    pub time_control: Option<TimeControl>,
+    pub white: Option<String>, // the names of the players, None for a free color
+    pub black: Option<String>,
+    pub spectators: u32,
+    pub is_over: bool,
+}
+
+// Why the lobby could not do what a client asked for
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LobbyError {
+    NoGame { game_id: GameId },
+    NotInGame,
+}
+
+impl fmt::Display for LobbyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LobbyError::NoGame { game_id } => return write!(f, "There is no game {}", game_id),
+            LobbyError::NotInGame => return write!(f, "Not in a game"),
+        }
+    }
+}
+
+impl std::error::Error for LobbyError {}
+
+// The games on a server and the clients waiting to be paired. Every game is played on
+// its own, a client is in at most one game or waiting in the queue
+#[derive(Default)]
+pub struct Lobby {
+    games: Vec<OnlineGame>,
+    queue: Vec<(ClientId, Option<TimeControl>)>, // the client that has waited longest first
+    next_game_id: GameId,
+}
+
+impl Lobby {
+    pub fn new() -> Self {
+        return Lobby::default();
+    }
+
+    pub fn get_games(&self) -> &[OnlineGame] {
+        return &self.games;
+    }
+
+    pub fn get_game(&self, game_id: GameId) -> Option<&OnlineGame> {
+        return self.games.iter().find(|game| game.id == game_id);
+    }
+
+    pub fn get_game_mut(&mut self, game_id: GameId) -> Option<&mut OnlineGame> {
+        return self.games.iter_mut().find(|game| game.id == game_id);
+    }
+
+    // Get the game a client plays or watches
+    pub fn get_game_of(&self, client: ClientId) -> Option<GameId> {
+        return self
+            .games
+            .iter()
+            .find(|game| game.seats.get_seat(client).is_some())
+            .map(|game| game.id);
+    }
+
+    pub fn is_queued(&self, client: ClientId) -> bool {
+        return self.queue.iter().any(|(queued, _)| *queued == client);
+    }
+
+    // Start a game and seat the client on the color it wants, or the first free color
+    pub fn create_game(
+        &mut self,
+        client: ClientId,
+        time_control: Option<TimeControl>,
+        color: Option<ChessPieceColor>,
+        now: Instant,
+    ) -> (GameId, Seat) {
+        self.leave(client, now);
+        let game_id = self.next_game_id;
+        self.next_game_id += 1;
+        let mut game = OnlineGame::new(game_id, time_control);
+        let seat = game.join(client, color.map(Seat::from_color), now);
+        self.games.push(game);
+        return (game_id, seat);
+    }
+
+    // Play a game on a free color, or watch it. A client that wants to play a game where
+    // both colors are taken gets to watch it. A client already in the game keeps its seat
+    pub fn join_game(
+        &mut self,
+        client: ClientId,
+        game_id: GameId,
+        spectate: bool,
+        now: Instant,
+    ) -> Result<Seat, LobbyError> {
+        let game = match self.get_game(game_id) {
+            Some(game) => game,
+            None => return Err(LobbyError::NoGame { game_id }),
+        };
+        if let Some(seat) = game.seats.get_seat(client) {
+            return Ok(seat);
+        }
+        self.leave(client, now);
+        let wanted = if spectate {
+            Some(Seat::Spectator)
+        } else {
+            None
+        };
+        let game = self.get_game_mut(game_id).unwrap();
+        return Ok(game.join(client, wanted, now));
+    }
+
+    // Pair the client with the client that has waited longest for the same time control,
+    // the one that waited plays White. Returns the new game, or None if the client has to wait
+    pub fn find_game(
+        &mut self,
+        client: ClientId,
+        time_control: Option<TimeControl>,
+        now: Instant,
+    ) -> Option<GameId> {
+        self.leave(client, now);
+        let index = self
+            .queue
+            .iter()
+            .position(|(_, wanted)| *wanted == time_control);
+        let opponent = match index {
+            Some(index) => self.queue.remove(index).0,
+            None => {
+                self.queue.push((client, time_control));
+                return None;
+            }
+        };
+        let (game_id, _) =
+            self.create_game(opponent, time_control, Some(ChessPieceColor::White), now);
+        self.get_game_mut(game_id)
+            .unwrap()
+            .join(client, Some(Seat::Black), now);
+        return Some(game_id);
+    }
+
+    // Leave the game or the queue, games without clients are removed.
+    // Returns the game the client left
+    pub fn leave(&mut self, client: ClientId, now: Instant) -> Option<GameId> {
+        self.queue.retain(|(queued, _)| *queued != client);
+        let game_id = self.get_game_of(client)?;
+        let game = self.get_game_mut(game_id).unwrap();
+        game.leave(client, now);
+        if game.seats.get_clients().is_empty() {
+            self.games.retain(|game| game.id != game_id);
+        }
+        return Some(game_id);
+    }
+
+    // Ends the games where the player to move ran out of time, returns the games and the
+    // players that lost
+    pub fn check_timeouts(&mut self, now: Instant) -> Vec<(GameId, ChessPieceColor)> {
+        let mut timeouts = Vec::new();
+        for game in self.games.iter_mut() {
+            if let Some(color) = game.check_timeout(now) {
+                timeouts.push((game.id, color));
+            }
+        }
+        return timeouts;
+    }
+}
@@ -0,0 +1,573 @@
+// Reading and writing games in Portable Game Notation (PGN)
+use std::io::BufRead;
+
+use crate::chess_game::{ChessPieceColor, Game};
+
+// The tags every game has, written first and in this order
+pub const SEVEN_TAG_ROSTER: [&str; 7] =
+    ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
+
+// Lines in the movetext are wrapped to this length when writing
+const MAX_LINE_LENGTH: usize = 80;
+
+// A move in the movetext, with its annotations and alternative lines
+#[derive(Clone, Default)]
+pub struct PgnMove {
+    pub san: String,
+    pub nags: Vec<u8>, // Numeric annotation glyphs, ex. 1 for "!" and 2 for "?"
+    pub comments_before: Vec<String>,
+    pub comments: Vec<String>,
+    pub variations: Vec<Vec<PgnMove>>, // Lines played instead of this move
+}
+
+impl PgnMove {
+    pub fn new(san: &str) -> Self {
+        Self {
+            san: san.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>,
+    pub moves: Vec<PgnMove>,
+    pub result: String, // "1-0", "0-1", "1/2-1/2" or "*" if the game is not finished
+}
+
+impl Default for PgnGame {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PgnGame {
+    // Create a game with the seven tag roster set to unknown values
+    pub fn new() -> Self {
+        let mut tags: Vec<(String, String)> = Vec::new();
+        for name in SEVEN_TAG_ROSTER.iter() {
+            tags.push((name.to_string(), get_unknown_tag_value(name).to_string()));
+        }
+        Self {
+            tags,
+            moves: Vec::new(),
+            result: "*".to_string(),
+        }
+    }
+
+    pub fn get_tag(&self, name: &str) -> Option<&str> {
+        for (tag_name, value) in self.tags.iter() {
+            if tag_name == name {
+                return Some(value.as_str());
+            }
+        }
+        return None;
+    }
+
+    // Set a tag, replacing the value if the tag already exists
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        for (tag_name, tag_value) in self.tags.iter_mut() {
+            if tag_name == name {
+                *tag_value = value.to_string();
+                return;
+            }
+        }
+        self.tags.push((name.to_string(), value.to_string()));
+    }
+
+    // Set the result of the game, also updates the Result tag
+    pub fn set_result(&mut self, result: &str) {
+        self.result = result.to_string();
+        self.set_tag("Result", result);
+    }
+
+    // Parse a single game
+    pub fn parse(text: &str) -> Result<PgnGame, String> {
+        let tokens = tokenize(text)?;
+        let mut game = PgnGame {
+            tags: Vec::new(),
+            moves: Vec::new(),
+            result: "*".to_string(),
+        };
+
+        // Tag pair section
+        let mut index = 0;
+        while index < tokens.len() && tokens[index] == Token::TagOpen {
+            let name = match tokens.get(index + 1) {
+                Some(Token::Symbol(name)) => name.clone(),
+                _ => return Err("Expected tag name after '['".to_string()),
+            };
+            let value = match tokens.get(index + 2) {
+                Some(Token::Text(value)) => value.clone(),
+                _ => return Err(format!("Expected a quoted value for tag {}", name)),
+            };
+            if tokens.get(index + 3) != Some(&Token::TagClose) {
+                return Err(format!("Expected ']' after tag {}", name));
+            }
+            game.set_tag(&name, &value);
+            index += 4;
+        }
+
+        // Movetext section
+        let (moves, result) = parse_line(&tokens, &mut index, 0)?;
+        if index < tokens.len() {
+            return Err("Unexpected text after the game result".to_string());
+        }
+        game.moves = moves;
+        game.result = match result {
+            Some(result) => result,
+            None => game.get_tag("Result").unwrap_or("*").to_string(),
+        };
+        return Ok(game);
+    }
+
+    // Get the position the game starts from, set by the FEN tag if it exists
+    pub fn get_start_position(&self) -> Result<Game, String> {
+        let mut game = match self.get_tag("FEN") {
+            Some(fen) => Game::from_fen(fen)?,
+            None => {
+                let mut game = Game::new();
+                game.set_up_board();
+                game
+            }
+        };
+        // Chess960 games can start from the standard position too, then only the tag tells
+        let variant = self.get_tag("Variant").unwrap_or("");
+        if variant.eq_ignore_ascii_case("chess960") {
+            game.set_chess960(true);
+        }
+        return Ok(game);
+    }
+
+    // Play all moves, including variations, and get the final position of the main line
+    pub fn replay(&self) -> Result<Game, String> {
+        let mut game = self.get_start_position()?;
+        replay_moves(&mut game, &self.moves)?;
+        return Ok(game);
+    }
+
+    // Write the game in PGN export format
+    pub fn to_pgn(&self) -> String {
+        let mut text = String::new();
+
+        // The seven tag roster comes first, followed by the other tags
+        for name in SEVEN_TAG_ROSTER.iter() {
+            let value = if *name == "Result" {
+                self.result.as_str()
+            } else {
+                self.get_tag(name)
+                    .unwrap_or_else(|| get_unknown_tag_value(name))
+            };
+            text.push_str(&format_tag(name, value));
+        }
+        for (name, value) in self.tags.iter() {
+            if !SEVEN_TAG_ROSTER.contains(&name.as_str()) {
+                text.push_str(&format_tag(name, value));
+            }
+        }
+        text.push('\n');
+
+        // Move numbers continue from the start position
+        let (move_number, turn) = match self.get_start_position() {
+            Ok(game) => (game.get_full_move_number(), game.turn),
+            Err(_) => (1, ChessPieceColor::White),
+        };
+        let mut words: Vec<String> = Vec::new();
+        write_moves(&mut words, &self.moves, move_number, turn);
+        words.push(self.result.clone());
+
+        // Wrap the movetext
+        let mut line = String::new();
+        for word in words {
+            if !line.is_empty() && line.len() + 1 + word.len() > MAX_LINE_LENGTH {
+                text.push_str(&line);
+                text.push('\n');
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&word);
+        }
+        text.push_str(&line);
+        text.push_str("\n\n");
+        return text;
+    }
+}
+
+// Reads games one at a time, so large databases do not have to fit in memory
+pub struct PgnReader<R: BufRead> {
+    reader: R,
+    next_line: Option<String>, // The first line of the next game, read while finding the end
+}
+
+impl<R: BufRead> PgnReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            next_line: None,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for PgnReader<R> {
+    type Item = Result<PgnGame, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut text = String::new();
+        let mut in_movetext = false;
+        let mut in_comment = false;
+        let mut has_tags = false;
+        let mut tags_ended = false; // An empty line after the tags, a game can have no movetext
+        loop {
+            let line = match self.next_line.take() {
+                Some(line) => line,
+                None => {
+                    let mut line = String::new();
+                    match self.reader.read_line(&mut line) {
+                        Ok(0) => break,
+                        Ok(_) => line,
+                        Err(error) => return Some(Err(error.to_string())),
+                    }
+                }
+            };
+            let trimmed = line.trim_start_matches('\u{feff}').trim();
+            if !in_comment && trimmed.starts_with('[') {
+                // A tag after the movetext or after the end of the tags starts the next game
+                if in_movetext || tags_ended {
+                    self.next_line = Some(line);
+                    break;
+                }
+                has_tags = true;
+            } else if trimmed.is_empty() {
+                tags_ended = has_tags;
+            } else if !in_comment && !trimmed.starts_with('%') {
+                in_movetext = true;
+            }
+            if in_movetext {
+                in_comment = is_comment_open_after(&line, in_comment);
+            }
+            text.push_str(&line);
+        }
+        if text.trim().is_empty() {
+            return None;
+        }
+        return Some(PgnGame::parse(&text));
+    }
+}
+
+// The value used for seven tag roster tags when it is not known
+fn get_unknown_tag_value(name: &str) -> &'static str {
+    match name {
+        "Date" => return "????.??.??",
+        "Result" => return "*",
+        _ => return "?",
+    }
+}
+
+#[derive(Clone, PartialEq)]
+enum Token {
+    TagOpen,
+    TagClose,
+    Text(String),
+    Symbol(String),
+    Period,
+    Comment(String),
+    Nag(u8),
+    VariationOpen,
+    VariationClose,
+    Unfinished,
+}
+
+// Returns true if a brace comment is still open at the end of the line
+fn is_comment_open_after(line: &str, mut in_comment: bool) -> bool {
+    for letter in line.chars() {
+        if in_comment {
+            if letter == '}' {
+                in_comment = false;
+            }
+        } else if letter == '{' {
+            in_comment = true;
+        } else if letter == ';' {
+            break;
+        }
+    }
+    return in_comment;
+}
+
+fn is_symbol_letter(letter: char) -> bool {
+    return letter.is_ascii_alphanumeric() || "_+#=:-/".contains(letter);
+}
+
+// Get the numeric annotation glyph for move suffixes like "!?"
+fn get_suffix_nag(suffix: &str) -> Result<u8, String> {
+    match suffix {
+        "!" => return Ok(1),
+        "?" => return Ok(2),
+        "!!" => return Ok(3),
+        "??" => return Ok(4),
+        "!?" => return Ok(5),
+        "?!" => return Ok(6),
+        _ => return Err(format!("Invalid move suffix '{}'", suffix)),
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens: Vec<Token> = Vec::new();
+    let chars: Vec<char> = text.chars().collect();
+    let mut line_number = 1;
+    let mut index = 0;
+    while index < chars.len() {
+        let letter = chars[index];
+        let at_line_start = index == 0 || chars[index - 1] == '\n';
+        if letter == '\n' {
+            line_number += 1;
+            index += 1;
+        } else if letter.is_whitespace() || letter == '\u{feff}' {
+            index += 1;
+        } else if letter == '%' && at_line_start {
+            // Escaped lines are ignored
+            while index < chars.len() && chars[index] != '\n' {
+                index += 1;
+            }
+        } else if letter == ';' {
+            // Comment to the end of the line
+            let start = index + 1;
+            while index < chars.len() && chars[index] != '\n' {
+                index += 1;
+            }
+            let comment: String = chars[start..index].iter().collect();
+            tokens.push(Token::Comment(comment.trim().to_string()));
+        } else if letter == '{' {
+            let start = index + 1;
+            while index < chars.len() && chars[index] != '}' {
+                if chars[index] == '\n' {
+                    line_number += 1;
+                }
+                index += 1;
+            }
+            if index >= chars.len() {
+                return Err(format!("Unclosed comment on line {}", line_number));
+            }
+            let comment: String = chars[start..index].iter().collect();
+            tokens.push(Token::Comment(comment.trim().to_string()));
+            index += 1;
+        } else if letter == '"' {
+            let mut value = String::new();
+            index += 1;
+            loop {
+                if index >= chars.len() || chars[index] == '\n' {
+                    return Err(format!("Unclosed string on line {}", line_number));
+                }
+                if chars[index] == '"' {
+                    break;
+                }
+                if chars[index] == '\\' && index + 1 < chars.len() {
+                    index += 1;
+                }
+                value.push(chars[index]);
+                index += 1;
+            }
+            tokens.push(Token::Text(value));
+            index += 1;
+        } else if letter == '$' {
+            let start = index + 1;
+            index += 1;
+            while index < chars.len() && chars[index].is_ascii_digit() {
+                index += 1;
+            }
+            let number: String = chars[start..index].iter().collect();
+            match number.parse::<u8>() {
+                Ok(nag) => tokens.push(Token::Nag(nag)),
+                Err(_) => return Err(format!("Invalid NAG on line {}", line_number)),
+            }
+        } else if letter == '!' || letter == '?' {
+            let start = index;
+            while index < chars.len() && (chars[index] == '!' || chars[index] == '?') {
+                index += 1;
+            }
+            let suffix: String = chars[start..index].iter().collect();
+            tokens.push(Token::Nag(get_suffix_nag(&suffix)?));
+        } else if is_symbol_letter(letter) {
+            let start = index;
+            while index < chars.len() && is_symbol_letter(chars[index]) {
+                index += 1;
+            }
+            tokens.push(Token::Symbol(chars[start..index].iter().collect()));
+        } else {
+            tokens.push(match letter {
+                '[' => Token::TagOpen,
+                ']' => Token::TagClose,
+                '(' => Token::VariationOpen,
+                ')' => Token::VariationClose,
+                '.' => Token::Period,
+                '*' => Token::Unfinished,
+                _ => {
+                    return Err(format!(
+                        "Unexpected character '{}' on line {}",
+                        letter, line_number
+                    ))
+                }
+            });
+            index += 1;
+        }
+    }
+    return Ok(tokens);
+}
+
+// Parse moves until the end of a variation or the game result
+fn parse_line(
+    tokens: &[Token],
+    index: &mut usize,
+    depth: usize,
+) -> Result<(Vec<PgnMove>, Option<String>), String> {
+    let mut moves: Vec<PgnMove> = Vec::new();
+    let mut comments_before: Vec<String> = Vec::new();
+    while *index < tokens.len() {
+        let token = tokens[*index].clone();
+        *index += 1;
+        match token {
+            Token::Comment(comment) => match moves.last_mut() {
+                Some(last_move) => last_move.comments.push(comment),
+                None => comments_before.push(comment),
+            },
+            Token::Nag(nag) => match moves.last_mut() {
+                Some(last_move) => last_move.nags.push(nag),
+                None => return Err("Annotation before the first move".to_string()),
+            },
+            Token::VariationOpen => {
+                let (variation, result) = parse_line(tokens, index, depth + 1)?;
+                if result.is_some() {
+                    return Err("Game result inside a variation".to_string());
+                }
+                match moves.last_mut() {
+                    Some(last_move) => last_move.variations.push(variation),
+                    None => return Err("Variation before the first move".to_string()),
+                }
+            }
+            Token::VariationClose => {
+                if depth == 0 {
+                    return Err("Unexpected ')' outside a variation".to_string());
+                }
+                return Ok((moves, None));
+            }
+            Token::Unfinished => {
+                return Ok((moves, Some("*".to_string())));
+            }
+            Token::Period => {}
+            Token::Symbol(symbol) => {
+                if symbol == "1-0" || symbol == "0-1" || symbol == "1/2-1/2" {
+                    return Ok((moves, Some(symbol)));
+                }
+                // Skip move numbers
+                if symbol.chars().all(|letter| letter.is_ascii_digit()) {
+                    continue;
+                }
+                let mut pgn_move = PgnMove::new(&symbol);
+                pgn_move.comments_before = comments_before;
+                comments_before = Vec::new();
+                moves.push(pgn_move);
+            }
+            Token::TagOpen | Token::TagClose | Token::Text(_) => {
+                return Err("Unexpected tag in the movetext".to_string());
+            }
+        }
+    }
+    if depth > 0 {
+        return Err("Unclosed variation".to_string());
+    }
+    return Ok((moves, None));
+}
+
+fn replay_moves(game: &mut Game, moves: &[PgnMove]) -> Result<(), String> {
+    for pgn_move in moves.iter() {
+        // Variations are played from the position before the move they replace
+        for variation in pgn_move.variations.iter() {
+            let mut variation_game = game.clone();
+            replay_moves(&mut variation_game, variation)?;
+        }
+        let move_number = game.get_full_move_number();
+        let dots = if game.turn == ChessPieceColor::White {
+            "."
+        } else {
+            "..."
+        };
+        if let Err(error) = game.algebraic_notation_move(pgn_move.san.clone()) {
+            return Err(format!(
+                "Invalid move {}{} {}: {}",
+                move_number, dots, pgn_move.san, error
+            ));
+        }
+    }
+    return Ok(());
+}
+
+fn format_tag(name: &str, value: &str) -> String {
+    let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+    return format!("[{} \"{}\"]\n", name, value);
+}
+
+// Add a comment as words so it can be wrapped
+fn write_comment(words: &mut Vec<String>, comment: &str) {
+    let mut comment_words: Vec<String> = comment
+        .replace('}', "")
+        .split_whitespace()
+        .map(|word| word.to_string())
+        .collect();
+    if comment_words.is_empty() {
+        comment_words.push(String::new());
+    }
+    comment_words[0].insert(0, '{');
+    comment_words.last_mut().unwrap().push('}');
+    words.append(&mut comment_words);
+}
+
+fn write_moves(
+    words: &mut Vec<String>,
+    moves: &[PgnMove],
+    mut move_number: u32,
+    mut turn: ChessPieceColor,
+) {
+    // Black moves need a number at the start of a line and after comments and variations
+    let mut write_number = true;
+    for pgn_move in moves.iter() {
+        for comment in pgn_move.comments_before.iter() {
+            write_comment(words, comment);
+            write_number = true;
+        }
+        if turn == ChessPieceColor::White {
+            words.push(format!("{}.", move_number));
+        } else if write_number {
+            words.push(format!("{}...", move_number));
+        }
+        words.push(pgn_move.san.clone());
+        write_number = false;
+
+        for nag in pgn_move.nags.iter() {
+            words.push(format!("${}", nag));
+        }
+        for comment in pgn_move.comments.iter() {
+            write_comment(words, comment);
+            write_number = true;
+        }
+        for variation in pgn_move.variations.iter() {
+            let mut variation_words: Vec<String> = Vec::new();
+            write_moves(&mut variation_words, variation, move_number, turn);
+            if variation_words.is_empty() {
+                continue;
+            }
+            variation_words[0].insert(0, '(');
+            variation_words.last_mut().unwrap().push(')');
+            words.append(&mut variation_words);
+            write_number = true;
+        }
+
+        if turn == ChessPieceColor::White {
+            turn = ChessPieceColor::Black;
+        } else {
+            turn = ChessPieceColor::White;
+            move_number += 1;
+        }
+    }
+}
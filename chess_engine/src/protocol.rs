@@ -0,0 +1,840 @@
+// The network protocol between the chess server and its clients. Every message is sent as a
+// frame, a 4 byte big endian length followed by the message. A message starts with a byte
+// telling its kind, numbers are big endian and strings have a 4 byte length before the UTF-8.
+//
+// The client starts with Hello, carrying the protocol version, and the server answers with
+// Welcome or Rejected. A server only talks to clients with a version it supports, so Hello,
+// Welcome and Rejected must look the same in every version.
+//
+// After the handshake the client is in the lobby, where it can list, create and join games,
+// or wait to be paired with FindGame. Moves and chat go to the game the client is in, and the
+// others in the game are told with PlayerLeft when a player leaves it
+use crate::chess_game::{ChessMove, ChessPieceColor, MoveError};
+use crate::clock::{ClockError, TimeControl};
+use crate::online::{GameId, GameInfo, LobbyError, MoveRejection, Seat};
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::time::Duration;
+
+pub const PROTOCOL_VERSION: u32 = 4;
+pub const MIN_PROTOCOL_VERSION: u32 = 4; // The oldest version that can still be talked to
+pub const MAX_FRAME_SIZE: usize = 64 * 1024;
+
+// Sent first in Hello so other programs connecting to the port are found at once
+const MAGIC: &[u8; 4] = b"VCHS";
+
+// Move errors are sent as their index here, new errors must be added last
+const MOVE_ERRORS: [MoveError; 19] = [
+    MoveError::InvalidNotation,
+    MoveError::OutsideBoard,
+    MoveError::NoPiece,
+    MoveError::OpponentPiece,
+    MoveError::NullMove,
+    MoveError::OwnPieceOnTarget,
+    MoveError::InvalidPieceMove,
+    MoveError::PathBlocked,
+    MoveError::NothingToCapture,
+    MoveError::PieceHasMoved,
+    MoveError::LeavesKingInCheck,
+    MoveError::CastleThroughCheck,
+    MoveError::NoCastlingRook,
+    MoveError::NoKing,
+    MoveError::PromotionRequired,
+    MoveError::InvalidPromotion,
+    MoveError::AmbiguousMove,
+    MoveError::NoMatchingPiece,
+    MoveError::GameOver,
+];
+
+// Why a message could not be sent or read
+#[derive(Debug)]
+pub enum ProtocolError {
+    Io(io::Error),
+    Closed,
+    FrameTooLarge(usize),
+    InvalidMessage(String),
+    IncompatibleVersion { version: u32 },
+    Rejected(String),
+}
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ProtocolError::Io(error) => return write!(f, "Connection error: {}", error),
+            ProtocolError::Closed => return write!(f, "The connection was closed"),
+            ProtocolError::FrameTooLarge(size) => {
+                return write!(
+                    f,
+                    "Message of {} bytes is larger than {} bytes",
+                    size, MAX_FRAME_SIZE
+                )
+            }
+            ProtocolError::InvalidMessage(reason) => {
+                return write!(f, "Invalid message: {}", reason)
+            }
+            ProtocolError::IncompatibleVersion { version } => {
+                return write!(
+                    f,
+                    "Protocol version {} is not supported, versions {} to {} are",
+                    version, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION
+                )
+            }
+            ProtocolError::Rejected(reason) => {
+                return write!(f, "Rejected by the server: {}", reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProtocolError {}
+
+impl From<io::Error> for ProtocolError {
+    fn from(error: io::Error) -> Self {
+        if error.kind() == io::ErrorKind::UnexpectedEof {
+            return ProtocolError::Closed;
+        }
+        return ProtocolError::Io(error);
+    }
+}
+
+// Returns true if a peer with the protocol version can be talked to
+pub fn is_compatible(version: u32) -> bool {
+    return (MIN_PROTOCOL_VERSION..=PROTOCOL_VERSION).contains(&version);
+}
+
+// Messages from a client to the server
+#[derive(Clone, PartialEq, Debug)]
+pub enum ClientMessage {
+    Hello {
+        version: u32,
+        name: String,
+    },
+    Move {
+        chess_move: ChessMove,
+    },
+    GetPosition,
+    Chat {
+        text: String,
+    },
+    ListGames,
+    CreateGame {
+        time_control: Option<TimeControl>,
+        color: Option<ChessPieceColor>, // None to play any color
+    },
+    JoinGame {
+        game_id: GameId,
+        spectate: bool,
+    },
+    FindGame {
+        time_control: Option<TimeControl>,
+    },
+    LeaveGame,
+}
+
+// Messages from the server to a client
+#[derive(Clone, PartialEq, Debug)]
+pub enum ServerMessage {
+    Welcome {
+        version: u32,
+    },
+    Rejected {
+        reason: String,
+    },
+    Seated {
+        game_id: GameId,
+        seat: Seat,
+        time_control: Option<TimeControl>,
+    },
+    Moved {
+        chess_move: ChessMove,
+        san: String,
+        fen: String,
+    },
+    MoveRejected {
+        rejection: MoveRejection,
+    },
+    Position {
+        fen: String,
+    },
+    Chat {
+        name: String,
+        text: String,
+    },
+    GameList {
+        games: Vec<GameInfo>,
+    },
+    Queued,
+    LobbyError {
+        error: LobbyError,
+    },
+    Clock {
+        white: Duration,
+        black: Duration,
+        running: bool,
+    },
+    TimeUp {
+        color: ChessPieceColor,
+    },
+    PlayerLeft {
+        seat: Seat,
+    },
+}
+
+impl ClientMessage {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = MessageWriter::new();
+        match self {
+            ClientMessage::Hello { version, name } => {
+                writer.put_u8(0);
+                writer.put_bytes(MAGIC);
+                writer.put_u32(*version);
+                writer.put_string(name);
+            }
+            ClientMessage::Move { chess_move } => {
+                writer.put_u8(1);
+                writer.put_move(chess_move);
+            }
+            ClientMessage::GetPosition => writer.put_u8(2),
+            ClientMessage::Chat { text } => {
+                writer.put_u8(3);
+                writer.put_string(text);
+            }
+            ClientMessage::ListGames => writer.put_u8(4),
+            ClientMessage::CreateGame {
+                time_control,
+                color,
+            } => {
+                writer.put_u8(5);
+                writer.put_time_control(time_control.as_ref());
+                writer.put_option_color(*color);
+            }
+            ClientMessage::JoinGame { game_id, spectate } => {
+                writer.put_u8(6);
+                writer.put_u64(*game_id);
+                writer.put_bool(*spectate);
+            }
+            ClientMessage::FindGame { time_control } => {
+                writer.put_u8(7);
+                writer.put_time_control(time_control.as_ref());
+            }
+            ClientMessage::LeaveGame => writer.put_u8(8),
+        }
+        return writer.bytes;
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<ClientMessage, ProtocolError> {
+        let mut reader = MessageReader::new(bytes);
+        let message = match reader.get_u8()? {
+            0 => {
+                if reader.get_bytes(MAGIC.len())? != MAGIC {
+                    return Err(ProtocolError::InvalidMessage(
+                        "Not a chess client".to_string(),
+                    ));
+                }
+                ClientMessage::Hello {
+                    version: reader.get_u32()?,
+                    name: reader.get_string()?,
+                }
+            }
+            1 => ClientMessage::Move {
+                chess_move: reader.get_move()?,
+            },
+            2 => ClientMessage::GetPosition,
+            3 => ClientMessage::Chat {
+                text: reader.get_string()?,
+            },
+            4 => ClientMessage::ListGames,
+            5 => ClientMessage::CreateGame {
+                time_control: reader.get_time_control()?,
+                color: reader.get_option_color()?,
+            },
+            6 => ClientMessage::JoinGame {
+                game_id: reader.get_u64()?,
+                spectate: reader.get_bool()?,
+            },
+            7 => ClientMessage::FindGame {
+                time_control: reader.get_time_control()?,
+            },
+            8 => ClientMessage::LeaveGame,
+            kind => return Err(get_unknown_kind_error(kind)),
+        };
+        reader.finish()?;
+        return Ok(message);
+    }
+}
+
+impl ServerMessage {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = MessageWriter::new();
+        match self {
+            ServerMessage::Welcome { version } => {
+                writer.put_u8(0);
+                writer.put_u32(*version);
+            }
+            ServerMessage::Rejected { reason } => {
+                writer.put_u8(1);
+                writer.put_string(reason);
+            }
+            ServerMessage::Moved {
+                chess_move,
+                san,
+                fen,
+            } => {
+                writer.put_u8(2);
+                writer.put_move(chess_move);
+                writer.put_string(san);
+                writer.put_string(fen);
+            }
+            ServerMessage::MoveRejected { rejection } => {
+                writer.put_u8(3);
+                writer.put_rejection(rejection);
+            }
+            ServerMessage::Position { fen } => {
+                writer.put_u8(4);
+                writer.put_string(fen);
+            }
+            ServerMessage::Chat { name, text } => {
+                writer.put_u8(5);
+                writer.put_string(name);
+                writer.put_string(text);
+            }
+            ServerMessage::Seated {
+                game_id,
+                seat,
+                time_control,
+            } => {
+                writer.put_u8(6);
+                writer.put_u64(*game_id);
+                writer.put_seat(*seat);
+                writer.put_time_control(time_control.as_ref());
+            }
+            ServerMessage::GameList { games } => {
+                writer.put_u8(7);
+                writer.put_u32(games.len() as u32);
+                for game in games.iter() {
+                    writer.put_game_info(game);
+                }
+            }
+            ServerMessage::Queued => writer.put_u8(8),
+            ServerMessage::LobbyError { error } => {
+                writer.put_u8(9);
+                writer.put_lobby_error(error);
+            }
+            ServerMessage::Clock {
+                white,
+                black,
+                running,
+            } => {
+                writer.put_u8(10);
+                writer.put_duration(*white);
+                writer.put_duration(*black);
+                writer.put_bool(*running);
+            }
+            ServerMessage::TimeUp { color } => {
+                writer.put_u8(11);
+                writer.put_color(*color);
+            }
+            ServerMessage::PlayerLeft { seat } => {
+                writer.put_u8(12);
+                writer.put_seat(*seat);
+            }
+        }
+        return writer.bytes;
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<ServerMessage, ProtocolError> {
+        let mut reader = MessageReader::new(bytes);
+        let message = match reader.get_u8()? {
+            0 => ServerMessage::Welcome {
+                version: reader.get_u32()?,
+            },
+            1 => ServerMessage::Rejected {
+                reason: reader.get_string()?,
+            },
+            2 => ServerMessage::Moved {
+                chess_move: reader.get_move()?,
+                san: reader.get_string()?,
+                fen: reader.get_string()?,
+            },
+            3 => ServerMessage::MoveRejected {
+                rejection: reader.get_rejection()?,
+            },
+            4 => ServerMessage::Position {
+                fen: reader.get_string()?,
+            },
+            5 => ServerMessage::Chat {
+                name: reader.get_string()?,
+                text: reader.get_string()?,
+            },
+            6 => ServerMessage::Seated {
+                game_id: reader.get_u64()?,
+                seat: reader.get_seat()?,
+                time_control: reader.get_time_control()?,
+            },
+            7 => {
+                let count = reader.get_u32()?;
+                let mut games = Vec::new();
+                for _ in 0..count {
+                    games.push(reader.get_game_info()?);
+                }
+                ServerMessage::GameList { games }
+            }
+            8 => ServerMessage::Queued,
+            9 => ServerMessage::LobbyError {
+                error: reader.get_lobby_error()?,
+            },
+            10 => ServerMessage::Clock {
+                white: reader.get_duration()?,
+                black: reader.get_duration()?,
+                running: reader.get_bool()?,
+            },
+            11 => ServerMessage::TimeUp {
+                color: reader.get_color()?,
+            },
+            12 => ServerMessage::PlayerLeft {
+                seat: reader.get_seat()?,
+            },
+            kind => return Err(get_unknown_kind_error(kind)),
+        };
+        reader.finish()?;
+        return Ok(message);
+    }
+}
+
+fn get_unknown_kind_error(kind: u8) -> ProtocolError {
+    return get_unknown_value_error("message kind", kind);
+}
+
+fn get_unknown_value_error(name: &str, value: u8) -> ProtocolError {
+    return ProtocolError::InvalidMessage(format!("Unknown {} {}", name, value));
+}
+
+// Put the length before a message
+pub fn encode_frame(message: &[u8]) -> Result<Vec<u8>, ProtocolError> {
+    if message.len() > MAX_FRAME_SIZE {
+        return Err(ProtocolError::FrameTooLarge(message.len()));
+    }
+    let mut frame = (message.len() as u32).to_be_bytes().to_vec();
+    frame.extend_from_slice(message);
+    return Ok(frame);
+}
+
+// Write a message with its length before it
+pub fn write_frame(writer: &mut impl Write, message: &[u8]) -> Result<(), ProtocolError> {
+    writer.write_all(&encode_frame(message)?)?;
+    writer.flush()?;
+    return Ok(());
+}
+
+// Take the first message out of bytes read so far, for sockets that do not block.
+// Returns None until the whole frame has arrived
+pub fn take_frame(buffer: &mut Vec<u8>) -> Result<Option<Vec<u8>>, ProtocolError> {
+    if buffer.len() < 4 {
+        return Ok(None);
+    }
+    let length = u32::from_be_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]) as usize;
+    if length > MAX_FRAME_SIZE {
+        return Err(ProtocolError::FrameTooLarge(length));
+    }
+    if buffer.len() < 4 + length {
+        return Ok(None);
+    }
+    let message = buffer[4..4 + length].to_vec();
+    buffer.drain(..4 + length);
+    return Ok(Some(message));
+}
+
+// Read a message written by write_frame, waits until all of it has arrived
+pub fn read_frame(reader: &mut impl Read) -> Result<Vec<u8>, ProtocolError> {
+    let mut length = [0; 4];
+    reader.read_exact(&mut length)?;
+    let length = u32::from_be_bytes(length) as usize;
+    if length > MAX_FRAME_SIZE {
+        return Err(ProtocolError::FrameTooLarge(length));
+    }
+    let mut message = vec![0; length];
+    reader.read_exact(&mut message)?;
+    return Ok(message);
+}
+
+pub fn send_client_message(
+    writer: &mut impl Write,
+    message: &ClientMessage,
+) -> Result<(), ProtocolError> {
+    return write_frame(writer, &message.encode());
+}
+
+pub fn read_client_message(reader: &mut impl Read) -> Result<ClientMessage, ProtocolError> {
+    return ClientMessage::decode(&read_frame(reader)?);
+}
+
+pub fn send_server_message(
+    writer: &mut impl Write,
+    message: &ServerMessage,
+) -> Result<(), ProtocolError> {
+    return write_frame(writer, &message.encode());
+}
+
+pub fn read_server_message(reader: &mut impl Read) -> Result<ServerMessage, ProtocolError> {
+    return ServerMessage::decode(&read_frame(reader)?);
+}
+
+// Say hello to the server and wait for the answer. Returns the version of the server
+pub fn client_handshake<S: Read + Write>(stream: &mut S, name: &str) -> Result<u32, ProtocolError> {
+    let hello = ClientMessage::Hello {
+        version: PROTOCOL_VERSION,
+        name: name.to_string(),
+    };
+    send_client_message(stream, &hello)?;
+    match read_server_message(stream)? {
+        ServerMessage::Welcome { version } => {
+            if !is_compatible(version) {
+                return Err(ProtocolError::IncompatibleVersion { version });
+            }
+            return Ok(version);
+        }
+        ServerMessage::Rejected { reason } => return Err(ProtocolError::Rejected(reason)),
+        message => {
+            return Err(ProtocolError::InvalidMessage(format!(
+                "Expected Welcome, got {:?}",
+                message
+            )))
+        }
+    }
+}
+
+// Check the first message from a client, it must be Hello with a version the server
+// supports. Returns the name of the client
+pub fn check_hello(message: ClientMessage) -> Result<String, ProtocolError> {
+    match message {
+        ClientMessage::Hello { version, name } => {
+            if !is_compatible(version) {
+                return Err(ProtocolError::IncompatibleVersion { version });
+            }
+            return Ok(name);
+        }
+        message => {
+            return Err(ProtocolError::InvalidMessage(format!(
+                "Expected Hello, got {:?}",
+                message
+            )))
+        }
+    }
+}
+
+// Wait for the hello of a new client and answer it. Clients that do not start with Hello,
+// or have a version the server does not support, are told why before the error is returned.
+// Returns the name of the client
+pub fn server_handshake<S: Read + Write>(stream: &mut S) -> Result<String, ProtocolError> {
+    let result = read_client_message(stream).and_then(check_hello);
+
+    match result {
+        Ok(name) => {
+            let welcome = ServerMessage::Welcome {
+                version: PROTOCOL_VERSION,
+            };
+            send_server_message(stream, &welcome)?;
+            return Ok(name);
+        }
+        Err(ProtocolError::Io(error)) => return Err(ProtocolError::Io(error)),
+        Err(ProtocolError::Closed) => return Err(ProtocolError::Closed),
+        Err(error) => {
+            // the peer may already be gone, the handshake failed either way
+            let rejected = ServerMessage::Rejected {
+                reason: error.to_string(),
+            };
+            let _send_error = send_server_message(stream, &rejected);
+            return Err(error);
+        }
+    }
+}
+
+struct MessageWriter {
+    bytes: Vec<u8>,
+}
+
+impl MessageWriter {
+    fn new() -> Self {
+        return MessageWriter { bytes: Vec::new() };
+    }
+
+    fn put_u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    fn put_u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_be_bytes());
+    }
+
+    fn put_u64(&mut self, value: u64) {
+        self.bytes.extend_from_slice(&value.to_be_bytes());
+    }
+
+    fn put_bool(&mut self, value: bool) {
+        self.put_u8(value as u8);
+    }
+
+    fn put_bytes(&mut self, bytes: &[u8]) {
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    fn put_string(&mut self, text: &str) {
+        self.put_u32(text.len() as u32);
+        self.put_bytes(text.as_bytes());
+    }
+
+    fn put_move(&mut self, chess_move: &ChessMove) {
+        self.put_string(&chess_move.to_long_algebraic_notation());
+    }
+
+    fn put_color(&mut self, color: ChessPieceColor) {
+        self.put_u8(color as u8);
+    }
+
+    fn put_option_color(&mut self, color: Option<ChessPieceColor>) {
+        match color {
+            Some(color) => self.put_color(color),
+            None => self.put_u8(2),
+        }
+    }
+
+    fn put_seat(&mut self, seat: Seat) {
+        self.put_option_color(seat.get_color());
+    }
+
+    fn put_option_string(&mut self, text: Option<&String>) {
+        self.put_bool(text.is_some());
+        if let Some(text) = text {
+            self.put_string(text);
+        }
+    }
+
+    // Written as in the PGN TimeControl tag, where "-" is no time control
+    fn put_time_control(&mut self, time_control: Option<&TimeControl>) {
+        match time_control {
+            Some(time_control) => self.put_string(&time_control.to_string()),
+            None => self.put_string("-"),
+        }
+    }
+
+    // In milliseconds
+    fn put_duration(&mut self, duration: Duration) {
+        self.put_u64(duration.as_millis() as u64);
+    }
+
+    fn put_game_info(&mut self, game: &GameInfo) {
+        self.put_u64(game.id);
+        self.put_time_control(game.time_control.as_ref());
+        self.put_option_string(game.white.as_ref());
+        self.put_option_string(game.black.as_ref());
+        self.put_u32(game.spectators);
+        self.put_bool(game.is_over);
+    }
+
+    fn put_lobby_error(&mut self, error: &LobbyError) {
+        match error {
+            LobbyError::NoGame { game_id } => {
+                self.put_u8(0);
+                self.put_u64(*game_id);
+            }
+            LobbyError::NotInGame => self.put_u8(1),
+        }
+    }
+
+    fn put_rejection(&mut self, rejection: &MoveRejection) {
+        match rejection {
+            MoveRejection::Spectator => self.put_u8(0),
+            MoveRejection::WrongSeat { turn } => {
+                self.put_u8(1);
+                self.put_color(*turn);
+            }
+            MoveRejection::Illegal(error) => {
+                self.put_u8(2);
+                let index = MOVE_ERRORS.iter().position(|known| known == error);
+                self.put_u8(index.unwrap() as u8);
+            }
+            MoveRejection::NotStarted => self.put_u8(3),
+            MoveRejection::Clock(error) => {
+                self.put_u8(4);
+                match error {
+                    ClockError::NotRunning => self.put_u8(0),
+                    ClockError::WrongTurn => self.put_u8(1),
+                    ClockError::Flagged { color } => {
+                        self.put_u8(2);
+                        self.put_color(*color);
+                    }
+                }
+            }
+        }
+    }
+}
+
+struct MessageReader<'a> {
+    bytes: &'a [u8],
+    index: usize,
+}
+
+impl<'a> MessageReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        return MessageReader { bytes, index: 0 };
+    }
+
+    fn get_bytes(&mut self, count: usize) -> Result<&'a [u8], ProtocolError> {
+        if self.bytes.len() - self.index < count {
+            return Err(ProtocolError::InvalidMessage(
+                "The message ended too early".to_string(),
+            ));
+        }
+        let bytes = &self.bytes[self.index..self.index + count];
+        self.index += count;
+        return Ok(bytes);
+    }
+
+    fn get_u8(&mut self) -> Result<u8, ProtocolError> {
+        return Ok(self.get_bytes(1)?[0]);
+    }
+
+    fn get_u32(&mut self) -> Result<u32, ProtocolError> {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(self.get_bytes(4)?);
+        return Ok(u32::from_be_bytes(bytes));
+    }
+
+    fn get_u64(&mut self) -> Result<u64, ProtocolError> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.get_bytes(8)?);
+        return Ok(u64::from_be_bytes(bytes));
+    }
+
+    fn get_bool(&mut self) -> Result<bool, ProtocolError> {
+        match self.get_u8()? {
+            0 => return Ok(false),
+            1 => return Ok(true),
+            value => return Err(get_unknown_value_error("bool", value)),
+        }
+    }
+
+    fn get_string(&mut self) -> Result<String, ProtocolError> {
+        let length = self.get_u32()? as usize;
+        let bytes = self.get_bytes(length)?;
+        return String::from_utf8(bytes.to_vec())
+            .map_err(|_| ProtocolError::InvalidMessage("Invalid UTF-8 in a string".to_string()));
+    }
+
+    fn get_move(&mut self) -> Result<ChessMove, ProtocolError> {
+        let text = self.get_string()?;
+        return ChessMove::from_long_algebraic_notation(&text)
+            .map_err(|error| ProtocolError::InvalidMessage(format!("{}: {}", error, text)));
+    }
+
+    fn get_color(&mut self) -> Result<ChessPieceColor, ProtocolError> {
+        match self.get_u8()? {
+            0 => return Ok(ChessPieceColor::White),
+            1 => return Ok(ChessPieceColor::Black),
+            value => return Err(get_unknown_value_error("color", value)),
+        }
+    }
+
+    fn get_option_color(&mut self) -> Result<Option<ChessPieceColor>, ProtocolError> {
+        match self.get_u8()? {
+            0 => return Ok(Some(ChessPieceColor::White)),
+            1 => return Ok(Some(ChessPieceColor::Black)),
+            2 => return Ok(None),
+            value => return Err(get_unknown_value_error("color", value)),
+        }
+    }
+
+    fn get_option_string(&mut self) -> Result<Option<String>, ProtocolError> {
+        if !self.get_bool()? {
+            return Ok(None);
+        }
+        return Ok(Some(self.get_string()?));
+    }
+
+    fn get_time_control(&mut self) -> Result<Option<TimeControl>, ProtocolError> {
+        let text = self.get_string()?;
+        if text == "-" {
+            return Ok(None);
+        }
+        return TimeControl::parse(&text)
+            .map(Some)
+            .map_err(ProtocolError::InvalidMessage);
+    }
+
+    fn get_duration(&mut self) -> Result<Duration, ProtocolError> {
+        return Ok(Duration::from_millis(self.get_u64()?));
+    }
+
+    fn get_game_info(&mut self) -> Result<GameInfo, ProtocolError> {
+        return Ok(GameInfo {
+            id: self.get_u64()?,
+            time_control: self.get_time_control()?,
+            white: self.get_option_string()?,
+            black: self.get_option_string()?,
+            spectators: self.get_u32()?,
+            is_over: self.get_bool()?,
+        });
+    }
+
+    fn get_lobby_error(&mut self) -> Result<LobbyError, ProtocolError> {
+        match self.get_u8()? {
+            0 => {
+                return Ok(LobbyError::NoGame {
+                    game_id: self.get_u64()?,
+                })
+            }
+            1 => return Ok(LobbyError::NotInGame),
+            value => return Err(get_unknown_value_error("lobby error", value)),
+        }
+    }
+
+    fn get_seat(&mut self) -> Result<Seat, ProtocolError> {
+        match self.get_u8()? {
+            0 => return Ok(Seat::White),
+            1 => return Ok(Seat::Black),
+            2 => return Ok(Seat::Spectator),
+            value => return Err(get_unknown_value_error("seat", value)),
+        }
+    }
+
+    fn get_rejection(&mut self) -> Result<MoveRejection, ProtocolError> {
+        match self.get_u8()? {
+            0 => return Ok(MoveRejection::Spectator),
+            1 => {
+                return Ok(MoveRejection::WrongSeat {
+                    turn: self.get_color()?,
+                })
+            }
+            2 => {
+                let index = self.get_u8()?;
+                return match MOVE_ERRORS.get(index as usize) {
+                    Some(error) => Ok(MoveRejection::Illegal(*error)),
+                    None => Err(get_unknown_value_error("move error", index)),
+                };
+            }
+            3 => return Ok(MoveRejection::NotStarted),
+            4 => {
+                let error = match self.get_u8()? {
+                    0 => ClockError::NotRunning,
+                    1 => ClockError::WrongTurn,
+                    2 => ClockError::Flagged {
+                        color: self.get_color()?,
+                    },
+                    value => return Err(get_unknown_value_error("clock error", value)),
+                };
+                return Ok(MoveRejection::Clock(error));
+            }
+            value => return Err(get_unknown_value_error("move rejection", value)),
+        }
+    }
+
+    // Make sure the whole message was read
+    fn finish(&self) -> Result<(), ProtocolError> {
+        if self.index != self.bytes.len() {
+            return Err(ProtocolError::InvalidMessage(
+                "Unexpected bytes at the end of the message".to_string(),
+            ));
+        }
+        return Ok(());
+    }
+}
@@ -0,0 +1,539 @@
+// Finds the best move of a position, with iterative deepening negamax and alpha-beta pruning.
+// Scores are in centipawns for the player to move
+use crate::bitboard::{self, Bitboards};
+use crate::chess_game::{ChessMove, ChessPieceId, Game};
+use crate::evaluation::{evaluate_with_config, EvaluationConfig};
+use std::cmp::Reverse;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+pub const MATE_SCORE: i32 = 100_000;
+pub const MAX_DEPTH: u32 = 64;
+const INFINITY: i32 = MATE_SCORE + 1;
+const MAX_PLY: usize = 128;
+
+// Piece values used for move ordering, indexed by ChessPieceId
+const PIECE_VALUES: [i32; 6] = [100, 320, 500, 0, 900, 330];
+
+// Returns true if a score means one of the players can force mate
+pub fn is_mate_score(score: i32) -> bool {
+    return score.abs() > MATE_SCORE - MAX_PLY as i32;
+}
+
+// Get the number of moves to mate from a score, negative if the player to move gets mated
+pub fn get_mate_in(score: i32) -> Option<i32> {
+    if !is_mate_score(score) {
+        return None;
+    }
+    let moves = (MATE_SCORE - score.abs() + 1) / 2;
+    if score > 0 {
+        return Some(moves);
+    } else {
+        return Some(-moves);
+    }
+}
+
+// When to stop searching, the search stops at the first limit reached.
+// Without limits it searches to MAX_DEPTH
+#[derive(Clone, Default)]
+pub struct SearchLimits {
+    pub depth: Option<u32>,
+    pub nodes: Option<u64>,
+    pub time: Option<Duration>,
+    pub stop: Option<Arc<AtomicBool>>, // Set from another thread to stop the search
+}
+
+// The result of the deepest completed iteration
+#[derive(Clone, Debug)]
+pub struct SearchResult {
+    pub best_move: Option<ChessMove>, // None if there are no legal moves
+    pub score: i32,
+    pub pv: Vec<ChessMove>, // The principal variation, the moves both players are expected to play
+    pub depth: u32,
+    pub nodes: u64,
+    pub time: Duration,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Bound {
+    Exact,
+    Lower, // The score is at least this, the search failed high
+    Upper, // The score is at most this, the search failed low
+}
+
+#[derive(Clone, Copy)]
+struct TableEntry {
+    hash: u64,
+    best_move: Option<ChessMove>,
+    score: i32,
+    depth: u32,
+    bound: Bound,
+}
+
+// Remembers searched positions by their hash, so positions reached through different
+// move orders, and positions from earlier iterations, do not have to be searched again
+pub struct TranspositionTable {
+    entries: Vec<Option<TableEntry>>,
+}
+
+impl TranspositionTable {
+    pub fn new(size_mb: usize) -> Self {
+        let entry_size = std::mem::size_of::<Option<TableEntry>>();
+        let count = (size_mb.max(1) * 1024 * 1024 / entry_size).max(1);
+        return Self {
+            entries: vec![None; count],
+        };
+    }
+
+    pub fn clear(&mut self) {
+        for entry in self.entries.iter_mut() {
+            *entry = None;
+        }
+    }
+
+    fn get(&self, hash: u64) -> Option<TableEntry> {
+        let entry = self.entries[(hash % self.entries.len() as u64) as usize];
+        if entry.is_some() && entry.unwrap().hash == hash {
+            return entry;
+        }
+        return None;
+    }
+
+    // Always replace, newer entries are more likely to be needed again
+    fn store(&mut self, entry: TableEntry) {
+        let index = (entry.hash % self.entries.len() as u64) as usize;
+        self.entries[index] = Some(entry);
+    }
+}
+
+// Searches positions, keeping the transposition table and move ordering
+// statistics between searches of the same game
+pub struct Searcher {
+    table: TranspositionTable,
+    killers: [[Option<ChessMove>; 2]; MAX_PLY],
+    history: [[[i32; 64]; 64]; 2], // Indexed by color, from square and to square
+    hashes: Vec<u64>,              // Positions before the current one, to find repetitions
+    config: EvaluationConfig,      // The weights positions are evaluated with
+    limits: SearchLimits,
+    start: Instant,
+    nodes: u64,
+    aborted: bool,
+}
+
+impl Searcher {
+    pub fn new(table_size_mb: usize) -> Self {
+        return Self {
+            table: TranspositionTable::new(table_size_mb),
+            killers: [[None; 2]; MAX_PLY],
+            history: [[[0; 64]; 64]; 2],
+            hashes: Vec::new(),
+            config: EvaluationConfig::default(),
+            limits: SearchLimits::default(),
+            start: Instant::now(),
+            nodes: 0,
+            aborted: false,
+        };
+    }
+
+    // Forget everything from earlier searches, ex. when a new game starts
+    pub fn clear(&mut self) {
+        self.table.clear();
+        self.killers = [[None; 2]; MAX_PLY];
+        self.history = [[[0; 64]; 64]; 2];
+    }
+
+    // Change the size of the transposition table, which also clears it
+    pub fn resize_table(&mut self, size_mb: usize) {
+        self.table = TranspositionTable::new(size_mb);
+    }
+
+    // Evaluate positions with other weights, the table is cleared since its scores are stale
+    pub fn set_evaluation_config(&mut self, config: EvaluationConfig) {
+        self.config = config;
+        self.table.clear();
+    }
+
+    pub fn search(&mut self, game: &Game, limits: &SearchLimits) -> SearchResult {
+        return self.search_with_info(game, limits, |_| {});
+    }
+
+    // Search and call on_iteration with the result after every completed depth
+    pub fn search_with_info<F: FnMut(&SearchResult)>(
+        &mut self,
+        game: &Game,
+        limits: &SearchLimits,
+        mut on_iteration: F,
+    ) -> SearchResult {
+        self.limits = limits.clone();
+        self.start = Instant::now();
+        self.nodes = 0;
+        self.aborted = false;
+        self.killers = [[None; 2]; MAX_PLY];
+        for color in self.history.iter_mut() {
+            for from in color.iter_mut() {
+                for score in from.iter_mut() {
+                    *score /= 8;
+                }
+            }
+        }
+        self.set_up_hashes(game);
+
+        let mut game = game.clone_position();
+        let moves = game.generate_legal_moves();
+        let mut result = SearchResult {
+            best_move: moves.first().copied(),
+            score: 0,
+            pv: Vec::new(),
+            depth: 0,
+            nodes: 0,
+            time: Duration::from_secs(0),
+        };
+        if moves.is_empty() {
+            if game.is_check().is_some() {
+                result.score = -MATE_SCORE;
+            }
+            return result;
+        }
+
+        let max_depth = limits.depth.unwrap_or(MAX_DEPTH).clamp(1, MAX_DEPTH);
+        for depth in 1..=max_depth {
+            let mut pv = Vec::new();
+            let score = self.negamax(&mut game, depth, 0, -INFINITY, INFINITY, &mut pv);
+            // An unfinished iteration is not used
+            if self.aborted {
+                break;
+            }
+            if !pv.is_empty() {
+                result.best_move = Some(pv[0]);
+            }
+            result.score = score;
+            result.pv = pv;
+            result.depth = depth;
+            result.nodes = self.nodes;
+            result.time = self.start.elapsed();
+            on_iteration(&result);
+
+            // Stop early when a mate is found or the next depth is unlikely to finish
+            if is_mate_score(score) && depth as i32 > MATE_SCORE - score.abs() {
+                break;
+            }
+            if limits.time.is_some() && self.start.elapsed() > limits.time.unwrap() / 2 {
+                break;
+            }
+        }
+        result.nodes = self.nodes;
+        result.time = self.start.elapsed();
+        return result;
+    }
+
+    // Remember the positions since the last pawn move or capture, since they can be repeated
+    fn set_up_hashes(&mut self, game: &Game) {
+        let history = game.get_history();
+        let start = history
+            .len()
+            .saturating_sub(game.get_halfmove_clock() as usize);
+        self.hashes = history[start..]
+            .iter()
+            .map(|record| record.get_hash())
+            .collect();
+    }
+
+    fn negamax(
+        &mut self,
+        game: &mut Game,
+        mut depth: u32,
+        ply: usize,
+        mut alpha: i32,
+        beta: i32,
+        pv: &mut Vec<ChessMove>,
+    ) -> i32 {
+        self.count_node();
+        if self.aborted {
+            return 0;
+        }
+        let hash = game.hash();
+        if ply > 0 && self.is_draw(game, hash) {
+            return 0;
+        }
+        let in_check = game.is_check().is_some();
+        if in_check && ply < MAX_PLY / 2 {
+            depth += 1;
+        }
+        if depth == 0 || ply >= MAX_PLY - 1 {
+            return self.quiescence(game, ply, alpha, beta);
+        }
+
+        // Use the score of an earlier search of the position if it was deep enough
+        let entry = self.table.get(hash);
+        let mut table_move = None;
+        if let Some(entry) = entry {
+            table_move = entry.best_move;
+            let score = Self::score_from_table(entry.score, ply);
+            if ply > 0
+                && entry.depth >= depth
+                && (entry.bound == Bound::Exact
+                    || (entry.bound == Bound::Lower && score >= beta)
+                    || (entry.bound == Bound::Upper && score <= alpha))
+            {
+                if let Some(best_move) = entry.best_move {
+                    pv.push(best_move);
+                }
+                return score;
+            }
+        }
+
+        let mut moves = game.generate_legal_moves();
+        if moves.is_empty() {
+            if in_check {
+                return -MATE_SCORE + ply as i32;
+            }
+            return 0;
+        }
+        self.order_moves(game, &mut moves, table_move, ply);
+
+        let original_alpha = alpha;
+        let mut best_score = -INFINITY;
+        let mut best_move = moves[0];
+        self.hashes.push(hash);
+        for chess_move in moves {
+            let mut child_pv = Vec::new();
+            let is_quiet =
+                !Self::is_capture(game, chess_move) && chess_move.promote_piece.is_none();
+            let record = game.make_move(chess_move);
+            let score = -self.negamax(game, depth - 1, ply + 1, -beta, -alpha, &mut child_pv);
+            game.unmake_move(record);
+            if self.aborted {
+                self.hashes.pop();
+                return 0;
+            }
+            if score > best_score {
+                best_score = score;
+                best_move = chess_move;
+            }
+            if score > alpha {
+                alpha = score;
+                pv.clear();
+                pv.push(chess_move);
+                pv.append(&mut child_pv);
+            }
+            if alpha >= beta {
+                if is_quiet {
+                    self.add_killer(chess_move, ply);
+                    let from = bitboard::get_square(chess_move.board_move.from_pos);
+                    let to = bitboard::get_square(chess_move.board_move.to_pos);
+                    self.history[game.turn as usize][from][to] += (depth * depth) as i32;
+                }
+                break;
+            }
+        }
+        self.hashes.pop();
+
+        let bound = if best_score >= beta {
+            Bound::Lower
+        } else if best_score > original_alpha {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+        self.table.store(TableEntry {
+            hash,
+            best_move: Some(best_move),
+            score: Self::score_to_table(best_score, ply),
+            depth,
+            bound,
+        });
+        return best_score;
+    }
+
+    // Only search captures and promotions, so the evaluation is not done in the middle
+    // of an exchange of pieces
+    fn quiescence(&mut self, game: &mut Game, ply: usize, mut alpha: i32, beta: i32) -> i32 {
+        self.count_node();
+        if self.aborted {
+            return 0;
+        }
+        let mut moves = game.generate_legal_moves();
+        let in_check = game.is_check().is_some();
+        if moves.is_empty() {
+            if in_check {
+                return -MATE_SCORE + ply as i32;
+            }
+            return 0;
+        }
+        if ply >= MAX_PLY - 1 {
+            return evaluate_with_config(game, &self.config);
+        }
+
+        // The player to move does not have to take, so the evaluation is a lower bound.
+        // A player in check cannot stand still, so its evaluation is not a bound
+        if !in_check {
+            let stand_pat = evaluate_with_config(game, &self.config);
+            if stand_pat >= beta {
+                return stand_pat;
+            }
+            if stand_pat > alpha {
+                alpha = stand_pat;
+            }
+            moves.retain(|chess_move| {
+                Self::is_capture(game, *chess_move) || chess_move.promote_piece.is_some()
+            });
+        }
+        self.order_moves(game, &mut moves, None, ply);
+        for chess_move in moves {
+            let record = game.make_move(chess_move);
+            let score = -self.quiescence(game, ply + 1, -beta, -alpha);
+            game.unmake_move(record);
+            if self.aborted {
+                return 0;
+            }
+            if score >= beta {
+                return score;
+            }
+            if score > alpha {
+                alpha = score;
+            }
+        }
+        return alpha;
+    }
+
+    // Search the moves most likely to be best first, so more of the others are pruned:
+    // the move from the transposition table, captures of valuable pieces by cheap pieces,
+    // promotions, killer moves and then quiet moves that often caused cutoffs
+    fn order_moves(
+        &self,
+        game: &Game,
+        moves: &mut Vec<ChessMove>,
+        table_move: Option<ChessMove>,
+        ply: usize,
+    ) {
+        let bitboards = game.get_bitboards();
+        let mut scored_moves: Vec<(i32, ChessMove)> = moves
+            .iter()
+            .map(|chess_move| {
+                let score =
+                    self.get_move_order_score(game, &bitboards, *chess_move, table_move, ply);
+                (score, *chess_move)
+            })
+            .collect();
+        scored_moves.sort_by_key(|scored_move| Reverse(scored_move.0));
+        moves.clear();
+        moves.extend(scored_moves.iter().map(|(_, chess_move)| *chess_move));
+    }
+
+    fn get_move_order_score(
+        &self,
+        game: &Game,
+        bitboards: &Bitboards,
+        chess_move: ChessMove,
+        table_move: Option<ChessMove>,
+        ply: usize,
+    ) -> i32 {
+        if Some(chess_move) == table_move {
+            return 10_000_000;
+        }
+        let from = bitboard::get_square(chess_move.board_move.from_pos);
+        let to = bitboard::get_square(chess_move.board_move.to_pos);
+        let attacker = bitboards.get_piece_id(from).unwrap();
+        if Self::is_capture(game, chess_move) {
+            // Most valuable victim, least valuable attacker. En passant takes a pawn
+            let victim = bitboards.get_piece_id(to).unwrap_or(ChessPieceId::Pawn);
+            return 1_000_000 + 10 * PIECE_VALUES[victim as usize]
+                - PIECE_VALUES[attacker as usize] / 10;
+        }
+        if let Some(promote_piece) = chess_move.promote_piece {
+            return 900_000 + PIECE_VALUES[promote_piece as usize];
+        }
+        if self.killers[ply][0] == Some(chess_move) {
+            return 800_000;
+        }
+        if self.killers[ply][1] == Some(chess_move) {
+            return 790_000;
+        }
+        return self.history[game.turn as usize][from][to].min(700_000);
+    }
+
+    fn add_killer(&mut self, chess_move: ChessMove, ply: usize) {
+        if self.killers[ply][0] != Some(chess_move) {
+            self.killers[ply][1] = self.killers[ply][0];
+            self.killers[ply][0] = Some(chess_move);
+        }
+    }
+
+    fn is_capture(game: &Game, chess_move: ChessMove) -> bool {
+        let bitboards = game.get_bitboards();
+        let from = bitboard::get_square(chess_move.board_move.from_pos);
+        let to = bitboard::get_square(chess_move.board_move.to_pos);
+        // Castling in Chess960 is written as the king taking its own rook
+        if bitboards.get_occupied() & !bitboards.get_color(game.turn) & (1 << to) != 0 {
+            return true;
+        }
+        // A pawn moving diagonally to an empty square takes en passant
+        return bitboards.get_piece_id(from) == Some(ChessPieceId::Pawn)
+            && chess_move.board_move.from_pos.x != chess_move.board_move.to_pos.x;
+    }
+
+    // Fifty moves without pawn moves or captures, repetition or too little material to mate.
+    // A position counts as a draw the first time it repeats, since the players could repeat
+    // it again
+    fn is_draw(&self, game: &Game, hash: u64) -> bool {
+        let halfmove_clock = game.get_halfmove_clock() as usize;
+        if halfmove_clock >= 100 || game.is_insufficient_material() {
+            return true;
+        }
+        // Only positions with the same player to move, since the last pawn move or capture
+        return self
+            .hashes
+            .iter()
+            .rev()
+            .take(halfmove_clock)
+            .skip(1)
+            .step_by(2)
+            .any(|past_hash| *past_hash == hash);
+    }
+
+    fn count_node(&mut self) {
+        self.nodes += 1;
+        // Checking the time is slow, so it is only done now and then
+        if self.nodes.is_multiple_of(1024) && self.should_stop() {
+            self.aborted = true;
+        }
+    }
+
+    fn should_stop(&self) -> bool {
+        let limits = &self.limits;
+        if limits.stop.is_some() && limits.stop.as_ref().unwrap().load(Ordering::Relaxed) {
+            return true;
+        }
+        if limits.nodes.is_some() && self.nodes >= limits.nodes.unwrap() {
+            return true;
+        }
+        return limits.time.is_some() && self.start.elapsed() >= limits.time.unwrap();
+    }
+
+    // Mate scores are stored as distance from the position, not from the root of the search
+    fn score_to_table(score: i32, ply: usize) -> i32 {
+        if score > MATE_SCORE - MAX_PLY as i32 {
+            return score + ply as i32;
+        }
+        if score < -MATE_SCORE + MAX_PLY as i32 {
+            return score - ply as i32;
+        }
+        return score;
+    }
+
+    fn score_from_table(score: i32, ply: usize) -> i32 {
+        if score > MATE_SCORE - MAX_PLY as i32 {
+            return score - ply as i32;
+        }
+        if score < -MATE_SCORE + MAX_PLY as i32 {
+            return score + ply as i32;
+        }
+        return score;
+    }
+}
+
+// Search a position without keeping anything for later searches
+pub fn search(game: &Game, limits: &SearchLimits) -> SearchResult {
+    return Searcher::new(16).search(game, limits);
+}
@@ -0,0 +1,45 @@
+// Zobrist keys, a random number for every piece on every square and for the other parts
+// of a position. The hash of a position is all its keys xored together, so a move only
+// has to xor the keys of what it changed
+use crate::chess_game::{ChessPieceColor, ChessPieceId};
+
+const PIECE_KEY_COUNT: usize = 2 * 6 * 64;
+
+const KEYS: [u64; PIECE_KEY_COUNT + 1 + 16 + 8] = make_keys();
+
+// Fixed random numbers, so the hash of a position is the same every time the program runs
+const fn make_keys() -> [u64; PIECE_KEY_COUNT + 1 + 16 + 8] {
+    let mut keys = [0; PIECE_KEY_COUNT + 1 + 16 + 8];
+    let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+    let mut i = 0;
+    while i < keys.len() {
+        // splitmix64
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        keys[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    return keys;
+}
+
+pub fn piece_key(id: ChessPieceId, color: ChessPieceColor, square: usize) -> u64 {
+    return KEYS[(color as usize * 6 + id as usize) * 64 + square];
+}
+
+// Xored in when black is to move
+pub fn side_key() -> u64 {
+    return KEYS[PIECE_KEY_COUNT];
+}
+
+// The castling rights are four bits, white king side, white queen side,
+// black king side and black queen side
+pub fn castling_key(castling_rights: u8) -> u64 {
+    return KEYS[PIECE_KEY_COUNT + 1 + castling_rights as usize];
+}
+
+// The file of the square a pawn can be taken on en passant
+pub fn passant_key(file: usize) -> u64 {
+    return KEYS[PIECE_KEY_COUNT + 1 + 16 + file];
+}
@@ -0,0 +1,271 @@
+#[cfg(test)]
+mod clock_test {
+    pub extern crate chess_engine;
+    use chess_engine::chess_game::*;
+    use chess_engine::clock::*;
+    use std::time::{Duration, Instant};
+
+    fn seconds(seconds: u64) -> Duration {
+        return Duration::from_secs(seconds);
+    }
+
+    #[test]
+    fn time_control_test() {
+        for text in &[
+            "300",
+            "180+2",
+            "300d5",
+            "300b5",
+            "40/5400+30:1800+30",
+            "40/7200",
+        ] {
+            let time_control = TimeControl::parse(text).unwrap();
+            assert_eq!(time_control.to_string(), text.to_string());
+        }
+        assert_eq!(
+            TimeControl::parse("180+2").unwrap(),
+            TimeControl::fischer(seconds(180), seconds(2))
+        );
+        let time_control = TimeControl::parse("40/5400+30:1800+30").unwrap();
+        assert_eq!(time_control.stages.len(), 2);
+        assert_eq!(time_control.stages[0].moves, Some(40));
+        assert_eq!(time_control.stages[1].moves, None);
+        assert_eq!(time_control.get_start_time(), seconds(5400));
+
+        for text in &["", "abc", "0", "0/300", "300+", "1800:40/5400", "40/"] {
+            assert_eq!(TimeControl::parse(text).is_err(), true, "{}", text);
+        }
+
+        assert_eq!(format_time(seconds(5400)), "1:30:00");
+        assert_eq!(format_time(seconds(299)), "4:59");
+        assert_eq!(format_time(Duration::from_millis(9560)), "0:09.5");
+        assert_eq!(format_time(seconds(0)), "0:00.0");
+    }
+
+    #[test]
+    fn clock_test() {
+        let start = Instant::now();
+        let at = |time: u64| start + seconds(time);
+
+        // Sudden death, the clock only runs for the player to move
+        let mut clock = Clock::new(TimeControl::sudden_death(seconds(60)));
+        assert_eq!(
+            clock.press(ChessPieceColor::White, at(0)),
+            Err(ClockError::NotRunning)
+        );
+        assert_eq!(clock.start(at(0)).is_ok(), true);
+        assert_eq!(
+            clock.get_remaining(ChessPieceColor::White, at(10)),
+            seconds(50)
+        );
+        assert_eq!(
+            clock.get_remaining(ChessPieceColor::Black, at(10)),
+            seconds(60)
+        );
+        assert_eq!(
+            clock.press(ChessPieceColor::Black, at(10)),
+            Err(ClockError::WrongTurn)
+        );
+        assert_eq!(clock.press(ChessPieceColor::White, at(10)).is_ok(), true);
+        assert_eq!(clock.get_turn(), ChessPieceColor::Black);
+        assert_eq!(
+            clock.get_remaining(ChessPieceColor::White, at(30)),
+            seconds(50)
+        );
+        assert_eq!(
+            clock.get_remaining(ChessPieceColor::Black, at(30)),
+            seconds(40)
+        );
+
+        // A stopped clock keeps the time used so far
+        clock.stop(at(30));
+        assert_eq!(
+            clock.get_remaining(ChessPieceColor::Black, at(100)),
+            seconds(40)
+        );
+        assert_eq!(clock.start(at(100)).is_ok(), true);
+        assert_eq!(
+            clock.get_remaining(ChessPieceColor::Black, at(110)),
+            seconds(30)
+        );
+
+        // Running out of time
+        assert_eq!(clock.check_flag(at(139)), None);
+        assert_eq!(clock.check_flag(at(140)), Some(ChessPieceColor::Black));
+        assert_eq!(clock.is_running(), false);
+        assert_eq!(
+            clock.press(ChessPieceColor::Black, at(141)),
+            Err(ClockError::Flagged {
+                color: ChessPieceColor::Black
+            })
+        );
+
+        // Fischer increment is added after every move
+        let mut clock = Clock::new(TimeControl::fischer(seconds(60), seconds(2)));
+        assert_eq!(clock.start(at(0)).is_ok(), true);
+        assert_eq!(clock.press(ChessPieceColor::White, at(10)).is_ok(), true);
+        assert_eq!(
+            clock.get_remaining(ChessPieceColor::White, at(10)),
+            seconds(52)
+        );
+
+        // Bronstein gives back the time used, at most the delay
+        let mut clock = Clock::new(TimeControl::bronstein(seconds(60), seconds(5)));
+        assert_eq!(clock.start(at(0)).is_ok(), true);
+        assert_eq!(
+            clock.get_remaining(ChessPieceColor::White, at(3)),
+            seconds(57)
+        );
+        assert_eq!(clock.press(ChessPieceColor::White, at(3)).is_ok(), true);
+        assert_eq!(
+            clock.get_remaining(ChessPieceColor::White, at(3)),
+            seconds(60)
+        );
+        assert_eq!(clock.press(ChessPieceColor::Black, at(13)).is_ok(), true);
+        assert_eq!(
+            clock.get_remaining(ChessPieceColor::Black, at(13)),
+            seconds(55)
+        );
+
+        // A simple delay waits before the time counts down
+        let mut clock = Clock::new(TimeControl::simple_delay(seconds(60), seconds(5)));
+        assert_eq!(clock.start(at(0)).is_ok(), true);
+        assert_eq!(
+            clock.get_remaining(ChessPieceColor::White, at(3)),
+            seconds(60)
+        );
+        assert_eq!(
+            clock.get_remaining(ChessPieceColor::White, at(8)),
+            seconds(57)
+        );
+        assert_eq!(clock.press(ChessPieceColor::White, at(8)).is_ok(), true);
+        assert_eq!(
+            clock.get_remaining(ChessPieceColor::White, at(8)),
+            seconds(57)
+        );
+
+        // With little time left the two delays are not the same
+        let mut bronstein = Clock::new(TimeControl::bronstein(seconds(2), seconds(5)));
+        let mut delay = Clock::new(TimeControl::simple_delay(seconds(2), seconds(5)));
+        assert_eq!(bronstein.start(at(0)).is_ok(), true);
+        assert_eq!(delay.start(at(0)).is_ok(), true);
+        assert_eq!(bronstein.check_flag(at(3)), Some(ChessPieceColor::White));
+        assert_eq!(delay.check_flag(at(3)), None);
+        assert_eq!(delay.check_flag(at(7)), Some(ChessPieceColor::White));
+    }
+
+    #[test]
+    fn clock_stages_test() {
+        let start = Instant::now();
+        let at = |time: u64| start + seconds(time);
+
+        // 40 moves in 90 minutes, then 30 minutes for the rest, with 30 seconds per move
+        let time_control = TimeControl::parse("40/5400+30:1800+30").unwrap();
+        let mut clock = Clock::new(time_control);
+        assert_eq!(clock.start(at(0)).is_ok(), true);
+        let mut time = 0;
+        for _ in 0..39 {
+            for color in &[ChessPieceColor::White, ChessPieceColor::Black] {
+                time += 60;
+                assert_eq!(clock.press(*color, at(time)).is_ok(), true);
+            }
+        }
+        assert_eq!(clock.get_moves_to_go(ChessPieceColor::White), Some(1));
+        assert_eq!(
+            clock.get_remaining(ChessPieceColor::White, at(time)),
+            seconds(5400 - 39 * 30)
+        );
+        time += 60;
+        assert_eq!(clock.press(ChessPieceColor::White, at(time)).is_ok(), true);
+        assert_eq!(clock.get_moves_to_go(ChessPieceColor::White), None);
+        assert_eq!(clock.get_moves_to_go(ChessPieceColor::Black), Some(1));
+        assert_eq!(
+            clock.get_remaining(ChessPieceColor::White, at(time)),
+            seconds(5400 - 40 * 30 + 1800)
+        );
+
+        // A last stage with a move count is repeated
+        let mut clock = Clock::new(TimeControl::parse("2/60").unwrap());
+        assert_eq!(clock.start(at(0)).is_ok(), true);
+        for time in 1..=4 {
+            let color = clock.get_turn();
+            assert_eq!(clock.press(color, at(time * 10)).is_ok(), true);
+        }
+        assert_eq!(
+            clock.get_remaining(ChessPieceColor::White, at(40)),
+            seconds(100)
+        );
+        assert_eq!(clock.get_moves_to_go(ChessPieceColor::White), Some(2));
+
+        // Taking back a move gives the turn back without a bonus
+        let mut clock = Clock::new(TimeControl::fischer(seconds(60), seconds(2)));
+        assert_eq!(clock.start(at(0)).is_ok(), true);
+        assert_eq!(clock.press(ChessPieceColor::White, at(10)).is_ok(), true);
+        clock.set_turn(ChessPieceColor::White, at(15));
+        assert_eq!(
+            clock.get_remaining(ChessPieceColor::Black, at(20)),
+            seconds(55)
+        );
+        assert_eq!(
+            clock.get_remaining(ChessPieceColor::White, at(20)),
+            seconds(47)
+        );
+        clock.set_remaining(ChessPieceColor::White, seconds(30), at(20));
+        assert_eq!(
+            clock.get_remaining(ChessPieceColor::White, at(25)),
+            seconds(25)
+        );
+    }
+
+    #[test]
+    fn clock_timeout_test() {
+        let start = Instant::now();
+        let mut game = Game::new();
+        game.set_up_board();
+        let mut clock = Clock::new(TimeControl::sudden_death(seconds(60)));
+        assert_eq!(clock.start(start).is_ok(), true);
+        assert_eq!(clock.check_timeout(&mut game, start + seconds(59)), false);
+        assert_eq!(game.game_status(), GameStatus::Ongoing);
+        assert_eq!(clock.check_timeout(&mut game, start + seconds(61)), true);
+        assert_eq!(
+            game.game_status(),
+            GameStatus::Timeout {
+                winner: ChessPieceColor::Black
+            }
+        );
+        // The game is only ended once
+        assert_eq!(clock.check_timeout(&mut game, start + seconds(62)), false);
+
+        // It is a draw when the opponent cannot mate, as with a bare king
+        let mut game = Game::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+        let mut clock = Clock::new(TimeControl::sudden_death(seconds(60)));
+        assert_eq!(clock.start(start).is_ok(), true);
+        assert_eq!(clock.check_timeout(&mut game, start + seconds(61)), true);
+        assert_eq!(
+            game.game_status(),
+            GameStatus::TimeoutVsInsufficientMaterial
+        );
+        assert_eq!(game.game_status().get_result(), "1/2-1/2");
+    }
+
+    #[test]
+    fn can_mate_test() {
+        let can_mate = |fen: &str, color: ChessPieceColor| {
+            return Game::from_fen(fen).unwrap().can_mate(color);
+        };
+        let white = ChessPieceColor::White;
+        assert_eq!(can_mate("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1", white), true);
+        assert_eq!(can_mate("4k3/4p3/8/8/8/8/8/4K3 w - - 0 1", white), false);
+        // A knight needs a piece other than a queen to block the king in
+        assert_eq!(can_mate("4k3/8/8/8/8/8/8/4KN2 w - - 0 1", white), false);
+        assert_eq!(can_mate("3qk3/8/8/8/8/8/8/4KN2 w - - 0 1", white), false);
+        assert_eq!(can_mate("3rk3/8/8/8/8/8/8/4KN2 w - - 0 1", white), true);
+        assert_eq!(can_mate("4k3/8/8/8/8/8/8/3NKN2 w - - 0 1", white), true);
+        // Bishops on one square color need a knight, a pawn or a bishop on the other color
+        assert_eq!(can_mate("3rk3/8/8/8/8/8/8/4KB2 w - - 0 1", white), false);
+        assert_eq!(can_mate("4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1", white), true);
+        assert_eq!(can_mate("4k3/8/8/8/8/8/8/3BKB2 w - - 0 1", white), false);
+        assert_eq!(can_mate("4kb2/8/8/8/8/8/8/4KB2 w - - 0 1", white), true);
+        assert_eq!(can_mate("4k3/4p3/8/8/8/8/8/4KB2 w - - 0 1", white), true);
+    }
+}
@@ -0,0 +1,105 @@
+#[cfg(test)]
+mod evaluation_test {
+    pub extern crate chess_engine;
+    use chess_engine::chess_game::*;
+    use chess_engine::evaluation::*;
+
+    fn evaluate_fen(fen: &str) -> i32 {
+        return evaluate(&Game::from_fen(fen).unwrap());
+    }
+
+    #[test]
+    fn evaluation_symmetry_test() {
+        // Make sure the starting position is even for both players
+        assert_eq!(evaluate_fen(STARTING_FEN), 0);
+        assert_eq!(
+            evaluate_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1"),
+            0
+        );
+
+        // Make sure a position and the same position with the colors swapped give the same
+        // value for the player to move
+        let positions = [
+            (
+                "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+                "r3k2r/pppbbppp/2n2q1P/1P2p3/3pn3/BN2PNP1/P1PPQPB1/R3K2R b KQkq - 0 1",
+            ),
+            (
+                "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+                "8/4p1p1/8/1r3P1K/kp5R/3P4/2P5/8 b - - 0 1",
+            ),
+        ];
+        for (fen, mirrored_fen) in positions.iter() {
+            assert_eq!(evaluate_fen(fen), evaluate_fen(mirrored_fen));
+        }
+    }
+
+    #[test]
+    fn evaluation_material_test() {
+        // White is a queen up, good for white and bad for black
+        let fen = "rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        assert_eq!(evaluate_fen(fen) > 800, true);
+        let fen = "rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1";
+        assert_eq!(evaluate_fen(fen) < -800, true);
+
+        // The breakdown is always for white
+        let game = Game::from_fen(fen).unwrap();
+        let breakdown = get_breakdown(&game, &EvaluationConfig::default());
+        assert_eq!(breakdown.total, -evaluate(&game));
+        assert_eq!(breakdown.material.middlegame, 900);
+        assert_eq!(breakdown.phase, MAX_PHASE - 4);
+        assert_eq!(format!("{}", breakdown).contains("Material"), true);
+
+        // With only kings and pawns only the endgame values count
+        let game = Game::from_fen("4k3/pppp4/8/8/8/8/PPPPP3/4K3 w - - 0 1").unwrap();
+        let breakdown = get_breakdown(&game, &EvaluationConfig::default());
+        assert_eq!(breakdown.phase, 0);
+        assert_eq!(breakdown.material.endgame, 120);
+        assert_eq!(breakdown.material.taper(breakdown.phase), 120);
+    }
+
+    #[test]
+    fn evaluation_terms_test() {
+        let config = EvaluationConfig::default();
+        let get = |fen: &str| get_breakdown(&Game::from_fen(fen).unwrap(), &config);
+
+        // Two bishops against bishop and knight
+        let breakdown = get("2bnk3/8/8/8/8/8/8/2B1KB2 w - - 0 1");
+        assert_eq!(breakdown.bishop_pair, config.bishop_pair);
+
+        // A passed pawn that has moved four rows
+        let breakdown = get("4k3/8/8/3P4/8/8/8/4K3 w - - 0 1");
+        assert_eq!(
+            breakdown.pawn_structure,
+            config.passed_pawn[3] + config.isolated_pawn
+        );
+
+        // Neither pawn is passed when they are in front of each other on files next to each other
+        let breakdown = get("4k3/2p5/8/3P4/8/8/8/4K3 w - - 0 1");
+        assert_eq!(breakdown.pawn_structure, Term::default());
+
+        // Doubled and isolated pawns, with the pawn in front passed
+        let breakdown = get("4k3/8/8/8/3P4/3P4/8/4K3 w - - 0 1");
+        assert_eq!(
+            breakdown.pawn_structure,
+            config.doubled_pawn + config.isolated_pawn * 2 + config.passed_pawn[2]
+        );
+
+        // A king behind its pawns is safer than a king in the open
+        let safe = get("6k1/8/8/8/8/8/5PPP/6K1 w - - 0 1");
+        let open = get("6k1/8/8/8/8/8/PPP5/6K1 w - - 0 1");
+        assert_eq!(
+            safe.king_safety.middlegame > open.king_safety.middlegame,
+            true
+        );
+
+        // Make sure changing the weights changes the evaluation
+        let game = Game::from_fen("2bnk3/8/8/8/8/8/8/2B1KB2 w - - 0 1").unwrap();
+        let mut changed_config = EvaluationConfig::default();
+        changed_config.bishop_pair = Term::new(200, 200);
+        assert_eq!(
+            evaluate_with_config(&game, &changed_config) > evaluate(&game),
+            true
+        );
+    }
+}
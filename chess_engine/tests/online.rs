@@ -0,0 +1,229 @@
+#[cfg(test)]
+mod online_test {
+    pub extern crate chess_engine;
+    use chess_engine::chess_game::*;
+    use chess_engine::clock::*;
+    use chess_engine::online::*;
+    use std::time::{Duration, Instant};
+
+    fn get_move(text: &str) -> ChessMove {
+        return ChessMove::from_long_algebraic_notation(text).unwrap();
+    }
+
+    #[test]
+    fn seats_test() {
+        // The first two clients play, the rest watch
+        let mut seats = Seats::new();
+        assert_eq!(seats.join(1, None), Seat::White);
+        assert_eq!(seats.join(2, None), Seat::Black);
+        assert_eq!(seats.join(3, None), Seat::Spectator);
+        assert_eq!(seats.join(4, Some(Seat::White)), Seat::Spectator);
+        assert_eq!(seats.get_player(ChessPieceColor::Black), Some(2));
+        assert_eq!(seats.get_clients(), vec![1, 2, 3, 4]);
+
+        // A color is free again when its player leaves
+        assert_eq!(seats.leave(1), Some(Seat::White));
+        assert_eq!(seats.leave(1), None);
+        assert_eq!(seats.get_seat(1), None);
+        assert_eq!(seats.join(4, None), Seat::White);
+        assert_eq!(seats.get_clients(), vec![4, 2, 3]);
+
+        // A wanted seat is given if it is free
+        let mut seats = Seats::new();
+        assert_eq!(seats.join(1, Some(Seat::Black)), Seat::Black);
+        assert_eq!(seats.join(2, Some(Seat::Black)), Seat::White);
+        assert_eq!(seats.join(3, Some(Seat::Spectator)), Seat::Spectator);
+        assert_eq!(seats.leave(2), Some(Seat::White));
+        assert_eq!(seats.join(3, Some(Seat::White)), Seat::White);
+        assert_eq!(seats.get_clients(), vec![3, 1]);
+    }
+
+    #[test]
+    fn seats_move_test() {
+        let mut seats = Seats::new();
+        seats.join(1, None);
+        seats.join(2, None);
+        seats.join(3, None);
+        let mut game = Game::new();
+        game.set_up_board();
+
+        assert_eq!(
+            seats.play_move(2, &mut game, get_move("e7e5")).err(),
+            Some(MoveRejection::WrongSeat {
+                turn: ChessPieceColor::White
+            })
+        );
+        assert_eq!(
+            seats.play_move(3, &mut game, get_move("e2e4")).err(),
+            Some(MoveRejection::Spectator)
+        );
+        assert_eq!(
+            seats.play_move(9, &mut game, get_move("e2e4")).err(),
+            Some(MoveRejection::Spectator)
+        );
+        assert_eq!(
+            seats.play_move(1, &mut game, get_move("e2e5")).err(),
+            Some(MoveRejection::Illegal(MoveError::InvalidPieceMove))
+        );
+        assert_eq!(game.to_fen(), STARTING_FEN);
+
+        assert_eq!(
+            seats.play_move(1, &mut game, get_move("e2e4")).is_ok(),
+            true
+        );
+        assert_eq!(
+            seats.play_move(2, &mut game, get_move("e7e5")).is_ok(),
+            true
+        );
+        assert_eq!(
+            game.to_fen(),
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2"
+        );
+    }
+
+    #[test]
+    fn lobby_test() {
+        let now = Instant::now();
+        let mut lobby = Lobby::new();
+        let blitz = Some(TimeControl::parse("180+2").unwrap());
+        assert_eq!(
+            lobby.create_game(1, blitz.clone(), Some(ChessPieceColor::Black), now),
+            (0, Seat::Black)
+        );
+        assert_eq!(lobby.create_game(2, None, None, now), (1, Seat::White));
+        assert_eq!(
+            lobby.get_game(0).unwrap().get_time_control(),
+            blitz.as_ref()
+        );
+        assert_eq!(lobby.get_game(0).unwrap().is_open(), true);
+
+        // The clock starts when both colors are taken
+        let times = lobby.get_game(0).unwrap().get_clock_times(now);
+        assert_eq!(times.unwrap().2, false);
+        assert_eq!(lobby.join_game(3, 0, false, now), Ok(Seat::White));
+        assert_eq!(lobby.get_game(0).unwrap().is_open(), false);
+        let times = lobby.get_game(0).unwrap().get_clock_times(now);
+        assert_eq!(
+            times,
+            Some((Duration::from_secs(180), Duration::from_secs(180), true))
+        );
+        assert_eq!(lobby.get_game(1).unwrap().get_clock_times(now), None);
+
+        // Players that join a full game watch it, and the games are played on their own
+        assert_eq!(lobby.join_game(4, 0, false, now), Ok(Seat::Spectator));
+        assert_eq!(lobby.join_game(5, 1, true, now), Ok(Seat::Spectator));
+        assert_eq!(
+            lobby.join_game(5, 7, false, now),
+            Err(LobbyError::NoGame { game_id: 7 })
+        );
+        assert_eq!(lobby.get_game_of(5), Some(1));
+        let game = lobby.get_game_mut(0).unwrap();
+        assert_eq!(game.play_move(3, get_move("e2e4"), now).is_ok(), true);
+        assert_eq!(
+            lobby.get_game(1).unwrap().game.to_fen(),
+            STARTING_FEN.to_string()
+        );
+
+        // A client is in one game at a time, and empty games are removed
+        assert_eq!(lobby.create_game(5, None, None, now), (2, Seat::White));
+        assert_eq!(lobby.get_game_of(5), Some(2));
+        assert_eq!(lobby.leave(2, now), Some(1));
+        assert_eq!(lobby.get_game(1).is_none(), true);
+        assert_eq!(lobby.leave(2, now), None);
+        assert_eq!(lobby.get_games().len(), 2);
+    }
+
+    #[test]
+    fn matchmaking_test() {
+        let now = Instant::now();
+        let mut lobby = Lobby::new();
+        let bullet = Some(TimeControl::parse("60").unwrap());
+        assert_eq!(lobby.find_game(1, bullet.clone(), now), None);
+        assert_eq!(lobby.find_game(2, None, now), None);
+        assert_eq!(lobby.is_queued(1), true);
+
+        // Only clients that want the same time control are paired, the first to wait is White
+        assert_eq!(lobby.find_game(3, bullet.clone(), now), Some(0));
+        let game = lobby.get_game(0).unwrap();
+        assert_eq!(game.seats.get_player(ChessPieceColor::White), Some(1));
+        assert_eq!(game.seats.get_player(ChessPieceColor::Black), Some(3));
+        assert_eq!(lobby.is_queued(1), false);
+        assert_eq!(lobby.is_queued(2), true);
+
+        // A client leaving the queue is not paired
+        assert_eq!(lobby.leave(2, now), None);
+        assert_eq!(lobby.find_game(4, None, now), None);
+
+        // The player to move loses when the time is up, and only once
+        let later = now + Duration::from_secs(61);
+        assert_eq!(
+            lobby.check_timeouts(later),
+            vec![(0, ChessPieceColor::White)]
+        );
+        assert_eq!(lobby.check_timeouts(later), vec![]);
+        let game = lobby.get_game_mut(0).unwrap();
+        assert_eq!(
+            game.get_status(),
+            GameStatus::Timeout {
+                winner: ChessPieceColor::Black
+            }
+        );
+        assert_eq!(
+            game.play_move(1, get_move("e2e4"), later).err(),
+            Some(MoveRejection::Illegal(MoveError::GameOver))
+        );
+    }
+
+    #[test]
+    fn online_game_test() {
+        let now = Instant::now();
+        let mut lobby = Lobby::new();
+        let blitz = Some(TimeControl::parse("180").unwrap());
+        let (game_id, _) = lobby.create_game(1, blitz, Some(ChessPieceColor::White), now);
+
+        // Moves are not played before the opponent has sat down
+        let game = lobby.get_game_mut(game_id).unwrap();
+        assert_eq!(
+            game.play_move(1, get_move("e2e4"), now).err(),
+            Some(MoveRejection::NotStarted)
+        );
+        assert_eq!(game.game.to_fen(), STARTING_FEN.to_string());
+
+        // The clock follows the moves once both players are seated
+        assert_eq!(lobby.join_game(2, game_id, false, now), Ok(Seat::Black));
+        let later = now + Duration::from_secs(10);
+        let game = lobby.get_game_mut(game_id).unwrap();
+        assert_eq!(game.play_move(1, get_move("e2e4"), later).is_ok(), true);
+        assert_eq!(game.get_time_left(later), Some(Duration::from_secs(180)));
+        assert_eq!(game.play_move(2, get_move("e7e5"), later).is_ok(), true);
+        assert_eq!(game.get_time_left(later), Some(Duration::from_secs(170)));
+
+        // Joining the own game again keeps the seat, so no one else can take it mid-game
+        assert_eq!(lobby.join_game(1, game_id, true, later), Ok(Seat::White));
+        let game = lobby.get_game(game_id).unwrap();
+        assert_eq!(game.has_started(), true);
+        assert_eq!(game.is_open(), false);
+        assert_eq!(game.get_status(), GameStatus::Ongoing);
+
+        // A player that leaves loses, and the seat is not given to the next client
+        assert_eq!(lobby.leave(2, later), Some(game_id));
+        let game = lobby.get_game(game_id).unwrap();
+        assert_eq!(
+            game.get_status(),
+            GameStatus::Resignation {
+                winner: ChessPieceColor::White
+            }
+        );
+        assert_eq!(game.get_time_left(later), None);
+        assert_eq!(game.is_open(), false);
+        assert_eq!(
+            lobby.join_game(3, game_id, false, later),
+            Ok(Seat::Spectator)
+        );
+        let game = lobby.get_game_mut(game_id).unwrap();
+        assert_eq!(
+            game.play_move(1, get_move("g1f3"), later).err(),
+            Some(MoveRejection::Illegal(MoveError::GameOver))
+        );
+    }
+}
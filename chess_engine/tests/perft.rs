@@ -0,0 +1,242 @@
+#[cfg(test)]
+mod perft_test {
+    pub extern crate chess_engine;
+    use chess_engine::chess_game::*;
+
+    // Count positions and compare them with the published numbers
+    fn assert_perft(fen: &str, counts: &[u64]) {
+        let mut game = Game::from_fen(fen).unwrap();
+        for (depth, count) in counts.iter().enumerate() {
+            assert_eq!(
+                game.perft(depth as u32 + 1),
+                *count,
+                "{} depth {}",
+                fen,
+                depth + 1
+            );
+        }
+        // Make sure perft leaves the position as it was
+        assert_eq!(game.to_fen(), fen);
+    }
+
+    fn assert_perft_depth(fen: &str, depth: u32, count: u64) {
+        let mut game = Game::from_fen(fen).unwrap();
+        assert_eq!(game.perft(depth), count, "{} depth {}", fen, depth);
+    }
+
+    #[test]
+    fn perft_starting_position_test() {
+        assert_perft(STARTING_FEN, &[20, 400, 8902, 197281]);
+    }
+
+    #[test]
+    fn perft_kiwipete_test() {
+        assert_perft(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            &[48, 2039, 97862],
+        );
+    }
+
+    #[test]
+    fn perft_reference_positions_test() {
+        // En passant, checks and pins with few pieces
+        assert_perft(
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            &[14, 191, 2812, 43238],
+        );
+        // Promotions and castling, and the same position with the colors swapped
+        assert_perft(
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            &[6, 264, 9467],
+        );
+        assert_perft(
+            "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1",
+            &[6, 264, 9467],
+        );
+        assert_perft(
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            &[44, 1486, 62379],
+        );
+        assert_perft(
+            "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+            &[46, 2079, 89890],
+        );
+    }
+
+    #[test]
+    fn perft_edge_cases_test() {
+        // Self stalemate, stalemate and checkmate, and promoting to give check
+        assert_perft_depth("K1k5/8/P7/8/8/8/8/8 w - - 0 1", 6, 2217);
+        assert_perft_depth("8/8/2k5/5q2/5n2/8/5K2/8 b - - 0 1", 4, 23527);
+        assert_perft_depth("8/P1k5/K7/8/8/8/8/8 w - - 0 1", 6, 92683);
+        assert_perft_depth("4k3/1P6/8/8/8/8/K7/8 w - - 0 1", 6, 217342);
+    }
+
+    // Chess960 positions are written back with X-FEN castling rights, so only the counts are compared
+    fn assert_chess960_perft(fen: &str, counts: &[u64]) {
+        let mut game = Game::from_fen(fen).unwrap();
+        assert_eq!(game.is_chess960(), true);
+        let hash = game.hash();
+        for (depth, count) in counts.iter().enumerate() {
+            assert_eq!(
+                game.perft(depth as u32 + 1),
+                *count,
+                "{} depth {}",
+                fen,
+                depth + 1
+            );
+        }
+        assert_eq!(game.hash(), hash);
+    }
+
+    #[test]
+    fn perft_chess960_test() {
+        assert_chess960_perft(
+            "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9",
+            &[21, 528, 12189],
+        );
+        assert_chess960_perft(
+            "b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9",
+            &[20, 479, 10471],
+        );
+
+        // Standard castling written as Chess960 castling gives the same counts
+        let mut game =
+            Game::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+        game.set_chess960(true);
+        assert_eq!(game.perft(3), 97862);
+    }
+
+    // These take too long without optimizations, run them with
+    // cargo test --release -- --ignored
+    #[test]
+    #[ignore]
+    fn perft_deep_test() {
+        // Illegal en passant captures, and en passant giving check
+        assert_perft_depth("3k4/3p4/8/K1P4r/8/8/8/8 b - - 0 1", 6, 1134888);
+        assert_perft_depth("8/8/4k3/8/2p5/8/B2P2K1/8 w - - 0 1", 6, 1015133);
+        assert_perft_depth("8/8/1k6/2b5/2pP4/8/5K2/8 b - d3 0 1", 6, 1440467);
+        // Castling giving check, castling rights and castling prevented
+        assert_perft_depth("5k2/8/8/8/8/8/8/4K2R w K - 0 1", 6, 661072);
+        assert_perft_depth("3k4/8/8/8/8/8/8/R3K3 w Q - 0 1", 6, 803711);
+        assert_perft_depth("r3k2r/1b4bq/8/8/8/8/7B/R3K2R w KQkq - 0 1", 4, 1274206);
+        assert_perft_depth("r3k2r/8/3Q4/8/8/5q2/8/R3K2R b KQkq - 0 1", 4, 1720476);
+        // Promoting out of check, discovered check, stalemate and checkmate
+        assert_perft_depth("2K2r2/4P3/8/8/8/8/8/3k4 w - - 0 1", 6, 3821001);
+        assert_perft_depth("8/8/1P2K3/8/2n5/1q6/8/5k2 b - - 0 1", 5, 1004658);
+        assert_perft_depth("8/k1P5/8/1K6/8/8/8/8 w - - 0 1", 7, 567584);
+        // The deepest published counts of the reference positions
+        assert_perft_depth(STARTING_FEN, 5, 4865609);
+        assert_perft_depth(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            4,
+            4085603,
+        );
+        assert_perft_depth("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 5, 674624);
+        assert_perft_depth(
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            4,
+            422333,
+        );
+        assert_perft_depth(
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            4,
+            2103487,
+        );
+        assert_perft_depth(
+            "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+            4,
+            3894594,
+        );
+        // Chess960
+        let mut game =
+            Game::from_fen("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9")
+                .unwrap();
+        assert_eq!(game.perft(4), 326672);
+        let mut game =
+            Game::from_fen("b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9").unwrap();
+        assert_eq!(game.perft(4), 273318);
+        let mut game =
+            Game::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+        game.set_chess960(true);
+        assert_eq!(game.perft(4), 4085603);
+    }
+
+    // The moves move_piece accepts have to be the generated moves, in the position
+    // and in the positions after them
+    fn assert_move_piece_matches_moves(game: &mut Game, depth: u32) {
+        let moves = game.generate_legal_moves();
+        for from in 0..64 {
+            for to in 0..64 {
+                let board_move = BoardMove::new(from % 8, from / 8, to % 8, to / 8);
+                let piece = game.get_board_piece_clone(board_move.from_pos);
+                let mut promote_pieces = vec![None];
+                if piece.is_some_and(|piece| piece.id == ChessPieceId::Pawn)
+                    && (board_move.to_pos.y == 0 || board_move.to_pos.y == 7)
+                {
+                    for promote_piece in [
+                        ChessPieceId::Queen,
+                        ChessPieceId::Rook,
+                        ChessPieceId::Bishop,
+                        ChessPieceId::Knight,
+                    ]
+                    .iter()
+                    {
+                        promote_pieces.push(Some(*promote_piece));
+                    }
+                }
+                for promote_piece in promote_pieces {
+                    let chess_move = ChessMove::new(board_move, promote_piece);
+                    let mut position = game.clone_position();
+                    let accepted = position.move_piece(board_move, true, promote_piece).is_ok();
+                    assert_eq!(
+                        accepted,
+                        moves.contains(&chess_move),
+                        "{} {}",
+                        game.to_fen(),
+                        chess_move.to_long_algebraic_notation()
+                    );
+                }
+            }
+        }
+        if depth > 1 {
+            for chess_move in moves {
+                let record = game.make_move(chess_move);
+                assert_move_piece_matches_moves(game, depth - 1);
+                game.unmake_move(record);
+            }
+        }
+    }
+
+    #[test]
+    fn move_piece_perft_test() {
+        let fens = [
+            STARTING_FEN,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9",
+        ];
+        for fen in fens.iter() {
+            let mut game = Game::from_fen(fen).unwrap();
+            assert_move_piece_matches_moves(&mut game, 2);
+        }
+    }
+
+    #[test]
+    fn divide_test() {
+        let mut game = Game::from_fen(STARTING_FEN).unwrap();
+        let counts = game.divide(3);
+        assert_eq!(counts.len(), 20);
+        let total: u64 = counts.iter().map(|(_, count)| count).sum();
+        assert_eq!(total, 8902);
+        let e2e4 = counts
+            .iter()
+            .find(|(chess_move, _)| chess_move.to_long_algebraic_notation() == "e2e4");
+        assert_eq!(e2e4.unwrap().1, 600);
+        assert_eq!(game.divide(0).len(), 0);
+    }
+}
@@ -0,0 +1,267 @@
+#[cfg(test)]
+mod protocol_test {
+    pub extern crate chess_engine;
+    use chess_engine::chess_game::*;
+    use chess_engine::clock::*;
+    use chess_engine::online::*;
+    use chess_engine::protocol::*;
+    use std::io::{Cursor, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+    use std::time::Duration;
+
+    fn get_move(text: &str) -> ChessMove {
+        return ChessMove::from_long_algebraic_notation(text).unwrap();
+    }
+
+    #[test]
+    fn message_test() {
+        let client_messages = vec![
+            ClientMessage::Hello {
+                version: PROTOCOL_VERSION,
+                name: "Vincent".to_string(),
+            },
+            ClientMessage::Move {
+                chess_move: get_move("e7e8n"),
+            },
+            ClientMessage::GetPosition,
+            ClientMessage::Chat {
+                text: "Lycka till! ♞".to_string(),
+            },
+            ClientMessage::ListGames,
+            ClientMessage::CreateGame {
+                time_control: Some(TimeControl::parse("40/5400+30:1800+30").unwrap()),
+                color: Some(ChessPieceColor::Black),
+            },
+            ClientMessage::CreateGame {
+                time_control: None,
+                color: None,
+            },
+            ClientMessage::JoinGame {
+                game_id: u64::MAX,
+                spectate: true,
+            },
+            ClientMessage::FindGame {
+                time_control: Some(TimeControl::simple_delay(
+                    Duration::from_secs(300),
+                    Duration::from_secs(5),
+                )),
+            },
+            ClientMessage::LeaveGame,
+        ];
+        for message in client_messages.iter() {
+            assert_eq!(ClientMessage::decode(&message.encode()).unwrap(), *message);
+        }
+
+        let server_messages = vec![
+            ServerMessage::Welcome {
+                version: PROTOCOL_VERSION,
+            },
+            ServerMessage::Rejected {
+                reason: "Too old".to_string(),
+            },
+            ServerMessage::Moved {
+                chess_move: get_move("e2e4"),
+                san: "e4".to_string(),
+                fen: "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1".to_string(),
+            },
+            ServerMessage::Seated {
+                game_id: 3,
+                seat: Seat::Spectator,
+                time_control: None,
+            },
+            ServerMessage::MoveRejected {
+                rejection: MoveRejection::Spectator,
+            },
+            ServerMessage::MoveRejected {
+                rejection: MoveRejection::WrongSeat {
+                    turn: ChessPieceColor::Black,
+                },
+            },
+            ServerMessage::MoveRejected {
+                rejection: MoveRejection::Illegal(MoveError::GameOver),
+            },
+            ServerMessage::MoveRejected {
+                rejection: MoveRejection::NotStarted,
+            },
+            ServerMessage::MoveRejected {
+                rejection: MoveRejection::Clock(ClockError::Flagged {
+                    color: ChessPieceColor::White,
+                }),
+            },
+            ServerMessage::Position {
+                fen: STARTING_FEN.to_string(),
+            },
+            ServerMessage::Chat {
+                name: "Victor".to_string(),
+                text: "Tack".to_string(),
+            },
+            ServerMessage::GameList { games: vec![] },
+            ServerMessage::GameList {
+                games: vec![
+                    GameInfo {
+                        id: 0,
+                        time_control: Some(TimeControl::parse("180+2").unwrap()),
+                        white: Some("Vincent".to_string()),
+                        black: None,
+                        spectators: 2,
+                        is_over: false,
+                    },
+                    GameInfo {
+                        id: 1,
+                        time_control: None,
+                        white: None,
+                        black: Some("Victor".to_string()),
+                        spectators: 0,
+                        is_over: true,
+                    },
+                ],
+            },
+            ServerMessage::Queued,
+            ServerMessage::LobbyError {
+                error: LobbyError::NoGame { game_id: 12 },
+            },
+            ServerMessage::LobbyError {
+                error: LobbyError::NotInGame,
+            },
+            ServerMessage::Clock {
+                white: Duration::from_millis(179_500),
+                black: Duration::from_secs(180),
+                running: true,
+            },
+            ServerMessage::TimeUp {
+                color: ChessPieceColor::Black,
+            },
+            ServerMessage::PlayerLeft { seat: Seat::White },
+        ];
+        for message in server_messages.iter() {
+            assert_eq!(ServerMessage::decode(&message.encode()).unwrap(), *message);
+        }
+
+        // Messages that are cut off, too long or unknown are not read
+        let bytes = server_messages[2].encode();
+        assert_eq!(
+            ServerMessage::decode(&bytes[..bytes.len() - 1]).is_err(),
+            true
+        );
+        let mut longer = bytes.clone();
+        longer.push(0);
+        assert_eq!(ServerMessage::decode(&longer).is_err(), true);
+        assert_eq!(ServerMessage::decode(&[200]).is_err(), true);
+        assert_eq!(ServerMessage::decode(&[]).is_err(), true);
+        assert_eq!(ServerMessage::decode(&[3, 2, 200]).is_err(), true);
+        assert_eq!(ServerMessage::decode(&[6, 3]).is_err(), true);
+        let bad_move = ClientMessage::Chat {
+            text: "e9e4".to_string(),
+        };
+        let mut bytes = bad_move.encode();
+        bytes[0] = 1;
+        assert_eq!(ClientMessage::decode(&bytes).is_err(), true);
+    }
+
+    #[test]
+    fn frame_test() {
+        // Messages longer than the old 32 byte messages are sent whole
+        let message = ServerMessage::Chat {
+            name: "Victor".to_string(),
+            text: "x".repeat(1000),
+        };
+        let mut buffer = Vec::new();
+        assert_eq!(send_server_message(&mut buffer, &message).is_ok(), true);
+        assert_eq!(send_server_message(&mut buffer, &message).is_ok(), true);
+        let mut reader = Cursor::new(buffer);
+        assert_eq!(read_server_message(&mut reader).unwrap(), message);
+        assert_eq!(read_server_message(&mut reader).unwrap(), message);
+        match read_server_message(&mut reader) {
+            Err(ProtocolError::Closed) => {}
+            result => panic!("Expected Closed, got {:?}", result),
+        }
+
+        // Frames are taken out of what has been read once all of them has arrived
+        let mut frames = Vec::new();
+        assert_eq!(send_server_message(&mut frames, &message).is_ok(), true);
+        let mut buffer = frames[..frames.len() - 1].to_vec();
+        assert_eq!(take_frame(&mut buffer).unwrap(), None);
+        buffer.push(frames[frames.len() - 1]);
+        buffer.extend_from_slice(&frames[..2]);
+        let frame = take_frame(&mut buffer).unwrap().unwrap();
+        assert_eq!(ServerMessage::decode(&frame).unwrap(), message);
+        assert_eq!(buffer, frames[..2].to_vec());
+
+        let mut buffer = Vec::new();
+        let too_large = vec![0; MAX_FRAME_SIZE + 1];
+        assert_eq!(write_frame(&mut buffer, &too_large).is_err(), true);
+        assert_eq!(buffer.len(), 0);
+        let mut reader = Cursor::new(((MAX_FRAME_SIZE + 1) as u32).to_be_bytes().to_vec());
+        match read_frame(&mut reader) {
+            Err(ProtocolError::FrameTooLarge(size)) => assert_eq!(size, MAX_FRAME_SIZE + 1),
+            result => panic!("Expected FrameTooLarge, got {:?}", result),
+        }
+    }
+
+    // Run the server side of the handshake on its own thread, returns what the client got
+    fn handshake_with(
+        client: impl FnOnce(&mut TcpStream) -> Result<u32, ProtocolError>,
+    ) -> (Result<String, ProtocolError>, Result<u32, ProtocolError>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            return server_handshake(&mut stream);
+        });
+        let mut stream = TcpStream::connect(address).unwrap();
+        let client_result = client(&mut stream);
+        return (server.join().unwrap(), client_result);
+    }
+
+    #[test]
+    fn handshake_test() {
+        let (server_result, client_result) =
+            handshake_with(|stream| client_handshake(stream, "Vincent"));
+        assert_eq!(server_result.unwrap(), "Vincent");
+        assert_eq!(client_result.unwrap(), PROTOCOL_VERSION);
+
+        // A client with a newer version is told it is not supported
+        let (server_result, client_result) = handshake_with(|stream| {
+            let hello = ClientMessage::Hello {
+                version: PROTOCOL_VERSION + 1,
+                name: "Future".to_string(),
+            };
+            send_client_message(stream, &hello)?;
+            match read_server_message(stream)? {
+                ServerMessage::Rejected { reason } => return Err(ProtocolError::Rejected(reason)),
+                _ => return Ok(0),
+            }
+        });
+        match server_result {
+            Err(ProtocolError::IncompatibleVersion { version }) => {
+                assert_eq!(version, PROTOCOL_VERSION + 1)
+            }
+            result => panic!("Expected IncompatibleVersion, got {:?}", result),
+        }
+        match client_result {
+            Err(ProtocolError::Rejected(reason)) => assert_eq!(reason.contains("version"), true),
+            result => panic!("Expected Rejected, got {:?}", result),
+        }
+
+        // A client of the old protocol sends 32 bytes, which is not a frame
+        let (server_result, _) = handshake_with(|stream| {
+            let mut buffer = b"move:e4".to_vec();
+            buffer.resize(32, 0);
+            stream.write_all(&buffer)?;
+            return Ok(0);
+        });
+        assert_eq!(server_result.is_err(), true);
+
+        // The first message must be Hello
+        let (server_result, client_result) = handshake_with(|stream| {
+            send_client_message(stream, &ClientMessage::GetPosition)?;
+            match read_server_message(stream)? {
+                ServerMessage::Rejected { reason } => return Err(ProtocolError::Rejected(reason)),
+                _ => return Ok(0),
+            }
+        });
+        assert_eq!(server_result.is_err(), true);
+        assert_eq!(client_result.is_err(), true);
+    }
+}
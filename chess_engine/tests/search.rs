@@ -0,0 +1,161 @@
+#[cfg(test)]
+mod search_test {
+    pub extern crate chess_engine;
+    use chess_engine::chess_game::*;
+    use chess_engine::evaluation::*;
+    use chess_engine::search::*;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn search_depth(fen: &str, depth: u32) -> SearchResult {
+        let game = Game::from_fen(fen).unwrap();
+        let limits = SearchLimits {
+            depth: Some(depth),
+            ..SearchLimits::default()
+        };
+        return search(&game, &limits);
+    }
+
+    fn best_move(result: &SearchResult) -> String {
+        return result.best_move.unwrap().to_long_algebraic_notation();
+    }
+
+    #[test]
+    fn search_mate_test() {
+        // Back rank mate in one
+        let result = search_depth("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1", 3);
+        assert_eq!(best_move(&result), "a1a8");
+        assert_eq!(get_mate_in(result.score), Some(1));
+
+        // Mate in two, a knight has to be given up first
+        let result = search_depth(
+            "r2qkb1r/pp2nppp/3p4/2pNN1B1/2BnP3/3P4/PPP2PPP/R2bK2R w KQkq - 1 1",
+            4,
+        );
+        assert_eq!(best_move(&result), "d5f6");
+        assert_eq!(get_mate_in(result.score), Some(2));
+        assert_eq!(result.pv.len(), 3);
+
+        // The player to move gets mated
+        let result = search_depth("k7/8/1K6/8/8/8/8/7R b - - 0 1", 3);
+        assert_eq!(get_mate_in(result.score), Some(-1));
+
+        // No moves in checkmate and stalemate
+        let result = search_depth("R5k1/5ppp/8/8/8/8/5PPP/6K1 b - - 0 1", 3);
+        assert_eq!(result.best_move.is_none(), true);
+        assert_eq!(result.score, -MATE_SCORE);
+        let result = search_depth("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1", 3);
+        assert_eq!(result.best_move.is_none(), true);
+        assert_eq!(result.score, 0);
+    }
+
+    #[test]
+    fn search_material_test() {
+        // Take a piece that is not defended
+        let result = search_depth("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1", 2);
+        assert_eq!(best_move(&result), "d2d5");
+        assert_eq!(result.score >= 400, true);
+
+        // Do not take a defended pawn with the queen
+        let result = search_depth("4k3/8/2p5/3p4/8/8/3Q4/4K3 w - - 0 1", 3);
+        assert_eq!(best_move(&result) != "d2d5", true);
+
+        // Promote
+        let result = search_depth("8/4P2k/8/8/8/8/8/4K3 w - - 0 1", 3);
+        assert_eq!(best_move(&result), "e7e8q");
+    }
+
+    #[test]
+    fn search_draw_test() {
+        // Repeat a position to get a draw when behind
+        let mut game = Game::from_fen("6k1/8/8/8/Q7/8/1R6/4K3 b - - 0 1").unwrap();
+        for text in &["Kh8", "Qa5", "Kg8", "Qa4"] {
+            assert_eq!(game.algebraic_notation_move(text.to_string()).is_ok(), true);
+        }
+        let limits = SearchLimits {
+            depth: Some(3),
+            ..SearchLimits::default()
+        };
+        let result = search(&game, &limits);
+        assert_eq!(best_move(&result), "g8h8");
+        assert_eq!(result.score, 0);
+
+        // Insufficient material is a draw
+        let result = search_depth("8/8/4k3/8/8/3NK3/8/8 w - - 0 1", 4);
+        assert_eq!(result.score, 0);
+    }
+
+    #[test]
+    fn search_limits_test() {
+        let mut game = Game::new();
+        game.set_up_board();
+
+        // Make sure the search stops at the depth limit and reports every depth
+        let mut searcher = Searcher::new(1);
+        let mut depths = Vec::new();
+        let limits = SearchLimits {
+            depth: Some(3),
+            ..SearchLimits::default()
+        };
+        let result = searcher.search_with_info(&game, &limits, |info| depths.push(info.depth));
+        assert_eq!(result.depth, 3);
+        assert_eq!(depths, vec![1, 2, 3]);
+        assert_eq!(result.pv.len() >= 1, true);
+        assert_eq!(result.pv[0] == result.best_move.unwrap(), true);
+
+        // Make sure the search stops at the node and time limits with a legal move
+        let limits = SearchLimits {
+            nodes: Some(5000),
+            ..SearchLimits::default()
+        };
+        let result = searcher.search(&game, &limits);
+        assert_eq!(result.nodes < 5000 + 1024, true);
+        assert_eq!(
+            game.generate_legal_moves()
+                .contains(&result.best_move.unwrap()),
+            true
+        );
+        let limits = SearchLimits {
+            time: Some(Duration::from_millis(50)),
+            ..SearchLimits::default()
+        };
+        let result = searcher.search(&game, &limits);
+        assert_eq!(result.time < Duration::from_millis(1000), true);
+        assert_eq!(result.best_move.is_some(), true);
+
+        // Make sure a stopped search still gives a move
+        let limits = SearchLimits {
+            stop: Some(Arc::new(AtomicBool::new(true))),
+            ..SearchLimits::default()
+        };
+        let result = searcher.search(&game, &limits);
+        assert_eq!(result.best_move.is_some(), true);
+
+        // Make sure searching does not change the game
+        assert_eq!(game.to_fen(), STARTING_FEN);
+    }
+
+    #[test]
+    fn search_config_test() {
+        let game = Game::from_fen("4k3/8/8/3n4/8/8/3R4/4K3 w - - 0 1").unwrap();
+        let limits = SearchLimits {
+            depth: Some(2),
+            ..SearchLimits::default()
+        };
+        let capture = ChessMove::from_long_algebraic_notation("d2d5").unwrap();
+
+        // Make sure the free knight is taken with the default weights
+        let mut searcher = Searcher::new(1);
+        assert_eq!(searcher.search(&game, &limits).best_move, Some(capture));
+
+        // Make sure the search uses the weights it is given
+        let mut config = EvaluationConfig::default();
+        config.piece_values[ChessPieceId::Knight as usize] = Term::new(-1000, -1000);
+        searcher.set_evaluation_config(config);
+        assert_eq!(
+            searcher.search(&game, &limits).best_move == Some(capture),
+            false
+        );
+    }
+}
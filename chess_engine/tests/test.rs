@@ -1124,4 +1124,1034 @@ mod chess_lib_test {
             false
         );
     }
+
+    #[test]
+    fn fen_test() {
+        // Make sure the starting position is exported correctly
+        let mut game = Game::new();
+        game.set_up_board();
+        assert_eq!(game.to_fen(), STARTING_FEN);
+
+        // Make sure side to move, en passant square and counters are exported
+        assert_eq!(game.algebraic_notation_move("e4".to_string()).is_ok(), true);
+        assert_eq!(
+            game.to_fen(),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+        );
+        assert_eq!(game.algebraic_notation_move("c5".to_string()).is_ok(), true);
+        assert_eq!(
+            game.algebraic_notation_move("Nf3".to_string()).is_ok(),
+            true
+        );
+        assert_eq!(
+            game.to_fen(),
+            "rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2"
+        );
+
+        // Make sure castling rights are lost when the king moves
+        assert_eq!(
+            game.algebraic_notation_move("Nc6".to_string()).is_ok(),
+            true
+        );
+        assert_eq!(
+            game.algebraic_notation_move("Ke2".to_string()).is_ok(),
+            true
+        );
+        assert_eq!(
+            game.to_fen(),
+            "r1bqkbnr/pp1ppppp/2n5/2p5/4P3/5N2/PPPPKPPP/RNBQ1B1R b kq - 3 3"
+        );
+
+        // Make sure positions round trip
+        let fens = [
+            STARTING_FEN,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "4k3/8/8/8/8/8/8/R3K3 w Q - 12 40",
+        ];
+        for fen in fens.iter() {
+            let game = Game::from_fen(fen);
+            assert_eq!(game.is_ok(), true);
+            assert_eq!(game.unwrap().to_fen(), fen.to_string());
+        }
+
+        // Make sure en passant can be played from an imported position
+        let mut game =
+            Game::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3")
+                .unwrap();
+        assert_eq!(
+            game.algebraic_notation_move("exf6".to_string()).is_ok(),
+            true
+        );
+        assert_eq!(
+            game.get_board_piece_clone(BoardPosition::from_algebraic_notation("f5").unwrap())
+                .is_some(),
+            false
+        );
+
+        // Make sure pawns off their starting rank cannot move two forward
+        let mut game = Game::from_fen("4k3/8/8/8/8/4P3/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            game.algebraic_notation_move("e5".to_string()).is_ok(),
+            false
+        );
+
+        // Make sure the halfmove clock is used for the fifty move rule
+        let mut game = Game::from_fen("4k3/8/8/8/8/8/8/R3K3 w Q - 100 80").unwrap();
+        assert_eq!(game.game_is_over(), true);
+
+        // Make sure malformed strings are rejected
+        let invalid_fens = [
+            "",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP w KQkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNRR w KQkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBN w KQkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNX w KQkq - 0 1",
+            "rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkqK - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkA - 0 1",
+            "rnbqkbn1/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e3 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e9 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - x 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 0",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0",
+        ];
+        for fen in invalid_fens.iter() {
+            assert_eq!(Game::from_fen(fen).is_err(), true);
+        }
+    }
+
+    #[test]
+    fn move_to_san_test() {
+        // Make sure pawn and piece moves are written correctly
+        let mut game = Game::new();
+        game.set_up_board();
+        assert_eq!(game.move_to_san(BoardMove::new(4, 6, 4, 4), None), "e4");
+        assert_eq!(game.move_to_san(BoardMove::new(6, 7, 5, 5), None), "Nf3");
+
+        // Make sure captures and check mate are written correctly
+        for text in ["e4", "e5", "Bc4", "Nc6", "Qh5", "Nf6"].iter() {
+            assert_eq!(game.algebraic_notation_move(text.to_string()).is_ok(), true);
+        }
+        assert_eq!(game.move_to_san(BoardMove::new(7, 3, 5, 1), None), "Qxf7#");
+
+        // Make sure castling is written correctly
+        let mut game =
+            Game::from_fen("r3k2r/pppppppp/8/8/8/8/PPPPPPPP/R3K2R w KQkq - 0 1").unwrap();
+        assert_eq!(game.move_to_san(BoardMove::new(4, 7, 6, 7), None), "O-O");
+        assert_eq!(game.move_to_san(BoardMove::new(4, 7, 2, 7), None), "O-O-O");
+
+        // Make sure the file is used to tell pieces apart
+        let mut game = Game::from_fen("1k6/8/8/8/8/8/4K3/R6R w - - 0 1").unwrap();
+        assert_eq!(game.move_to_san(BoardMove::new(0, 7, 3, 7), None), "Rad1");
+
+        // Make sure the rank is used to tell pieces apart
+        let mut game = Game::from_fen("7k/8/8/R7/8/8/4K3/R7 w - - 0 1").unwrap();
+        assert_eq!(game.move_to_san(BoardMove::new(0, 7, 0, 5), None), "R1a3");
+
+        // Make sure both file and rank are used when needed
+        let mut game = Game::from_fen("8/7k/8/8/8/Q7/8/Q1Q1K3 w - - 0 1").unwrap();
+        assert_eq!(game.move_to_san(BoardMove::new(0, 7, 1, 6), None), "Qa1b2");
+
+        // Make sure en passant and promotions are written correctly
+        let mut game = Game::from_fen("1r5k/P7/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+        assert_eq!(game.move_to_san(BoardMove::new(4, 3, 3, 2), None), "exd6");
+        assert_eq!(
+            game.move_to_san(BoardMove::new(0, 1, 0, 0), Some(ChessPieceId::Queen)),
+            "a8=Q"
+        );
+        assert_eq!(
+            game.move_to_san(BoardMove::new(0, 1, 1, 0), Some(ChessPieceId::Queen)),
+            "axb8=Q+"
+        );
+        assert_eq!(
+            game.move_to_san(BoardMove::new(0, 1, 1, 0), Some(ChessPieceId::Knight)),
+            "axb8=N"
+        );
+    }
+
+    #[test]
+    fn played_move_test() {
+        // Make sure the resolved move is returned
+        let mut game = Game::new();
+        game.set_up_board();
+        let played_move = game.algebraic_notation_move("Nf3".to_string()).unwrap();
+        assert_eq!(played_move.board_move == BoardMove::new(6, 7, 5, 5), true);
+        assert_eq!(played_move.piece.id == ChessPieceId::Knight, true);
+        assert_eq!(played_move.is_capture(), false);
+        assert_eq!(played_move.to_long_algebraic_notation(), "g1f3");
+
+        // Make sure captures are returned
+        let mut game = Game::from_fen("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1").unwrap();
+        let played_move = game.algebraic_notation_move("exd5".to_string()).unwrap();
+        assert_eq!(played_move.is_capture(), true);
+        assert_eq!(played_move.is_passant(), false);
+        let (captured_pos, captured_piece) = played_move.captured.unwrap();
+        assert_eq!(captured_pos == played_move.board_move.to_pos, true);
+        assert_eq!(captured_piece.id == ChessPieceId::Pawn, true);
+        assert_eq!(captured_piece.color == ChessPieceColor::Black, true);
+
+        // Make sure en passant captures return the square of the taken pawn
+        let mut game = Game::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+        let played_move = game.algebraic_notation_move("exd6".to_string()).unwrap();
+        assert_eq!(played_move.is_passant(), true);
+        assert_eq!(
+            played_move.captured.unwrap().0
+                == BoardPosition::from_algebraic_notation("d5").unwrap(),
+            true
+        );
+
+        // Make sure promotions are returned
+        let mut game = Game::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let played_move = game.algebraic_notation_move("a8N".to_string()).unwrap();
+        assert_eq!(
+            played_move.promote_piece == Some(ChessPieceId::Knight),
+            true
+        );
+        assert_eq!(played_move.to_long_algebraic_notation(), "a7a8n");
+
+        // Make sure the rook move is returned when casteling
+        let mut game = Game::from_fen("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+        let played_move = game.play_move(BoardMove::new(4, 7, 2, 7), None).unwrap();
+        assert_eq!(played_move.is_castle(), true);
+        assert_eq!(played_move.is_capture(), false);
+        assert_eq!(
+            played_move.rook_move.unwrap().to_long_algebraic_notation(),
+            "a1d1"
+        );
+
+        // Make sure nothing is returned for invalid moves
+        let mut game = Game::new();
+        game.set_up_board();
+        assert_eq!(
+            game.algebraic_notation_move("Nf4".to_string()).is_err(),
+            true
+        );
+    }
+
+    #[test]
+    fn pgn_test() {
+        use chess_engine::pgn::*;
+
+        let text = r#"[Event "Casual game"]
+[Site "Stockholm"]
+[Date "2021.05.01"]
+[Round "1"]
+[White "Vincent"]
+[Black "Victor"]
+[Result "1-0"]
+[Annotator "Someone"]
+
+1. e4 e5 {The open game} 2. Nf3 $1 Nc6 (2... d6 3. d4 (3. Bc4 Be7) 3... exd4)
+3. Bc5?! ; Not a legal move for a bishop on f1
+"#;
+        // Make sure illegal moves are found when replaying
+        let game = PgnGame::parse(text).unwrap();
+        assert_eq!(game.replay().is_err(), true);
+
+        let text = r#"[Event "Casual game"]
+[Site "Stockholm"]
+[Date "2021.05.01"]
+[Round "1"]
+[White "Vincent"]
+[Black "Victor"]
+[Result "1-0"]
+[Annotator "Someone"]
+
+1. e4 e5 {The open game} 2. Nf3 $1 Nc6 (2... d6 3. d4 (3. Bc4 Be7) 3... exd4)
+3. Bc4 Nf6?! ; Two knights defence
+4. Ng5 d5 5. exd5 Na5 6. Bb5+ c6 7. dxc6 bxc6 8. Qf3 cxb5 9. Qxa8 Bd6 10. O-O O-O 1-0
+"#;
+        // Make sure tags, comments, annotations and variations are parsed
+        let game = PgnGame::parse(text).unwrap();
+        assert_eq!(game.get_tag("White"), Some("Vincent"));
+        assert_eq!(game.get_tag("Annotator"), Some("Someone"));
+        assert_eq!(game.result, "1-0");
+        assert_eq!(game.moves.len(), 20);
+        assert_eq!(game.moves[1].comments[0], "The open game");
+        assert_eq!(game.moves[2].nags, vec![1]);
+        assert_eq!(game.moves[3].variations.len(), 1);
+        assert_eq!(game.moves[3].variations[0].len(), 3);
+        assert_eq!(game.moves[3].variations[0][1].variations[0][0].san, "Bc4");
+        assert_eq!(game.moves[5].nags, vec![6]);
+        assert_eq!(game.moves[5].comments[0], "Two knights defence");
+
+        // Make sure the moves can be replayed
+        let final_position = game.replay().unwrap();
+        assert_eq!(
+            final_position.to_fen(),
+            "Q1bq1rk1/p4ppp/3b1n2/np2p1N1/8/8/PPPP1PPP/RNB2RK1 w - - 3 11"
+        );
+
+        // Make sure a written game can be read again, and that lines are wrapped
+        let written = game.to_pgn();
+        assert_eq!(written.starts_with("[Event \"Casual game\"]\n"), true);
+        assert_eq!(
+            written.contains("Nc6 (2... d6 3. d4 (3. Bc4 Be7) 3... exd4)"),
+            true
+        );
+        for line in written.lines() {
+            assert_eq!(line.len() <= 80, true);
+        }
+        let reread = PgnGame::parse(&written).unwrap();
+        assert_eq!(reread.to_pgn(), written);
+
+        // Make sure games from a position are numbered correctly
+        let mut game = PgnGame::new();
+        game.set_tag("SetUp", "1");
+        game.set_tag("FEN", "4k3/8/8/8/8/8/4P3/4K3 b - - 0 30");
+        game.moves.push(PgnMove::new("Kd7"));
+        game.moves.push(PgnMove::new("e4"));
+        game.set_result("*");
+        assert_eq!(game.to_pgn().contains("\n30... Kd7 31. e4 *\n"), true);
+        assert_eq!(game.replay().is_ok(), true);
+
+        // Make sure multiple games are read one at a time
+        let database = format!("{}\n{}", text, written);
+        let mut reader = PgnReader::new(std::io::Cursor::new(database));
+        assert_eq!(reader.next().unwrap().unwrap().moves.len(), 20);
+        assert_eq!(reader.next().unwrap().unwrap().moves.len(), 20);
+        assert_eq!(reader.next().is_none(), true);
+
+        // Make sure games without moves are not joined with the next game
+        let database = "[Event \"First\"]\n[Site \"Here\"]\n\n[Event \"Second\"]\n\n";
+        let mut reader = PgnReader::new(std::io::Cursor::new(database));
+        let first = reader.next().unwrap().unwrap();
+        assert_eq!(first.get_tag("Event"), Some("First"));
+        assert_eq!(first.moves.len(), 0);
+        let second = reader.next().unwrap().unwrap();
+        assert_eq!(second.get_tag("Event"), Some("Second"));
+        assert_eq!(second.moves.len(), 0);
+        assert_eq!(reader.next().is_none(), true);
+
+        // Make sure malformed games are rejected
+        assert_eq!(PgnGame::parse("[Event \"Unclosed]\n1. e4 *").is_err(), true);
+        assert_eq!(PgnGame::parse("1. e4 (1. d4 *").is_err(), true);
+        assert_eq!(PgnGame::parse("1. e4 {Unclosed comment").is_err(), true);
+        assert_eq!(PgnGame::parse("1. e4 e5 1-0 2. Nf3").is_err(), true);
+    }
+
+    #[test]
+    fn undo_redo_test() {
+        let mut game = Game::new();
+        game.set_up_board();
+        let moves = vec![
+            "e4", "d5", "exd5", "c5", "dxc6", "Nf6", "cxb7", "e6", "bxa8=Q", "Be7", "Nf3", "O-O",
+        ];
+        let mut fens = vec![game.to_fen()];
+        for text in &moves {
+            assert_eq!(game.algebraic_notation_move(text.to_string()).is_ok(), true);
+            fens.push(game.to_fen());
+        }
+        assert_eq!(game.get_history().len(), moves.len());
+
+        // Make sure every position is restored when taking back moves
+        for i in (0..moves.len()).rev() {
+            let played_move = game.undo_move();
+            assert_eq!(played_move.is_some(), true);
+            assert_eq!(game.to_fen(), fens[i]);
+        }
+        assert_eq!(game.undo_move().is_none(), true);
+        assert_eq!(game.get_history().len(), 0);
+
+        // Make sure moves can be played again
+        for i in 0..moves.len() {
+            assert_eq!(game.redo_move().is_some(), true);
+            assert_eq!(game.to_fen(), fens[i + 1]);
+        }
+        assert_eq!(game.redo_move().is_none(), true);
+
+        // Make sure all past positions can be iterated
+        let past_fens: Vec<String> = game.get_past_positions().map(|g| g.to_fen()).collect();
+        assert_eq!(past_fens, fens);
+        assert_eq!(game.to_fen(), fens[moves.len()]);
+
+        // Make sure a new move clears the moves that can be played again
+        game.undo_move();
+        game.undo_move();
+        assert_eq!(
+            game.algebraic_notation_move("Nc3".to_string()).is_ok(),
+            true
+        );
+        assert_eq!(game.redo_move().is_none(), true);
+        assert_eq!(game.get_history().len(), moves.len() - 1);
+    }
+
+    #[test]
+    fn repetition_test() {
+        let mut game = Game::new();
+        game.set_up_board();
+        assert_eq!(game.get_repetition_count(), 1);
+        assert_eq!(game.claim_draw().is_err(), true);
+
+        // Move the knights back and forth to repeat the starting position
+        let knight_moves = vec!["Nf3", "Nf6", "Ng1", "Ng8"];
+        for text in &knight_moves {
+            assert_eq!(game.algebraic_notation_move(text.to_string()).is_ok(), true);
+        }
+        assert_eq!(game.get_repetition_count(), 2);
+        for text in &knight_moves {
+            assert_eq!(game.algebraic_notation_move(text.to_string()).is_ok(), true);
+        }
+        assert_eq!(game.get_repetition_count(), 3);
+        assert_eq!(game.is_threefold_repetition(), true);
+        assert_eq!(game.game_is_over(), false);
+
+        // Make sure a claimed draw ends the game
+        let mut claimed_game = game.clone();
+        assert_eq!(claimed_game.claim_draw().is_ok(), true);
+        assert_eq!(claimed_game.game_is_over(), true);
+        assert_eq!(claimed_game.get_winner() == None, true);
+        assert_eq!(
+            claimed_game
+                .algebraic_notation_move("e4".to_string())
+                .is_err(),
+            true
+        );
+
+        // Make sure the fifth repetition ends the game by itself
+        for _ in 0..2 {
+            for text in &knight_moves {
+                assert_eq!(game.game_is_over(), false);
+                assert_eq!(game.algebraic_notation_move(text.to_string()).is_ok(), true);
+            }
+        }
+        assert_eq!(game.get_repetition_count(), 5);
+        assert_eq!(game.is_fivefold_repetition(), true);
+        assert_eq!(game.game_is_over(), true);
+        assert_eq!(
+            game.algebraic_notation_move("Nf3".to_string()).err(),
+            Some(MoveError::GameOver)
+        );
+
+        // Make sure positions with different castling rights are different
+        let mut game = Game::from_fen("r3k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+        for text in &["Rh2", "Ra7", "Rh1", "Ra8"] {
+            assert_eq!(game.algebraic_notation_move(text.to_string()).is_ok(), true);
+        }
+        assert_eq!(game.get_repetition_count(), 1);
+
+        // Make sure en passant only counts when the capture is possible
+        let mut game = Game::from_fen("4k3/8/8/8/4p3/8/3P4/4K3 w - - 0 1").unwrap();
+        assert_eq!(game.algebraic_notation_move("d4".to_string()).is_ok(), true);
+        assert_eq!(game.get_repetition_count(), 1);
+        for text in &["Kf7", "Kd2", "Ke8", "Ke1"] {
+            assert_eq!(game.algebraic_notation_move(text.to_string()).is_ok(), true);
+        }
+        assert_eq!(game.get_repetition_count(), 1);
+        let mut game = Game::from_fen("8/8/8/8/k3p2R/8/3P4/4K3 w - - 0 1").unwrap();
+        assert_eq!(game.algebraic_notation_move("d4".to_string()).is_ok(), true);
+        for text in &["Ka5", "Kf1", "Ka4", "Ke1"] {
+            assert_eq!(game.algebraic_notation_move(text.to_string()).is_ok(), true);
+        }
+        assert_eq!(game.get_repetition_count(), 2);
+    }
+
+    #[test]
+    fn insufficient_material_test() {
+        let mut game = Game::new();
+        game.set_up_board();
+        assert_eq!(game.is_insufficient_material(), false);
+
+        // Make sure positions where no one can win are detected
+        for fen in &[
+            "4k3/8/8/8/8/8/8/4K3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/2B1K3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/1N2K3 w - - 0 1",
+            "2b1k3/8/8/8/8/8/8/3BK3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/B1B1K3 b - - 0 1",
+        ] {
+            let mut game = Game::from_fen(fen).unwrap();
+            assert_eq!(game.is_insufficient_material(), true);
+            assert_eq!(game.game_is_over(), true);
+            assert_eq!(game.get_winner() == None, true);
+            assert_eq!(game.game_status(), GameStatus::InsufficientMaterial);
+        }
+
+        // Make sure positions where a checkmate is possible are not
+        for fen in &[
+            "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/R3K3 w - - 0 1",
+            "2b1k3/8/8/8/8/8/8/2B1K3 w - - 0 1",
+            "1n2k3/8/8/8/8/8/8/1N2K3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/1NB1K3 w - - 0 1",
+        ] {
+            let mut game = Game::from_fen(fen).unwrap();
+            assert_eq!(game.is_insufficient_material(), false);
+            assert_eq!(game.game_is_over(), false);
+            assert_eq!(game.game_status(), GameStatus::Ongoing);
+        }
+
+        // Make sure capturing the last piece ends the game
+        let mut game = Game::from_fen("4k3/8/8/8/8/8/3r4/4K3 w - - 0 1").unwrap();
+        assert_eq!(game.game_is_over(), false);
+        assert_eq!(
+            game.algebraic_notation_move("Kxd2".to_string()).is_ok(),
+            true
+        );
+        assert_eq!(game.game_is_over(), true);
+        assert_eq!(
+            game.algebraic_notation_move("Ke7".to_string()).err(),
+            Some(MoveError::GameOver)
+        );
+    }
+
+    #[test]
+    fn game_status_test() {
+        let mut game = Game::new();
+        game.set_up_board();
+        assert_eq!(game.game_status(), GameStatus::Ongoing);
+        assert_eq!(game.game_status().get_result(), "*");
+
+        // Make sure checkmate has a winner
+        let mut game =
+            Game::from_fen("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3")
+                .unwrap();
+        let status = game.game_status();
+        assert_eq!(
+            status,
+            GameStatus::Checkmate {
+                winner: ChessPieceColor::Black
+            }
+        );
+        assert_eq!(status.get_result(), "0-1");
+        assert_eq!(game.get_winner(), Some(ChessPieceColor::Black));
+
+        // Make sure stalemate and the fifty move rule are told apart
+        let mut game = Game::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(game.game_status(), GameStatus::Stalemate);
+        assert_eq!(game.game_status().is_draw(), true);
+        let mut game = Game::from_fen("7k/8/6K1/8/8/8/8/R7 w - - 100 80").unwrap();
+        assert_eq!(game.game_status(), GameStatus::FiftyMove);
+        assert_eq!(game.game_status().get_result(), "1/2-1/2");
+
+        // Make sure games can be ended by the players
+        let mut game = Game::new();
+        game.set_up_board();
+        assert_eq!(game.resign(ChessPieceColor::White).is_ok(), true);
+        assert_eq!(
+            game.game_status(),
+            GameStatus::Resignation {
+                winner: ChessPieceColor::Black
+            }
+        );
+        assert_eq!(
+            game.algebraic_notation_move("e4".to_string()).is_err(),
+            true
+        );
+        assert_eq!(game.agree_draw().is_err(), true);
+
+        let mut game = Game::new();
+        game.set_up_board();
+        assert_eq!(game.algebraic_notation_move("e4".to_string()).is_ok(), true);
+        assert_eq!(game.time_out(ChessPieceColor::Black).is_ok(), true);
+        assert_eq!(game.game_status().get_result(), "1-0");
+
+        // Make sure taking back a move continues the game
+        assert_eq!(game.undo_move().is_some(), true);
+        assert_eq!(game.game_status(), GameStatus::Ongoing);
+        assert_eq!(game.agree_draw().is_ok(), true);
+        assert_eq!(game.game_status(), GameStatus::Agreement);
+    }
+
+    #[test]
+    fn move_error_test() {
+        let mut game = Game::new();
+        game.set_up_board();
+
+        // Make sure the reason a move fails can be told apart
+        let result = game.move_piece(BoardMove::new(0, 7, 0, 5), true, None);
+        assert_eq!(result, Err(MoveError::PathBlocked));
+        let result = game.move_piece(BoardMove::new(4, 6, 4, 3), true, None);
+        assert_eq!(result, Err(MoveError::InvalidPieceMove));
+        let result = game.move_piece(BoardMove::new(4, 1, 4, 3), true, None);
+        assert_eq!(result, Err(MoveError::OpponentPiece));
+        let result = game.move_piece(BoardMove::new(4, 4, 4, 3), true, None);
+        assert_eq!(result, Err(MoveError::NoPiece));
+        let result = game.move_piece(BoardMove::new(3, 7, 3, 6), true, None);
+        assert_eq!(result, Err(MoveError::OwnPieceOnTarget));
+        let result = game.move_piece(BoardMove::new(4, 6, 3, 5), true, None);
+        assert_eq!(result.err(), Some(MoveError::NothingToCapture));
+        assert_eq!(
+            game.algebraic_notation_move("e9".to_string()).err(),
+            Some(MoveError::InvalidNotation)
+        );
+        assert_eq!(
+            game.algebraic_notation_move("Qh5".to_string()).err(),
+            Some(MoveError::NoMatchingPiece)
+        );
+        assert_eq!(
+            BoardPosition::from_algebraic_notation("i9").err(),
+            Some(MoveError::InvalidNotation)
+        );
+
+        // Make sure moves into check are reported
+        let mut game = Game::from_fen("4k3/4r3/8/8/8/8/4B3/4K3 w - - 0 1").unwrap();
+        let result = game.move_piece(BoardMove::new(4, 6, 3, 5), true, None);
+        assert_eq!(result, Err(MoveError::LeavesKingInCheck));
+        let mut game = Game::from_fen("4k3/8/8/8/8/8/3r4/R3K3 w Q - 0 1").unwrap();
+        assert_eq!(
+            game.algebraic_notation_move("O-O-O".to_string()).err(),
+            Some(MoveError::CastleThroughCheck)
+        );
+
+        // Make sure promotion errors are reported
+        let mut game = Game::from_fen("7k/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let result = game.move_piece(BoardMove::new(0, 1, 0, 0), true, None);
+        assert_eq!(result, Err(MoveError::PromotionRequired));
+        assert_eq!(
+            game.algebraic_notation_move("Ke2=Q".to_string()).err(),
+            Some(MoveError::InvalidPromotion)
+        );
+
+        // Make sure errors can be shown to players
+        assert_eq!(MoveError::PathBlocked.to_string(), "Path is blocked");
+        let error: Box<dyn std::error::Error> = Box::new(MoveError::GameOver);
+        assert_eq!(error.to_string(), "The game is over");
+    }
+
+    #[test]
+    fn move_generator_test() {
+        // Get all moves by trying to move every piece to every square
+        fn get_moves_by_trying(game: &Game) -> Vec<String> {
+            let mut moves: Vec<String> = Vec::new();
+            for from_index in 0..64 {
+                for to_index in 0..64 {
+                    let board_move =
+                        BoardMove::new(from_index % 8, from_index / 8, to_index % 8, to_index / 8);
+                    let result = game.clone().move_piece(board_move, true, None);
+                    if result.is_ok() {
+                        moves.push(board_move.to_long_algebraic_notation());
+                    } else if result == Err(MoveError::PromotionRequired) {
+                        for promote_piece in &[
+                            ChessPieceId::Queen,
+                            ChessPieceId::Rook,
+                            ChessPieceId::Bishop,
+                            ChessPieceId::Knight,
+                        ] {
+                            let chess_move = ChessMove::new(board_move, Some(*promote_piece));
+                            let result =
+                                game.clone()
+                                    .move_piece(board_move, true, Some(*promote_piece));
+                            if result.is_ok() {
+                                moves.push(chess_move.to_long_algebraic_notation());
+                            }
+                        }
+                    }
+                }
+            }
+            moves.sort();
+            return moves;
+        }
+
+        fn get_generated_moves(game: &Game) -> Vec<String> {
+            let mut moves: Vec<String> = game
+                .generate_legal_moves()
+                .iter()
+                .map(|chess_move| chess_move.to_long_algebraic_notation())
+                .collect();
+            moves.sort();
+            return moves;
+        }
+
+        // Make sure the generator finds the same moves as move_piece accepts,
+        // in a few positions and in every position one move later
+        for fen in &[
+            STARTING_FEN,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "r3k2r/8/8/8/8/8/8/R2bK2R w KQkq - 0 1",
+        ] {
+            let game = Game::from_fen(fen).unwrap();
+            let generated_moves = get_generated_moves(&game);
+            assert_eq!(generated_moves, get_moves_by_trying(&game));
+            for chess_move in game.generate_legal_moves() {
+                let mut next_game = game.clone();
+                let result =
+                    next_game.move_piece(chess_move.board_move, true, chess_move.promote_piece);
+                assert_eq!(result.is_ok(), true);
+                assert_eq!(
+                    get_generated_moves(&next_game),
+                    get_moves_by_trying(&next_game)
+                );
+            }
+        }
+
+        // Make sure the number of moves is right in well known positions
+        let mut game = Game::new();
+        game.set_up_board();
+        assert_eq!(game.generate_legal_moves().len(), 20);
+        let game =
+            Game::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+        assert_eq!(game.generate_legal_moves().len(), 48);
+        let game = Game::from_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1").unwrap();
+        assert_eq!(game.generate_legal_moves().len(), 14);
+
+        // Make sure special moves are generated
+        let game = Game::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3")
+            .unwrap();
+        let moves = get_generated_moves(&game);
+        assert_eq!(moves.contains(&"e5f6".to_string()), true);
+        assert_eq!(moves.contains(&"e5d6".to_string()), false);
+        let game = Game::from_fen("n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1").unwrap();
+        let moves = get_generated_moves(&game);
+        for text in &["g2g1q", "g2g1r", "g2g1b", "g2g1n", "g2h1q"] {
+            assert_eq!(moves.contains(&text.to_string()), true);
+        }
+        let game = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        let moves = get_generated_moves(&game);
+        assert_eq!(moves.contains(&"e1g1".to_string()), true);
+        assert_eq!(moves.contains(&"e1c1".to_string()), true);
+        assert_eq!(moves.len(), 26);
+
+        // Make sure pieces between the king and rook stop casteling
+        let game = Game::from_fen("r3k2r/8/8/8/8/8/8/RN2K1NR w KQkq - 0 1").unwrap();
+        let moves = get_generated_moves(&game);
+        assert_eq!(moves.contains(&"e1g1".to_string()), false);
+        assert_eq!(moves.contains(&"e1c1".to_string()), false);
+        assert_eq!(
+            game.generate_legal_moves_from(BoardPosition::new(1, 7))
+                .len(),
+            3
+        );
+    }
+
+    #[test]
+    fn bitboard_test() {
+        use chess_engine::bitboard::*;
+
+        // Make sure attacks stop at the edge of the board and at pieces
+        let a8 = get_square(BoardPosition::new(0, 0));
+        let d4 = get_square(BoardPosition::new(3, 4));
+        assert_eq!(knight_attacks(a8).count_ones(), 2);
+        assert_eq!(knight_attacks(d4).count_ones(), 8);
+        assert_eq!(king_attacks(a8).count_ones(), 3);
+        assert_eq!(rook_attacks(d4, 0).count_ones(), 14);
+        assert_eq!(bishop_attacks(d4, 0).count_ones(), 13);
+        let blocker = get_bit(BoardPosition::new(3, 2));
+        assert_eq!(rook_attacks(d4, blocker).count_ones(), 12);
+        assert_eq!(rook_attacks(d4, blocker) & blocker, blocker);
+        assert_eq!(
+            pawn_attacks(d4, ChessPieceColor::White),
+            get_bit(BoardPosition::new(2, 3)) | get_bit(BoardPosition::new(4, 3))
+        );
+        assert_eq!(pawn_attacks(a8, ChessPieceColor::Black).count_ones(), 1);
+
+        // Make sure the bitboards follow the board
+        let mut game = Game::new();
+        game.set_up_board();
+        let bitboards = game.get_bitboards();
+        assert_eq!(bitboards.get_occupied().count_ones(), 32);
+        assert_eq!(
+            bitboards
+                .get_pieces(ChessPieceId::Pawn, ChessPieceColor::White)
+                .count_ones(),
+            8
+        );
+        assert_eq!(game.algebraic_notation_move("e4".to_string()).is_ok(), true);
+        let e4 = BoardPosition::new(4, 4);
+        assert_eq!(
+            game.get_bitboards()
+                .get_pieces(ChessPieceId::Pawn, ChessPieceColor::White)
+                & get_bit(e4),
+            get_bit(e4)
+        );
+        game.set_pos_to_none(e4);
+        assert_eq!(game.get_bitboards().get_occupied().count_ones(), 31);
+        *game.get_board_ref(e4).unwrap() =
+            Some(ChessPiece::new(ChessPieceId::Queen, ChessPieceColor::Black));
+        assert_eq!(
+            game.get_bitboards()
+                .get_pieces(ChessPieceId::Queen, ChessPieceColor::Black)
+                .count_ones(),
+            2
+        );
+        assert_eq!(
+            game.is_square_attacked(BoardPosition::new(4, 6), ChessPieceColor::Black),
+            true
+        );
+        assert_eq!(game.undo_move().is_some(), true);
+        assert_eq!(game.get_bitboards().get_occupied().count_ones(), 32);
+    }
+
+    #[test]
+    fn make_unmake_test() {
+        // Make sure make_move gives the same position as move_piece,
+        // and unmake_move gives back the position before the move
+        for fen in &[
+            STARTING_FEN,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        ] {
+            let mut game = Game::from_fen(fen).unwrap();
+            for chess_move in game.generate_legal_moves() {
+                let mut played_game = game.clone();
+                let result =
+                    played_game.move_piece(chess_move.board_move, true, chess_move.promote_piece);
+                assert_eq!(result.is_ok(), true);
+
+                let record = game.make_move(chess_move);
+                assert_eq!(game.to_fen(), played_game.to_fen());
+                assert_eq!(game.get_bitboards() == played_game.get_bitboards(), true);
+                assert_eq!(
+                    game.generate_legal_moves().len(),
+                    played_game.generate_legal_moves().len()
+                );
+                assert_eq!(game.get_history().len(), 0);
+
+                game.unmake_move(record);
+                assert_eq!(game.to_fen(), fen.to_string());
+                assert_eq!(
+                    game.get_bitboards() == Game::from_fen(fen).unwrap().get_bitboards(),
+                    true
+                );
+            }
+        }
+
+        // Make sure the record has what the move changed
+        let mut game = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        let record = game.make_move(ChessMove::new(BoardMove::new(4, 7, 6, 7), None));
+        assert_eq!(record.played_move.is_castle(), true);
+        assert_eq!(game.to_fen(), "r3k2r/8/8/8/8/8/8/R4RK1 b kq - 1 1");
+        game.unmake_move(record);
+        assert_eq!(game.to_fen(), "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        let mut game = Game::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let record = game.make_move(ChessMove::new(BoardMove::new(0, 1, 0, 0), None));
+        assert_eq!(record.played_move.promote_piece, Some(ChessPieceId::Queen));
+        assert_eq!(game.to_fen(), "Q3k3/8/8/8/8/8/8/4K3 b - - 0 1");
+    }
+
+    #[test]
+    fn hash_test() {
+        // Make sure the hash follows the position through every kind of move,
+        // and is the same as the hash of the position read from FEN
+        for fen in &[
+            STARTING_FEN,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "4k3/8/8/8/1p6/8/P1P5/4K3 w - - 0 1",
+        ] {
+            let mut game = Game::from_fen(fen).unwrap();
+            let hash = game.hash();
+            for chess_move in game.generate_legal_moves() {
+                let mut played_game = game.clone();
+                let result =
+                    played_game.move_piece(chess_move.board_move, true, chess_move.promote_piece);
+                assert_eq!(result.is_ok(), true);
+                let fen_hash = Game::from_fen(&played_game.to_fen()).unwrap().hash();
+                assert_eq!(played_game.hash(), fen_hash);
+                assert_ne!(played_game.hash(), hash);
+
+                let record = game.make_move(chess_move);
+                assert_eq!(game.hash(), fen_hash);
+                game.unmake_move(record);
+                assert_eq!(game.hash(), hash);
+                assert_eq!(played_game.undo_move().is_some(), true);
+                assert_eq!(played_game.hash(), hash);
+            }
+        }
+
+        // Make sure the same position reached in different orders has the same hash
+        let mut game1 = Game::new();
+        game1.set_up_board();
+        let mut game2 = game1.clone();
+        let start_hash = game1.hash();
+        for text in &["Nf3", "Nf6", "Ng1", "Ng8"] {
+            assert_eq!(
+                game1.algebraic_notation_move(text.to_string()).is_ok(),
+                true
+            );
+        }
+        assert_eq!(game1.hash(), start_hash);
+        for text in &["e4", "e5", "Nf3"] {
+            assert_eq!(
+                game1.algebraic_notation_move(text.to_string()).is_ok(),
+                true
+            );
+        }
+        for text in &["Nf3", "e5", "e4"] {
+            assert_eq!(
+                game2.algebraic_notation_move(text.to_string()).is_ok(),
+                true
+            );
+        }
+        assert_eq!(game1.hash(), game2.hash());
+
+        // Make sure side to move, castling rights and en passant are part of the hash
+        let hash = |fen: &str| Game::from_fen(fen).unwrap().hash();
+        assert_ne!(
+            hash("4k3/8/8/8/8/8/8/4K3 w - - 0 1"),
+            hash("4k3/8/8/8/8/8/8/4K3 b - - 0 1")
+        );
+        assert_ne!(
+            hash("r3k3/8/8/8/8/8/8/4K3 w q - 0 1"),
+            hash("r3k3/8/8/8/8/8/8/4K3 w - - 0 1")
+        );
+        assert_ne!(
+            hash("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1"),
+            hash("4k3/8/8/8/3pP3/8/8/4K3 b - - 0 1")
+        );
+        // The en passant square does not matter when no pawn can take
+        assert_eq!(
+            hash("4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1"),
+            hash("4k3/8/8/8/4P3/8/8/4K3 b - - 0 1")
+        );
+    }
+
+    #[test]
+    fn long_algebraic_notation_test() {
+        // Make sure moves are read from square names
+        let chess_move = ChessMove::from_long_algebraic_notation("e2e4").unwrap();
+        assert_eq!(chess_move.board_move == BoardMove::new(4, 6, 4, 4), true);
+        assert_eq!(chess_move.promote_piece.is_none(), true);
+        let chess_move = ChessMove::from_long_algebraic_notation("e7e8n").unwrap();
+        assert_eq!(chess_move.promote_piece == Some(ChessPieceId::Knight), true);
+        assert_eq!(chess_move.to_long_algebraic_notation(), "e7e8n");
+        assert_eq!(
+            BoardMove::from_long_algebraic_notation("a1h8").unwrap() == BoardMove::new(0, 7, 7, 0),
+            true
+        );
+
+        // Make sure invalid moves are not read
+        for text in &["", "e2", "e2e", "e2e9", "i2e4", "e2e4k", "e2e4qq", "e2é4"] {
+            assert_eq!(ChessMove::from_long_algebraic_notation(text).is_err(), true);
+        }
+        assert_eq!(
+            ChessMove::from_long_algebraic_notation("e7e8k"),
+            Err(MoveError::InvalidPromotion)
+        );
+        assert_eq!(
+            BoardMove::from_long_algebraic_notation("e7e8q").is_err(),
+            true
+        );
+    }
+
+    #[test]
+    fn chess960_test() {
+        use chess_engine::chess960;
+        use chess_engine::pgn::*;
+
+        // Number 518 is the standard starting position
+        let mut game = Game::new();
+        assert_eq!(
+            game.set_up_chess960(chess960::STANDARD_POSITION).is_ok(),
+            true
+        );
+        assert_eq!(game.is_chess960(), true);
+        assert_eq!(game.to_fen(), STARTING_FEN);
+        assert_eq!(game.set_up_chess960(0).is_ok(), true);
+        assert_eq!(
+            game.to_fen(),
+            "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1"
+        );
+        assert_eq!(
+            game.set_up_chess960(chess960::POSITION_COUNT).is_err(),
+            true
+        );
+
+        // The same seed gives the same position
+        let index = game.set_up_random_chess960(7);
+        let fen = game.to_fen();
+        assert_eq!(game.set_up_random_chess960(7), index);
+        assert_eq!(game.to_fen(), fen);
+
+        // Every back rank has the bishops on different colors and the king between the rooks
+        for index in 0..chess960::POSITION_COUNT {
+            let back_rank = chess960::get_back_rank(index).unwrap();
+            let find = |id: ChessPieceId| -> Vec<usize> {
+                return (0..8).filter(|x| back_rank[*x] == id).collect();
+            };
+            let bishops = find(ChessPieceId::Bishop);
+            let rooks = find(ChessPieceId::Rook);
+            let king = find(ChessPieceId::King);
+            assert_eq!(bishops[0] % 2 != bishops[1] % 2, true);
+            assert_eq!(rooks[0] < king[0] && king[0] < rooks[1], true);
+            assert_eq!(find(ChessPieceId::Knight).len(), 2);
+        }
+
+        // Shredder-FEN and X-FEN castling rights
+        let fen = "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9";
+        let game = Game::from_fen(fen).unwrap();
+        assert_eq!(game.is_chess960(), true);
+        assert_eq!(game.to_shredder_fen(), fen);
+        assert_eq!(
+            game.to_fen(),
+            "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w KQkq - 2 9"
+        );
+        // The file is needed when the castling rook is not the outermost rook
+        let game = Game::from_fen("4k3/8/8/8/8/8/8/R1R1K3 w C - 0 1").unwrap();
+        assert_eq!(game.to_fen(), "4k3/8/8/8/8/8/8/R1R1K3 w C - 0 1");
+        assert_eq!(game.to_shredder_fen(), "4k3/8/8/8/8/8/8/R1R1K3 w C - 0 1");
+        assert_eq!(
+            Game::from_fen("4k3/8/8/8/8/8/8/4K3 w D - 0 1").is_err(),
+            true
+        );
+        assert_eq!(
+            Game::from_fen("4k3/8/8/8/8/8/8/R3K2R w KK - 0 1").is_err(),
+            true
+        );
+        assert_eq!(Game::from_fen(STARTING_FEN).unwrap().is_chess960(), false);
+
+        // Castling is written as the king taking its own rook, here the king and rook swap squares
+        let fen = "1r3kr1/6p1/8/8/8/8/6P1/1R3KR1 w GBgb - 0 1";
+        let mut game = Game::from_fen(fen).unwrap();
+        let hash = game.hash();
+        let castle = BoardMove::new(5, 7, 6, 7);
+        assert_eq!(game.move_to_san(castle, None), "O-O+");
+        assert_eq!(game.play_move(castle, None).is_ok(), true);
+        assert_eq!(game.to_fen(), "1r3kr1/6p1/8/8/8/8/6P1/1R3RK1 b kq - 1 1");
+        assert_eq!(
+            game.get_history().last().unwrap().played_move.is_castle(),
+            true
+        );
+        assert_eq!(game.undo_move().is_some(), true);
+        assert_eq!(game.to_shredder_fen(), fen);
+        assert_eq!(game.hash(), hash);
+        assert_eq!(
+            game.algebraic_notation_move("O-O-O".to_string()).is_ok(),
+            true
+        );
+        assert_eq!(game.to_fen(), "1r3kr1/6p1/8/8/8/8/6P1/2KR2R1 b kq - 1 1");
+        assert_eq!(
+            game.algebraic_notation_move("O-O".to_string()).is_ok(),
+            true
+        );
+        assert_eq!(game.to_fen(), "1r3rk1/6p1/8/8/8/8/6P1/2KR2R1 w - - 2 2");
+
+        // The king can stay on its square, then only the rook moves
+        let mut game = Game::from_fen("1r4kr/8/8/8/8/8/8/1R4KR w HBhb - 0 1").unwrap();
+        let castle = BoardMove::new(6, 7, 7, 7);
+        assert_eq!(game.play_move(castle, None).is_ok(), true);
+        assert_eq!(game.to_fen(), "1r4kr/8/8/8/8/8/8/1R3RK1 b kq - 1 1");
+
+        // The rook and king must be able to reach their squares, and the king may not pass an attack
+        let game = Game::from_fen("1r3kr1/8/8/8/8/8/8/1RN2KR1 w GBgb - 0 1").unwrap();
+        let mut blocked = game.clone();
+        assert_eq!(
+            blocked.play_move(BoardMove::new(5, 7, 1, 7), None).is_err(),
+            true
+        );
+        let mut attacked = Game::from_fen("1r3kr1/8/8/8/8/8/8/1R2rK1R w HBgb - 0 1").unwrap();
+        assert_eq!(
+            attacked
+                .play_move(BoardMove::new(5, 7, 7, 7), None)
+                .is_err(),
+            true
+        );
+
+        // The variant tag turns on the Chess960 rules, also from the standard position
+        let mut pgn = PgnGame::new();
+        pgn.set_tag("Variant", "Chess960");
+        assert_eq!(pgn.get_start_position().unwrap().is_chess960(), true);
+        pgn.set_tag("Variant", "Standard");
+        assert_eq!(pgn.get_start_position().unwrap().is_chess960(), false);
+    }
 }
@@ -1,52 +1,154 @@
-use std::io::{self, ErrorKind, Read, Write};
-use std::net::TcpStream;
-use std::sync::mpsc::{self, TryRecvError};
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, Sender};
 use std::thread;
 use std::time::Duration;
 
-const LOCAL: &str = "127.0.0.1:6000";
-const MSG_SIZE: usize = 32;
-fn main() {
-    let mut client = TcpStream::connect(LOCAL).expect("Stream failed to connect");
-    client.set_nonblocking(true).expect("failed to initiate non-blocking");
-
-    let (tx, rx) = mpsc::channel::<String>();
-
-    thread::spawn(move || loop {
-        let mut buff = vec![0; MSG_SIZE];
-        match client.read_exact(&mut buff) {
-            Ok(_) => {
-                let msg = buff.into_iter().take_while(|&x| x != 0).collect::<Vec<_>>();
-                let return_message = String::from_utf8_lossy(&msg);
-                println!("message recv {:?}", return_message);
+use chess_engine::chess_game::{ChessMove, ChessPieceColor};
+use chess_engine::clock::TimeControl;
+use chess_engine::online::Seat;
+use chess_engine::protocol::{self, ClientMessage, ProtocolError, ServerMessage};
+
+pub(crate) const DEFAULT_ADDRESS: &str = "127.0.0.1:6000";
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const CLIENT_NAME: &str = "Schack Deluxe";
+
+/** What the thread reading from the server tells the GUI */
+pub(crate) enum ServerEvent {
+    Connected(TcpStream),
+    Message(ServerMessage),
+    Disconnected(String),
+}
+
+/** A connection to a server, it connects and reads on its own thread so the board keeps rendering */
+pub(crate) struct Client {
+    pub address: String,
+    pub seat: Option<Seat>,    // None until the server has given us a seat
+    pub queued: bool,          // waiting for the server to find an opponent
+    stream: Option<TcpStream>, // None until the handshake is done
+    rx: Receiver<ServerEvent>,
+}
+
+impl Client {
+    /** Start connecting to "host:port", the result is picked up with poll_event */
+    pub fn connect(address: &str) -> Self {
+        let (tx, rx) = mpsc::channel::<ServerEvent>();
+        let thread_address = address.to_string();
+        thread::spawn(move || read_server(thread_address, tx));
+        return Self {
+            address: address.to_string(),
+            seat: None,
+            queued: false,
+            stream: None,
+            rx,
+        };
+    }
+
+    pub fn is_connected(&self) -> bool {
+        return self.stream.is_some();
+    }
+
+    /** The color the user plays, None while connecting and for spectators */
+    pub fn get_color(&self) -> Option<ChessPieceColor> {
+        return self.seat.and_then(|seat| seat.get_color());
+    }
+
+    /** Get the next event from the server without waiting */
+    pub fn poll_event(&mut self) -> Option<ServerEvent> {
+        let mut event = self.rx.try_recv().ok()?;
+        match &event {
+            ServerEvent::Connected(stream) => match stream.try_clone() {
+                Ok(stream) => self.stream = Some(stream),
+                Err(error) => {
+                    // without a stream to write to, the connection is of no use
+                    let _shutdown_error = stream.shutdown(Shutdown::Both);
+                    event = ServerEvent::Disconnected(error.to_string());
+                }
             },
-            Err(ref err) if err.kind() == ErrorKind::WouldBlock => (),
-            Err(_) => {
-                println!("connection with server was severed");
-                break;
+            ServerEvent::Message(ServerMessage::Seated { seat, .. }) => {
+                self.seat = Some(*seat);
+                self.queued = false;
             }
+            ServerEvent::Message(ServerMessage::Queued) => self.queued = true,
+            ServerEvent::Disconnected(_) => self.stream = None,
+            _ => {}
         }
+        return Some(event);
+    }
+
+    pub fn send(&mut self, message: &ClientMessage) -> Result<(), ProtocolError> {
+        let stream = match self.stream.as_mut() {
+            Some(stream) => stream,
+            None => return Err(ProtocolError::Closed),
+        };
+        return protocol::send_client_message(stream, message);
+    }
 
-        match rx.try_recv() {
-            Ok(msg) => {
-                let mut buff = msg.clone().into_bytes();
-                buff.resize(MSG_SIZE, 0);
-                client.write_all(&buff).expect("writing to socket failed");
-                println!("message sent {:?}", msg);
-            }, 
-            Err(TryRecvError::Empty) => (),
-            Err(TryRecvError::Disconnected) => break
+    /** Send a move to the server, it is played when the server sends it back */
+    pub fn send_move(&mut self, chess_move: ChessMove) -> Result<(), ProtocolError> {
+        return self.send(&ClientMessage::Move { chess_move });
+    }
+
+    /** Ask the server for an opponent that wants the same time control */
+    pub fn find_game(&mut self, time_control: Option<TimeControl>) -> Result<(), ProtocolError> {
+        return self.send(&ClientMessage::FindGame { time_control });
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        // the reading thread stops when the connection is closed
+        if let Some(stream) = self.stream.as_ref() {
+            let _shutdown_error = stream.shutdown(Shutdown::Both);
         }
+    }
+}
+
+fn connect_to(address: &str) -> Result<TcpStream, String> {
+    let socket_address = match address.to_socket_addrs() {
+        Ok(mut addresses) => addresses.next(),
+        Err(error) => return Err(error.to_string()),
+    };
+    let socket_address = match socket_address {
+        Some(socket_address) => socket_address,
+        None => return Err(format!("{} is not an address", address)),
+    };
+    let mut stream =
+        TcpStream::connect_timeout(&socket_address, CONNECT_TIMEOUT).map_err(|e| e.to_string())?;
+    protocol::client_handshake(&mut stream, CLIENT_NAME).map_err(|e| e.to_string())?;
+    return Ok(stream);
+}
 
-        thread::sleep(Duration::from_millis(100));
-    });
+fn read_server(address: String, tx: Sender<ServerEvent>) {
+    let mut stream = match connect_to(&address) {
+        Ok(stream) => stream,
+        Err(error) => {
+            let _send_error = tx.send(ServerEvent::Disconnected(error));
+            return;
+        }
+    };
+    let writer = match stream.try_clone() {
+        Ok(writer) => writer,
+        Err(error) => {
+            let _send_error = tx.send(ServerEvent::Disconnected(error.to_string()));
+            return;
+        }
+    };
+    if tx.send(ServerEvent::Connected(writer)).is_err() {
+        return;
+    }
 
-    println!("Write a Message:");
     loop {
-        let mut buff = String::new();
-        io::stdin().read_line(&mut buff).expect("reading from stdin failed");
-        let msg = buff.trim().to_string();
-        if msg == ":quit" || tx.send(msg).is_err() {break}
+        match protocol::read_server_message(&mut stream) {
+            Ok(message) => {
+                if tx.send(ServerEvent::Message(message)).is_err() {
+                    // the GUI has dropped the client
+                    break;
+                }
+            }
+            Err(error) => {
+                let _send_error = tx.send(ServerEvent::Disconnected(error.to_string()));
+                break;
+            }
+        }
     }
-    println!("bye bye!");
 }
@@ -0,0 +1,137 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use chess_engine::chess_game::{ChessMove, ChessPieceColor, Game};
+use chess_engine::search::{SearchLimits, Searcher};
+
+const HASH_MB: usize = 16;
+const CLOCK_TIME_DIVISOR: u32 = 30; // the part of the time left used for a move
+
+pub(crate) struct Strength {
+    pub name: &'static str,
+    pub depth: u32,
+    pub time: Duration,
+}
+
+/** The strengths to choose from, the search stops at whichever limit comes first */
+pub(crate) const STRENGTHS: [Strength; 4] = [
+    Strength {
+        name: "Lätt",
+        depth: 1,
+        time: Duration::from_millis(200),
+    },
+    Strength {
+        name: "Medel",
+        depth: 3,
+        time: Duration::from_millis(500),
+    },
+    Strength {
+        name: "Svår",
+        depth: 6,
+        time: Duration::from_millis(1500),
+    },
+    Strength {
+        name: "Max",
+        depth: 64,
+        time: Duration::from_millis(4000),
+    },
+];
+
+/** The engine playing against the user, it searches on its own thread so the board keeps rendering */
+pub(crate) struct Computer {
+    pub color: ChessPieceColor,
+    pub strength: usize, // index in STRENGTHS
+    searcher: Option<Searcher>, // None while it is thinking
+    result_rx: Option<Receiver<(Searcher, Option<ChessMove>)>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl Computer {
+    pub fn new(color: ChessPieceColor, strength: usize) -> Self {
+        return Self {
+            color,
+            strength: strength.min(STRENGTHS.len() - 1),
+            searcher: Some(Searcher::new(HASH_MB)),
+            result_rx: None,
+            stop: Arc::new(AtomicBool::new(false)),
+        };
+    }
+
+    pub fn is_thinking(&self) -> bool {
+        return self.result_rx.is_some();
+    }
+
+    /** Start searching for a move in the position, the move is picked up with poll_move.
+    With a clock it uses at most a part of the time left */
+    pub fn start_thinking(&mut self, game: &Game, time_left: Option<Duration>) {
+        if self.is_thinking() {
+            return;
+        }
+        let strength = &STRENGTHS[self.strength];
+        let time = match time_left {
+            Some(time_left) => strength.time.min(time_left / CLOCK_TIME_DIVISOR),
+            None => strength.time,
+        };
+        self.stop = Arc::new(AtomicBool::new(false));
+        let limits = SearchLimits {
+            depth: Some(strength.depth),
+            time: Some(time),
+            stop: Some(self.stop.clone()),
+            ..SearchLimits::default()
+        };
+
+        let (tx, rx) = mpsc::channel();
+        let mut searcher = self.searcher.take().unwrap();
+        let game = game.clone();
+        thread::spawn(move || {
+            let result = searcher.search(&game, &limits);
+            let _send_error = tx.send((searcher, result.best_move));
+        });
+        self.result_rx = Some(rx);
+    }
+
+    /** Get the move if the search is done, None while it is still thinking.
+    Fails if the search stopped without a move, ex. if its thread panicked */
+    pub fn poll_move(&mut self) -> Result<Option<ChessMove>, String> {
+        let result_rx = match self.result_rx.as_ref() {
+            Some(result_rx) => result_rx,
+            None => return Ok(None),
+        };
+        match result_rx.try_recv() {
+            Ok((searcher, best_move)) => {
+                self.searcher = Some(searcher);
+                self.result_rx = None;
+                return Ok(best_move);
+            }
+            Err(TryRecvError::Empty) => return Ok(None),
+            Err(TryRecvError::Disconnected) => {
+                // the searcher was lost with the thread, start over with a new one
+                self.searcher = Some(Searcher::new(HASH_MB));
+                self.result_rx = None;
+                return Err("the search stopped without a move".to_string());
+            }
+        }
+    }
+
+    /** Stop thinking and throw away the move, ex. when the game is restarted or a move is taken back */
+    pub fn stop_thinking(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(result_rx) = self.result_rx.take() {
+            // waits for the search to see the stop flag, which is quick
+            let searcher = match result_rx.recv() {
+                Ok((searcher, _)) => searcher,
+                Err(_) => Searcher::new(HASH_MB),
+            };
+            self.searcher = Some(searcher);
+        }
+    }
+}
+
+impl Drop for Computer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
@@ -1,22 +1,42 @@
 //! The simplest possible example that does something.
 #![allow(clippy::unnecessary_wraps)]
 
-mod chess_server;
+mod chess_client;
+mod computer;
 mod render;
 
 use std::process::exit;
 use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::Instant;
 use std::{env, path};
 
-use chess_engine::chess_game::{BoardMove, BoardPosition, ChessPieceColor, ChessPieceId, Game};
+use chess_client::{Client, ServerEvent};
+use chess_engine::chess_game::{
+    BoardMove, BoardPosition, ChessMove, ChessPieceColor, ChessPieceId, Game, GameStatus,
+};
+use chess_engine::clock::{Clock, TimeControl};
+use chess_engine::online::Seat;
+use chess_engine::protocol::ServerMessage;
+use computer::Computer;
 use ggez::event;
-use ggez::event::MouseButton;
+use ggez::event::{KeyCode, KeyMods, MouseButton};
 use ggez::graphics::{self, Font, PxScale};
 use ggez::{Context, GameResult};
 
 use glam::Vec2;
 use render::*;
 
+const MAX_ADDRESS_LENGTH: usize = 64;
+
+/** The time controls to choose from, written as in the PGN TimeControl tag */
+pub(crate) const TIME_CONTROLS: [(&str, Option<&str>); 5] = [
+    ("Ingen", None),
+    ("3+2", Some("180+2")),
+    ("10+0", Some("600")),
+    ("15+10", Some("900+10")),
+    ("40/90+30", Some("40/5400+30:1800+30")),
+];
+
 struct SpriteSheet {
     pawn_white: graphics::Image,
     pawn_black: graphics::Image,
@@ -50,15 +70,20 @@ struct Icons {
     //surrender: graphics::Image,
     replay: graphics::Image,
     settings: graphics::Image,
-    //arrow_back: graphics::Image,
+    arrow_back: graphics::Image,
     exit: graphics::Image,
     confirm: graphics::Image,
 }
 
 #[derive(Clone, Copy)]
 enum Action {
-    StartServer,
     StartClient,
+    Connect,
+    CancelConnect,
+    PlayTwoPlayers,
+    PlayComputer(ChessPieceColor), // the color the user plays
+    SetStrength(usize),
+    SetTimeControl(usize),
     Restart,
     Quit,
     None,
@@ -94,9 +119,15 @@ struct InputStatus {
 
 pub struct MainState {
     frame: u64,
-    server: chess_server::Server,
     render_config: RenderConfig,
     active_message: Option<PendingAction>,
+    show_mode_selection: bool,
+    strength: usize, // index in computer::STRENGTHS, used for the next game against the computer
+    computer: Option<Computer>,
+    time_control: usize, // index in TIME_CONTROLS, used for the next game
+    clock: Option<Clock>,
+    connect_address: Option<String>, // the address written in the connect dialog, None when it is closed
+    client: Option<Client>,
     active_game: ActiveGame,
     input_staus: InputStatus,
 }
@@ -165,7 +196,7 @@ impl MainState {
             //surrender: addpng!(ctx, "surrender"),
             replay: add_png!(ctx, "replay"),
             settings: add_png!(ctx, "settings"),
-            //arrow_back: addpng!(ctx, "arrow_back"),
+            arrow_back: add_png!(ctx, "arrow_back"),
             exit: add_png!(ctx, "exit"),
             confirm: add_png!(ctx, "confirm"),
         };
@@ -173,16 +204,7 @@ impl MainState {
         let mut game = chess_engine::chess_game::Game::new();
         game.set_up_board();
 
-        let message = Some(PendingAction {
-            text: "Sluta spela?".to_string(),
-            confirm: icons.confirm.clone(),
-            cancel: icons.exit.clone(),
-            confirm_value: Action::StartServer,
-            cancel_value: Action::None,
-        });
-
         let s = MainState {
-            server: chess_server::start_server(),
             frame: 0,
             render_config: RenderConfig {
                 spritesets: vec![regular_sprites, horsey_sprites, emoji_sprites],
@@ -204,36 +226,295 @@ impl MainState {
                 mouse_clicked: false,
                 mouse_released: false,
             },
-            active_message: message,
+            active_message: None,
+            show_mode_selection: true,
+            strength: 1,
+            computer: None,
+            time_control: 0,
+            clock: None,
+            connect_address: None,
+            client: None,
         };
         Ok(s)
     }
 }
 
-fn get_possible_moves_from_position(game: Game, pos: BoardPosition) -> Vec<BoardPosition> {
+fn get_possible_moves_from_position(game: &Game, pos: BoardPosition) -> Vec<BoardPosition> {
     let mut possible_moves: Vec<BoardPosition> = Vec::new();
 
-    for y in 0..8 {
-        for x in 0..8 {
-            let board_move;
-            board_move = BoardMove::new(pos.x, pos.y, x, y);
+    for chess_move in game.generate_legal_moves_from(pos) {
+        // A pawn that promotes has one move per piece, but it is the same square
+        if !possible_moves.contains(&chess_move.board_move.to_pos) {
+            possible_moves.push(chess_move.board_move.to_pos);
+        }
+    }
 
-            let mut board_copy = game.clone();
-            if board_copy
-                .move_piece(board_move, true, Some(ChessPieceId::Queen))
-                .is_ok()
-            {
-                // Color square red if piece can move there
-                possible_moves.push(BoardPosition::new(x, y))
+    return possible_moves;
+}
+
+/** Describe how a game ended, shown in the popup asking to play again */
+fn get_game_over_text(status: GameStatus) -> String {
+    let winner = match status.get_winner() {
+        Some(ChessPieceColor::White) => "Vit vann",
+        Some(ChessPieceColor::Black) => "Svart vann",
+        None => "Oavgjort",
+    };
+    let reason = match status {
+        GameStatus::Checkmate { .. } => "schackmatt",
+        GameStatus::Resignation { .. } => "uppgivet",
+        GameStatus::Timeout { .. } => "tiden tog slut",
+        GameStatus::TimeoutVsInsufficientMaterial => "tiden tog slut, för lite material",
+        GameStatus::Stalemate => "patt",
+        GameStatus::FiftyMove => "50 drag",
+        GameStatus::Repetition => "upprepning",
+        GameStatus::InsufficientMaterial => "för lite material",
+        GameStatus::Agreement => "överenskommelse",
+        GameStatus::Ongoing => return "Spelet pågår".to_string(),
+    };
+    return format!("{} ({})", winner, reason);
+}
+
+/** Show the popup asking to play again if the game is over */
+fn show_game_over_message(main_state: &mut MainState) {
+    let status = main_state.active_game.game.game_status();
+    if status.is_over() {
+        if let Some(clock) = main_state.clock.as_mut() {
+            clock.stop(Instant::now());
+        }
+
+        // updates the popup message asking to play again
+        let text = format!("{}, Spela igen?", get_game_over_text(status));
+
+        main_state.input_staus.mouse_released = false;
+        main_state.active_message = Some(PendingAction {
+            text,
+            confirm: main_state.render_config.icons.confirm.clone(),
+            cancel: main_state.render_config.icons.exit.clone(),
+            confirm_value: Action::Restart,
+            cancel_value: Action::None,
+        })
+    }
+}
+
+/** Returns true if it is the computers turn, the user can not move then */
+fn is_computer_turn(main_state: &MainState) -> bool {
+    return match &main_state.computer {
+        Some(computer) => computer.color == main_state.active_game.game.turn,
+        None => false,
+    };
+}
+
+/** Returns true if the user plays online and it is not their turn, spectators can never move */
+fn is_opponent_turn(main_state: &MainState) -> bool {
+    return match &main_state.client {
+        Some(client) => client.get_color() != Some(main_state.active_game.game.turn),
+        None => false,
+    };
+}
+
+/** Press the clock for the player that just moved */
+fn press_clock(main_state: &mut MainState) {
+    let moved_color = if main_state.active_game.game.turn == ChessPieceColor::White {
+        ChessPieceColor::Black
+    } else {
+        ChessPieceColor::White
+    };
+    if let Some(clock) = main_state.clock.as_mut() {
+        let _clock_error = clock.press(moved_color, Instant::now());
+    }
+}
+
+/** End the game when the player to move runs out of time, online the server does it */
+fn do_clock_logic(main_state: &mut MainState) {
+    if main_state.client.is_some() {
+        return;
+    }
+    let clock = match main_state.clock.as_mut() {
+        Some(clock) => clock,
+        None => return,
+    };
+    if clock.check_timeout(&mut main_state.active_game.game, Instant::now()) {
+        if let Some(computer) = main_state.computer.as_mut() {
+            computer.stop_thinking();
+        }
+        show_game_over_message(main_state);
+    }
+}
+
+/** Let the computer think when it is its turn, and play its move when it is done */
+fn do_computer_logic(main_state: &mut MainState) {
+    let computer = match main_state.computer.as_mut() {
+        Some(computer) => computer,
+        None => return,
+    };
+    let game = &mut main_state.active_game.game;
+
+    if computer.is_thinking() {
+        let best_move = match computer.poll_move() {
+            Ok(Some(best_move)) => best_move,
+            Ok(None) => return,
+            Err(error) => {
+                // play on without the computer instead of waiting for it forever
+                println!("The computer could not find a move: {}", error);
+                main_state.computer = None;
+                show_error_message(main_state, "Datorn kunde inte hitta ett drag");
+                return;
             }
+        };
+        let result = game.play_move(best_move.board_move, best_move.promote_piece);
+        if let Err(error) = result {
+            // the computer would search for the same move again, so play on without it
+            println!(
+                "The computer played an invalid move {}: {}",
+                best_move.to_long_algebraic_notation(),
+                error
+            );
+            main_state.computer = None;
+            show_error_message(main_state, "Datorn spelade ett ogiltigt drag");
+            return;
         }
+        press_clock(main_state);
+        show_game_over_message(main_state);
+    } else if main_state.active_message.is_none()
+        && !main_state.show_mode_selection
+        && computer.color == game.turn
+        && !game.game_status().is_over()
+    {
+        // the computer uses less time when its clock is running low
+        let time_left = main_state
+            .clock
+            .as_ref()
+            .map(|clock| clock.get_remaining(computer.color, Instant::now()));
+        computer.start_thinking(game, time_left);
     }
+}
 
-    return possible_moves;
+/** Show a popup when something went wrong, ex. the connection to the server is lost */
+fn show_error_message(main_state: &mut MainState, text: &str) {
+    main_state.input_staus.mouse_released = false;
+    main_state.active_message = Some(PendingAction {
+        text: format!("{}, Spela igen?", text),
+        confirm: main_state.render_config.icons.confirm.clone(),
+        cancel: main_state.render_config.icons.exit.clone(),
+        confirm_value: Action::Restart,
+        cancel_value: Action::None,
+    })
+}
+
+/** Use the position from the server, the moves played before it are lost */
+fn set_position(main_state: &mut MainState, fen: &str) {
+    match Game::from_fen(fen) {
+        Ok(game) => {
+            main_state.active_game.game = game;
+            main_state.active_game.selected_square = None;
+            main_state.active_game.possible_moves = None;
+        }
+        Err(error) => println!("The server sent an invalid position: {}", error),
+    }
+}
+
+/** Apply what the server sends, the server decides which moves are played */
+fn do_network_logic(main_state: &mut MainState) {
+    let client = match main_state.client.as_mut() {
+        Some(client) => client,
+        None => return,
+    };
+    let event = match client.poll_event() {
+        Some(event) => event,
+        None => return,
+    };
+
+    match event {
+        ServerEvent::Connected(_) => {
+            // play the first client that wants the same time control
+            let time_control = TIME_CONTROLS[main_state.time_control]
+                .1
+                .map(|text| TimeControl::parse(text).unwrap());
+            if let Err(error) = client.find_game(time_control) {
+                println!("Could not look for a game: {}", error);
+            }
+        }
+        ServerEvent::Disconnected(error) => {
+            println!("{}", error);
+            // the client waits in the queue or is seated soon after it has connected
+            let text = if client.seat.is_some() || client.queued {
+                "Anslutningen bröts"
+            } else {
+                "Kunde inte ansluta"
+            };
+            main_state.client = None;
+            show_error_message(main_state, text);
+        }
+        ServerEvent::Message(message) => match message {
+            ServerMessage::Seated {
+                game_id,
+                seat,
+                time_control,
+            } => {
+                println!("Seated in game {} as {:?}", game_id, seat);
+                main_state.clock = time_control.map(Clock::new);
+            }
+            ServerMessage::Clock {
+                white,
+                black,
+                running,
+            } => {
+                // show the time of the clock on the server
+                let now = Instant::now();
+                let turn = main_state.active_game.game.turn;
+                if let Some(clock) = main_state.clock.as_mut() {
+                    clock.set_turn(turn, now);
+                    clock.set_remaining(ChessPieceColor::White, white, now);
+                    clock.set_remaining(ChessPieceColor::Black, black, now);
+                    if running {
+                        let _clock_error = clock.start(now);
+                    } else {
+                        clock.stop(now);
+                    }
+                }
+            }
+            ServerMessage::TimeUp { color } => {
+                let _game_over_error = main_state.active_game.game.time_out(color);
+                show_game_over_message(main_state);
+            }
+            ServerMessage::PlayerLeft { seat } => {
+                // a player that leaves gives up the game, as it does on the server
+                println!("{:?} left the game", seat);
+                if let Some(color) = seat.get_color() {
+                    let _game_over_error = main_state.active_game.game.resign(color);
+                    show_game_over_message(main_state);
+                }
+            }
+            ServerMessage::Position { fen } => set_position(main_state, &fen),
+            ServerMessage::Moved { chess_move, fen, .. } => {
+                let game = &mut main_state.active_game.game;
+                let result = game.play_move(chess_move.board_move, chess_move.promote_piece);
+                // keep the board in sync if the moves played here and on the server differ
+                if result.is_err() || game.to_fen() != fen {
+                    set_position(main_state, &fen);
+                }
+                main_state.active_game.selected_square = None;
+                main_state.active_game.possible_moves = None;
+                show_game_over_message(main_state);
+            }
+            ServerMessage::MoveRejected { rejection } => {
+                println!("The server rejected the move: {}", rejection);
+            }
+            ServerMessage::Chat { name, text } => println!("{}: {}", name, text),
+            ServerMessage::LobbyError { error } => println!("{}", error),
+            ServerMessage::Welcome { .. }
+            | ServerMessage::Rejected { .. }
+            | ServerMessage::Queued
+            | ServerMessage::GameList { .. } => {}
+        },
+    }
 }
 
 /** Handle user input logic to move pieces */
 fn do_game_logic(main_state: &mut MainState) {
+    if is_computer_turn(main_state) || is_opponent_turn(main_state) {
+        return;
+    }
     let input = &main_state.input_staus;
     let state = &mut main_state.active_game;
 
@@ -252,7 +533,7 @@ fn do_game_logic(main_state: &mut MainState) {
             {
                 state.selected_square = selected_square;
                 state.possible_moves = Some(get_possible_moves_from_position(
-                    state.game,
+                    &state.game,
                     selected_square.unwrap(),
                 ));
             } else {
@@ -276,34 +557,27 @@ fn do_game_logic(main_state: &mut MainState) {
                 let move_to = move_square.unwrap();
                 let move_from = state.selected_square.unwrap();
 
-                let result = state.game.move_piece(
-                    BoardMove::new(move_from.x, move_from.y, move_to.x, move_to.y),
-                    false,
-                    Some(ChessPieceId::Queen),
-                );
+                let board_move = BoardMove::new(move_from.x, move_from.y, move_to.x, move_to.y);
+
+                // online the move is played when the server sends it back
+                if let Some(client) = main_state.client.as_mut() {
+                    let chess_move = ChessMove::new(board_move, Some(ChessPieceId::Queen));
+                    if let Err(error) = client.send_move(chess_move) {
+                        println!("Could not send the move: {}", error);
+                    }
+                    return;
+                }
+
+                let result = state
+                    .game
+                    .move_piece(board_move, false, Some(ChessPieceId::Queen));
 
                 if result.is_err() {
                     // this should be impossible to achive
                     println!("Bruh how?")
                 } else {
-                    if state.game.game_is_over() {
-                        // updates the popup message asking to play again
-                        let winner = state.game.get_winner();
-                        let text = match winner {
-                            None => "Oavgjort, Spela igen?".to_string(),
-                            Some(ChessPieceColor::White) => "Vit vann, Spela igen?".to_string(),
-                            Some(ChessPieceColor::Black) => "Svart vann, Spela igen?".to_string(),
-                        };
-
-                        main_state.input_staus.mouse_released = false;
-                        main_state.active_message = Some(PendingAction {
-                            text,
-                            confirm: main_state.render_config.icons.confirm.clone(),
-                            cancel: main_state.render_config.icons.exit.clone(),
-                            confirm_value: Action::Restart,
-                            cancel_value: Action::None,
-                        })
-                    }
+                    press_clock(main_state);
+                    show_game_over_message(main_state);
                 }
             }
         } else {
@@ -313,33 +587,99 @@ fn do_game_logic(main_state: &mut MainState) {
     }
 }
 
-fn network_callback(input: String) -> String {
-    println!("GOT::: {}", input);
-    return "hello from server".to_string();
+/** Tell the user who plays what online, shown under the board */
+fn get_network_status_text(client: &Client, turn: ChessPieceColor) -> String {
+    if !client.is_connected() {
+        return format!("Ansluter till {}", client.address);
+    }
+    if client.queued {
+        return "Söker motståndare".to_string();
+    }
+    return match client.seat {
+        None => "Väntar på en plats".to_string(),
+        Some(Seat::Spectator) => "Du tittar på".to_string(),
+        Some(seat) => {
+            let color = if seat == Seat::White { "vit" } else { "svart" };
+            let turn_text = if seat.get_color() == Some(turn) {
+                "ditt drag"
+            } else {
+                "motståndarens drag"
+            };
+            format!("Du spelar {}, {}", color, turn_text)
+        }
+    };
+}
+
+/** Start a new game from the starting position */
+fn reset_game(state: &mut MainState) {
+    let mut game = chess_engine::chess_game::Game::new();
+    game.set_up_board();
+    state.active_game.game = game;
+    state.active_game.hover_position = None;
+    state.active_game.possible_moves = None;
+    state.active_game.selected_square = None;
+
+    state.clock = None;
+    if let Some(text) = TIME_CONTROLS[state.time_control].1 {
+        let mut clock = Clock::new(TimeControl::parse(text).unwrap());
+        let _clock_error = clock.start(Instant::now());
+        state.clock = Some(clock);
+    }
 }
 
 /** Handle action triggered by a popup */
 fn handle_action(action: Action, state: &mut MainState) {
-    
-
     match action {
-        Action::StartClient => {}
-        Action::StartServer => {
-            
-            /*thread::spawn(|| {
-                let server_result = chess_server::start(state, network_callback);
-                if server_result.is_err() {
-                    println!("Server Error");
-                }
-            })*/
+        Action::StartClient => {
+            // ask for the address of the server, the mode selection is shown again if cancelled
+            let address = match &state.client {
+                Some(client) => client.address.clone(),
+                None => chess_client::DEFAULT_ADDRESS.to_string(),
+            };
+            state.connect_address = Some(address);
         }
+        Action::Connect => {
+            let address = match state.connect_address.take() {
+                Some(address) => address,
+                None => return,
+            };
+            state.computer = None;
+            state.show_mode_selection = false;
+            reset_game(state);
+            // the server keeps the time, the clock is set up when the server has found a game
+            state.clock = None;
+            state.client = Some(Client::connect(address.trim()));
+        }
+        Action::CancelConnect => state.connect_address = None,
+        Action::PlayTwoPlayers => {
+            state.client = None;
+            state.computer = None;
+            state.show_mode_selection = false;
+            reset_game(state);
+        }
+        Action::PlayComputer(color) => {
+            let computer_color = if color == ChessPieceColor::White {
+                ChessPieceColor::Black
+            } else {
+                ChessPieceColor::White
+            };
+            state.client = None;
+            state.computer = Some(Computer::new(computer_color, state.strength));
+            state.show_mode_selection = false;
+            reset_game(state);
+        }
+        Action::SetStrength(strength) => state.strength = strength,
+        Action::SetTimeControl(time_control) => state.time_control = time_control,
         Action::Restart => {
-            let mut game = chess_engine::chess_game::Game::new();
-            game.set_up_board();
-            state.active_game.game = game;
-            state.active_game.hover_position = None;
-            state.active_game.possible_moves = None;
-            state.active_game.selected_square = None;
+            // choose the mode again, the new game starts when a mode is chosen
+            if let Some(computer) = state.computer.as_mut() {
+                computer.stop_thinking();
+            }
+            if let Some(clock) = state.clock.as_mut() {
+                clock.stop(Instant::now());
+            }
+            state.client = None;
+            state.show_mode_selection = true;
         }
         Action::Quit => exit(0),
         Action::None => {}
@@ -348,14 +688,15 @@ fn handle_action(action: Action, state: &mut MainState) {
 
 impl event::EventHandler<ggez::GameError> for MainState {
     fn update(&mut self, _ctx: &mut Context) -> GameResult {
+        do_clock_logic(self);
+        do_computer_logic(self);
+        do_network_logic(self);
         Ok(())
     }
 
     fn draw(&mut self, ctx: &mut Context) -> GameResult {
-        chess_server::server_loop(&mut self.server, &mut self.active_game.game);
-
         // cant move anything while a popup is active, game is paused
-        if self.active_message.is_none() {
+        if self.active_message.is_none() && !self.show_mode_selection {
             do_game_logic(self);
         }
 
@@ -371,7 +712,29 @@ impl event::EventHandler<ggez::GameError> for MainState {
         }
 
         render_pieces(ctx, &self.render_config, &mut self.active_game)?;
-        if self.active_message.is_none() {
+        if self.computer.is_some() && self.computer.as_ref().unwrap().is_thinking() {
+            render_thinking(ctx, &self.render_config, self.frame)?;
+        }
+        if self.clock.is_some() {
+            render_clocks(ctx, &self.render_config, self.clock.as_ref().unwrap())?;
+        }
+        if let Some(client) = self.client.as_ref() {
+            let text = get_network_status_text(client, self.active_game.game.turn);
+            render_status(ctx, &self.render_config, &text)?;
+        }
+
+        if self.connect_address.is_some() {
+            let action = render_connect_dialog(ctx, self)?;
+            if self.input_staus.mouse_released {
+                handle_action(action, self);
+            }
+        } else if self.show_mode_selection {
+            // the mode selection stays until a mode is chosen
+            let action = render_mode_selection(ctx, self)?;
+            if self.input_staus.mouse_released {
+                handle_action(action, self);
+            }
+        } else if self.active_message.is_none() {
             let selected_button = render_buttons(ctx, self);
 
             // handle main input buttons
@@ -403,6 +766,24 @@ impl event::EventHandler<ggez::GameError> for MainState {
                             self.render_config.active_sprites_index = 0
                         }
                     }
+                    // the server keeps the game online, so moves can not be taken back
+                    3 if self.client.is_none() => {
+                        // take back the last move
+                        if let Some(computer) = self.computer.as_mut() {
+                            computer.stop_thinking();
+                        }
+                        if self.active_game.game.undo_move().is_some() {
+                            // take back the computers move too, so it is the users turn again
+                            if is_computer_turn(self) {
+                                self.active_game.game.undo_move();
+                            }
+                            if let Some(clock) = self.clock.as_mut() {
+                                clock.set_turn(self.active_game.game.turn, Instant::now());
+                            }
+                            self.active_game.selected_square = None;
+                            self.active_game.possible_moves = None;
+                        }
+                    }
                     _ => {}
                 }
             }
@@ -448,6 +829,38 @@ impl event::EventHandler<ggez::GameError> for MainState {
         self.input_staus.mouse_released = true;
     }
 
+    fn text_input_event(&mut self, _ctx: &mut Context, character: char) {
+        if let Some(address) = self.connect_address.as_mut() {
+            if !character.is_control() && address.len() < MAX_ADDRESS_LENGTH {
+                address.push(character);
+            }
+        }
+    }
+
+    fn key_down_event(
+        &mut self,
+        ctx: &mut Context,
+        keycode: KeyCode,
+        _keymods: KeyMods,
+        _repeat: bool,
+    ) {
+        if self.connect_address.is_none() {
+            // the default of ggez, quit on escape
+            if keycode == KeyCode::Escape {
+                event::quit(ctx);
+            }
+            return;
+        }
+        match keycode {
+            KeyCode::Back => {
+                self.connect_address.as_mut().unwrap().pop();
+            }
+            KeyCode::Return | KeyCode::NumpadEnter => handle_action(Action::Connect, self),
+            KeyCode::Escape => handle_action(Action::CancelConnect, self),
+            _ => {}
+        }
+    }
+
     fn mouse_motion_event(&mut self, _ctx: &mut Context, x: f32, y: f32, _xrel: f32, _yrel: f32) {
         self.input_staus.pos_x = x;
         self.input_staus.pos_y = y;
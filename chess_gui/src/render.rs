@@ -1,9 +1,13 @@
+use std::time::Instant;
+
 use chess_engine::chess_game::{BoardPosition, ChessPiece, ChessPieceColor, ChessPieceId};
+use chess_engine::clock::{format_time, Clock};
 use ggez::graphics::{self, Color, Rect};
 use ggez::{Context, GameError, GameResult};
 use glam::*;
 
-use crate::{Action, ActiveGame, MainState, RenderConfig, SpriteSheet};
+use crate::computer::STRENGTHS;
+use crate::{Action, ActiveGame, MainState, RenderConfig, SpriteSheet, TIME_CONTROLS};
 
 pub(crate) const SCREEN_SIZE: (f32, f32) = (840f32, 840f32);
 
@@ -264,6 +268,269 @@ pub(crate) fn render_message(ctx: &mut Context, state: &MainState) -> Result<Act
     Ok(Action::None)
 }
 
+/** Renders a rounded square with a text, returns true if the mouse is over it */
+fn render_text_button(
+    ctx: &mut Context,
+    state: &MainState,
+    pos: Vec2,
+    size: Vec2,
+    text: &str,
+    color: Color,
+) -> GameResult<bool> {
+    let is_hovering = is_inside_square(
+        state.input_staus.pos_x,
+        state.input_staus.pos_y,
+        pos,
+        size,
+    );
+    let mut color = color;
+    if is_hovering {
+        color.a = 0.6;
+    }
+    render_round_rect(ctx, pos, size, color)?;
+
+    let active_font = &state.render_config.fontsets[state.render_config.active_fontset_index];
+    let mut text = graphics::Text::new(text);
+    text.set_font(active_font.font, active_font.font_size);
+    graphics::draw(
+        ctx,
+        &text,
+        graphics::DrawParam::new()
+            .dest(pos + size / 2.0)
+            .offset(Vec2::new(0.5, 0.5)),
+    )?;
+    Ok(is_hovering)
+}
+
+/** Renders the popup where the user chooses to play against another user or the computer */
+pub(crate) fn render_mode_selection(
+    ctx: &mut Context,
+    state: &MainState,
+) -> Result<Action, GameError> {
+    let size = Vec2::new(760.0, 470.0);
+    let center = Vec2::new(SCREEN_SIZE.0 / 2.0, SCREEN_SIZE.1 / 2.0);
+    let start = center - size / 2.0;
+    render_round_rect(ctx, start, size, BUTTON_COLOR)?;
+
+    let active_font = &state.render_config.fontsets[state.render_config.active_fontset_index];
+    let titles = [
+        ("Välj spelläge", 20.0),
+        ("Datorns styrka", 170.0),
+        ("Betänketid", 320.0),
+    ];
+    for (title, y) in titles.iter() {
+        let mut text = graphics::Text::new(*title);
+        text.set_font(active_font.font, active_font.font_size);
+        graphics::draw(
+            ctx,
+            &text,
+            graphics::DrawParam::new()
+                .dest(Vec2::new(center.x, start.y + y))
+                .offset(Vec2::new(0.5, 0.0)),
+        )?;
+    }
+
+    // every row of buttons is centered
+    let get_row_x = |count: usize, width: f32, gap: f32, i: usize| -> f32 {
+        let row_width = count as f32 * (width + gap) - gap;
+        return center.x - row_width / 2.0 + (width + gap) * i as f32;
+    };
+
+    let mut action = Action::None;
+
+    let modes = [
+        ("Två spelare", Action::PlayTwoPlayers),
+        ("Spela vit", Action::PlayComputer(ChessPieceColor::White)),
+        ("Spela svart", Action::PlayComputer(ChessPieceColor::Black)),
+        ("Anslut", Action::StartClient),
+    ];
+    let mode_size = Vec2::new(170.0, 70.0);
+    for (i, (name, mode_action)) in modes.iter().enumerate() {
+        let pos = Vec2::new(
+            get_row_x(modes.len(), mode_size.x, 20.0, i),
+            start.y + 70.0,
+        );
+        if render_text_button(ctx, state, pos, mode_size, name, CONFIRM_COLOR)? {
+            action = *mode_action;
+        }
+    }
+
+    // the chosen strength and time control are marked
+    let strength_size = Vec2::new(137.5, 60.0);
+    for (i, strength) in STRENGTHS.iter().enumerate() {
+        let pos = Vec2::new(
+            get_row_x(STRENGTHS.len(), strength_size.x, 12.5, i),
+            start.y + 220.0,
+        );
+        let color = if i == state.strength {
+            HIGHLIGHT_COLOR
+        } else {
+            BUTTON_COLOR_SELECTED
+        };
+        if render_text_button(ctx, state, pos, strength_size, strength.name, color)? {
+            action = Action::SetStrength(i);
+        }
+    }
+
+    let time_control_size = Vec2::new(136.0, 60.0);
+    for (i, (name, _)) in TIME_CONTROLS.iter().enumerate() {
+        let pos = Vec2::new(
+            get_row_x(TIME_CONTROLS.len(), time_control_size.x, 10.0, i),
+            start.y + 370.0,
+        );
+        let color = if i == state.time_control {
+            HIGHLIGHT_COLOR
+        } else {
+            BUTTON_COLOR_SELECTED
+        };
+        if render_text_button(ctx, state, pos, time_control_size, name, color)? {
+            action = Action::SetTimeControl(i);
+        }
+    }
+
+    Ok(action)
+}
+
+/** Renders the popup where the user writes the address of the server to play on */
+pub(crate) fn render_connect_dialog(
+    ctx: &mut Context,
+    state: &MainState,
+) -> Result<Action, GameError> {
+    let address = match &state.connect_address {
+        Some(address) => address,
+        None => return Ok(Action::None),
+    };
+
+    let size = Vec2::new(500.0, 250.0);
+    let center = Vec2::new(SCREEN_SIZE.0 / 2.0, SCREEN_SIZE.1 / 2.0);
+    let start = center - size / 2.0;
+    render_round_rect(ctx, start, size, BUTTON_COLOR)?;
+
+    let active_font = &state.render_config.fontsets[state.render_config.active_fontset_index];
+    let mut title = graphics::Text::new("Anslut till (värd:port)");
+    title.set_font(active_font.font, active_font.font_size);
+    graphics::draw(
+        ctx,
+        &title,
+        graphics::DrawParam::new()
+            .dest(Vec2::new(center.x, start.y + 20.0))
+            .offset(Vec2::new(0.5, 0.0)),
+    )?;
+
+    // the text field, the cursor blinks at the end of the address
+    let field_size = Vec2::new(440.0, 60.0);
+    let field_pos = Vec2::new(center.x - field_size.x / 2.0, start.y + 70.0);
+    render_round_rect(ctx, field_pos, field_size, BUTTON_COLOR_SELECTED)?;
+    let cursor = if state.frame / 30 % 2 == 0 { "|" } else { " " };
+    let mut text = graphics::Text::new(format!("{}{}", address, cursor));
+    text.set_font(active_font.font, active_font.font_size);
+    graphics::draw(
+        ctx,
+        &text,
+        graphics::DrawParam::new()
+            .dest(field_pos + Vec2::new(15.0, field_size.y / 2.0))
+            .offset(Vec2::new(0.0, 0.5)),
+    )?;
+
+    let mut action = Action::None;
+    let button_size = Vec2::new(190.0, 60.0);
+    let buttons = [
+        (
+            "Anslut",
+            Action::Connect,
+            CONFIRM_COLOR,
+            -button_size.x - 10.0,
+        ),
+        ("Avbryt", Action::CancelConnect, ERROR_COLOR, 10.0),
+    ];
+    for (name, button_action, color, x) in buttons.iter() {
+        let pos = Vec2::new(center.x + x, start.y + 160.0);
+        if render_text_button(ctx, state, pos, button_size, name, *color)? {
+            action = *button_action;
+        }
+    }
+
+    Ok(action)
+}
+
+/** Renders a text under the board while the computer is thinking, the dots move to show it is not stuck */
+pub(crate) fn render_thinking(
+    ctx: &mut Context,
+    config: &RenderConfig,
+    frame: u64,
+) -> GameResult<()> {
+    let dots = ".".repeat((frame / 20 % 4) as usize);
+    return render_status(ctx, config, &format!("Datorn tänker{:<3}", dots));
+}
+
+/** Renders a line of text under the board */
+pub(crate) fn render_status(
+    ctx: &mut Context,
+    config: &RenderConfig,
+    text: &str,
+) -> GameResult<()> {
+    let active_font = &config.fontsets[config.active_fontset_index];
+    let mut text = graphics::Text::new(text);
+    text.set_font(active_font.font, active_font.font_size);
+    graphics::draw(
+        ctx,
+        &text,
+        graphics::DrawParam::new()
+            .dest(Vec2::new(
+                SCREEN_SIZE.0 / 2.0,
+                BOARD_RENDER_START.1 + BOARD_RENDER_SIZE + 10.0,
+            ))
+            .offset(Vec2::new(0.5, 0.0)),
+    )?;
+    Ok(())
+}
+
+/** Renders the clocks under the board, white to the left and black to the right.
+The clock that is running is marked */
+pub(crate) fn render_clocks(
+    ctx: &mut Context,
+    config: &RenderConfig,
+    clock: &Clock,
+) -> GameResult<()> {
+    let active_font = &config.fontsets[config.active_fontset_index];
+    let now = Instant::now();
+    let size = Vec2::new(200.0, 50.0);
+    let y = BOARD_RENDER_START.1 + BOARD_RENDER_SIZE + 5.0;
+    let clocks = [
+        ("Vit", ChessPieceColor::White, BOARD_RENDER_START.0),
+        (
+            "Svart",
+            ChessPieceColor::Black,
+            BOARD_RENDER_START.0 + BOARD_RENDER_SIZE - size.x,
+        ),
+    ];
+    for (name, color, x) in clocks.iter() {
+        let pos = Vec2::new(*x, y);
+        let is_running = clock.is_running() && clock.get_turn() == *color;
+        let flagged = clock.get_flagged() == Some(*color);
+        let background = if flagged {
+            ERROR_COLOR
+        } else if is_running {
+            HIGHLIGHT_COLOR
+        } else {
+            BUTTON_COLOR
+        };
+        render_round_rect(ctx, pos, size, background)?;
+
+        let time = format_time(clock.get_remaining(*color, now));
+        let mut text = graphics::Text::new(format!("{} {}", name, time));
+        text.set_font(active_font.font, active_font.font_size);
+        graphics::draw(
+            ctx,
+            &text,
+            graphics::DrawParam::new()
+                .dest(pos + size / 2.0)
+                .offset(Vec2::new(0.5, 0.5)),
+        )?;
+    }
+    Ok(())
+}
+
 fn is_inside_square(mouse_x: f32, mouse_y: f32, pos: Vec2, size: Vec2) -> bool {
     return mouse_x > pos.x
         && mouse_x < pos.x + size.x
@@ -277,15 +544,16 @@ pub(crate) fn render_buttons(ctx: &mut Context, state: &MainState) -> Option<usi
         // &state.render_config.icons.surrender,
         &state.render_config.icons.replay,
         &state.render_config.icons.settings,
+        &state.render_config.icons.arrow_back,
     ];
 
-    let start = SCREEN_SIZE.0 / 2.0 - 200.0;
+    let start = SCREEN_SIZE.0 / 2.0 - 75.0 * (icons.len() - 1) as f32 - 50.0;
 
     let mouse_x = state.input_staus.pos_x;
     let mouse_y = state.input_staus.pos_y;
     let mut hover_button = None;
 
-    for x in 0..3 {
+    for x in 0..icons.len() {
         let pos = Vec2::new(start + 150.0 * (x as f32), 5.0);
         let size = Vec2::new(100.0, 50.0);
         let is_hovering = is_inside_square(mouse_x, mouse_y, pos, size);
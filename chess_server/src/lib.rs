@@ -0,0 +1,653 @@
+// A server hosting games for clients talking the protocol. Everything runs on one thread:
+// the sockets never block, and poll handles whatever has happened since it was last called,
+// so the server can run its own loop or be polled from the loop of another program
+use chess_engine::chess_game::{ChessMove, ChessPieceColor};
+use chess_engine::online::{ClientId, GameId, GameInfo, Lobby, LobbyError, OnlineGame, Seat};
+use chess_engine::protocol::{self, ClientMessage, ProtocolError, ServerMessage};
+use mio::net::{TcpListener, TcpStream};
+use mio::{Events, Interest, Poll, Token};
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+pub const DEFAULT_PORT: u16 = 6000;
+
+// The clients get the token of their id plus one
+const LISTENER: Token = Token(0);
+const EVENT_CAPACITY: usize = 256;
+const READ_SIZE: usize = 4096;
+// A client that does not read what is sent to it is dropped before it uses all the memory
+const MAX_OUTGOING: usize = 1024 * 1024;
+
+// What is logged, every level includes the ones before it
+#[derive(Clone, Copy, PartialEq, PartialOrd, Debug)]
+pub enum LogLevel {
+    Off,
+    Error,
+    Info,
+    Debug,
+}
+
+impl LogLevel {
+    pub fn parse(text: &str) -> Option<LogLevel> {
+        match text.to_ascii_lowercase().as_str() {
+            "off" => return Some(LogLevel::Off),
+            "error" => return Some(LogLevel::Error),
+            "info" => return Some(LogLevel::Info),
+            "debug" => return Some(LogLevel::Debug),
+            _ => return None,
+        }
+    }
+}
+
+struct Connection {
+    id: ClientId,
+    address: SocketAddr,
+    stream: TcpStream,
+    name: Option<String>, // None until the client has said hello
+    incoming: Vec<u8>,    // read, but not a whole frame yet
+    outgoing: Vec<u8>,    // waiting for the socket to take it
+    closing: bool,        // closed as soon as everything has been sent
+}
+
+pub struct Server {
+    readiness: Poll, // Tells run when a socket can be read or written
+    listener: TcpListener,
+    lobby: Lobby,
+    connections: Vec<Connection>,
+    next_id: ClientId,
+    log_level: LogLevel,
+}
+
+impl Server {
+    // Listen on an address, use port 0 to get any free port
+    pub fn bind(address: impl ToSocketAddrs, log_level: LogLevel) -> io::Result<Server> {
+        let listener = std::net::TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        let mut listener = TcpListener::from_std(listener);
+        let readiness = Poll::new()?;
+        readiness
+            .registry()
+            .register(&mut listener, LISTENER, Interest::READABLE)?;
+
+        let server = Server {
+            readiness,
+            listener,
+            lobby: Lobby::new(),
+            connections: Vec::new(),
+            next_id: 0,
+            log_level,
+        };
+        server.log(
+            LogLevel::Info,
+            &format!("Listening on {}", server.local_addr()?),
+        );
+        return Ok(server);
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        return self.listener.local_addr();
+    }
+
+    pub fn get_lobby(&self) -> &Lobby {
+        return &self.lobby;
+    }
+
+    pub fn get_client_count(&self) -> usize {
+        return self.connections.len();
+    }
+
+    // Serve until the program is stopped. Sleeps until a socket is ready
+    // or the clock of a game runs out
+    pub fn run(&mut self) {
+        let mut events = Events::with_capacity(EVENT_CAPACITY);
+        loop {
+            let timeout = self.get_next_timeout(Instant::now());
+            if let Err(error) = self.readiness.poll(&mut events, timeout) {
+                if error.kind() != io::ErrorKind::Interrupted {
+                    self.log(LogLevel::Error, &format!("Waiting failed: {}", error));
+                }
+            }
+            // the events only wake the server up, poll looks at every connection
+            self.poll();
+        }
+    }
+
+    // Accept new clients, read what the clients have sent and answer it, and end the games
+    // where a player ran out of time. Never waits, returns false if nothing happened
+    pub fn poll(&mut self) -> bool {
+        let mut busy = self.accept();
+        busy |= self.check_timeouts(Instant::now());
+
+        let mut received = Vec::new();
+        for connection in self.connections.iter_mut() {
+            if !connection.closing {
+                for result in receive(connection) {
+                    received.push((connection.id, result));
+                }
+            }
+        }
+        busy |= !received.is_empty();
+        for (id, result) in received {
+            match result {
+                Ok(message) => self.handle_message(id, message),
+                Err(error) => self.handle_error(id, error),
+            }
+        }
+
+        let mut dropped = Vec::new();
+        for connection in self.connections.iter_mut() {
+            let had_outgoing = !connection.outgoing.is_empty();
+            match flush(connection) {
+                Ok(()) => {
+                    if connection.closing && connection.outgoing.is_empty() {
+                        dropped.push((connection.id, None));
+                    }
+                }
+                Err(error) => dropped.push((connection.id, Some(error))),
+            }
+            busy |= had_outgoing;
+        }
+        for (id, error) in dropped {
+            self.remove(id, error);
+        }
+        return busy;
+    }
+
+    // Get how long until a player runs out of time, None if no clock is running
+    fn get_next_timeout(&self, now: Instant) -> Option<Duration> {
+        return self
+            .lobby
+            .get_games()
+            .iter()
+            .filter_map(|game| game.get_time_left(now))
+            .min();
+    }
+
+    fn log(&self, level: LogLevel, text: &str) {
+        if level != LogLevel::Off && level <= self.log_level {
+            eprintln!("{} {:?} {}", get_timestamp(), level, text);
+        }
+    }
+
+    fn accept(&mut self) -> bool {
+        let mut accepted = false;
+        loop {
+            let (mut stream, address) = match self.listener.accept() {
+                Ok(connection) => connection,
+                Err(error) => {
+                    if error.kind() != io::ErrorKind::WouldBlock {
+                        self.log(LogLevel::Error, &format!("Accept failed: {}", error));
+                    }
+                    return accepted;
+                }
+            };
+            accepted = true;
+            let _delay_error = stream.set_nodelay(true);
+
+            let id = self.next_id;
+            self.next_id += 1;
+            let token = Token(id as usize + 1);
+            let interest = Interest::READABLE | Interest::WRITABLE;
+            if let Err(error) = self
+                .readiness
+                .registry()
+                .register(&mut stream, token, interest)
+            {
+                self.log(LogLevel::Error, &format!("{}: {}", address, error));
+                continue;
+            }
+            self.log(LogLevel::Info, &format!("{} connected", address));
+            self.connections.push(Connection {
+                id,
+                address,
+                stream,
+                name: None,
+                incoming: Vec::new(),
+                outgoing: Vec::new(),
+                closing: false,
+            });
+        }
+    }
+
+    fn get_connection(&mut self, id: ClientId) -> Option<&mut Connection> {
+        return self
+            .connections
+            .iter_mut()
+            .find(|connection| connection.id == id);
+    }
+
+    fn get_name(&self, id: ClientId) -> String {
+        return match self
+            .connections
+            .iter()
+            .find(|connection| connection.id == id)
+        {
+            Some(connection) => match &connection.name {
+                Some(name) => format!("{} ({})", name, connection.address),
+                None => connection.address.to_string(),
+            },
+            None => format!("client {}", id),
+        };
+    }
+
+    // Queue a message, it is sent at the end of poll
+    fn send_to(&mut self, id: ClientId, message: &ServerMessage) {
+        let frame = match protocol::encode_frame(&message.encode()) {
+            Ok(frame) => frame,
+            Err(error) => {
+                self.log(
+                    LogLevel::Error,
+                    &format!("{:?} not sent: {}", message, error),
+                );
+                return;
+            }
+        };
+        if let Some(connection) = self.get_connection(id) {
+            connection.outgoing.extend_from_slice(&frame);
+        }
+    }
+
+    // Send to the players and the spectators of a game
+    fn send_to_game(&mut self, game_id: GameId, message: &ServerMessage) {
+        let clients = match self.lobby.get_game(game_id) {
+            Some(game) => game.seats.get_clients(),
+            None => return,
+        };
+        for id in clients {
+            self.send_to(id, message);
+        }
+    }
+
+    // Send to the clients that have joined the server but are not in a game
+    fn send_to_lobby(&mut self, message: &ServerMessage) {
+        let clients: Vec<ClientId> = self
+            .connections
+            .iter()
+            .filter(|connection| connection.name.is_some())
+            .map(|connection| connection.id)
+            .filter(|id| self.lobby.get_game_of(*id).is_none())
+            .collect();
+        for id in clients {
+            self.send_to(id, message);
+        }
+    }
+
+    fn send_lobby_error(&mut self, id: ClientId, error: LobbyError) {
+        self.log(
+            LogLevel::Debug,
+            &format!("{}: {}", self.get_name(id), error),
+        );
+        self.send_to(id, &ServerMessage::LobbyError { error });
+    }
+
+    // Tell a client where it sits and what the position is
+    fn send_seated(&mut self, id: ClientId, game_id: GameId, seat: Seat) {
+        let game = match self.lobby.get_game(game_id) {
+            Some(game) => game,
+            None => return,
+        };
+        let seated = ServerMessage::Seated {
+            game_id,
+            seat,
+            time_control: game.get_time_control().cloned(),
+        };
+        let position = ServerMessage::Position {
+            fen: game.game.to_fen(),
+        };
+        self.log(
+            LogLevel::Info,
+            &format!(
+                "{} joined game {} as {:?}",
+                self.get_name(id),
+                game_id,
+                seat
+            ),
+        );
+        self.send_to(id, &seated);
+        self.send_to(id, &position);
+    }
+
+    // Send the time left to everybody in a game, the clock starts when both players are seated
+    fn send_clock(&mut self, game_id: GameId, now: Instant) {
+        let times = self
+            .lobby
+            .get_game(game_id)
+            .and_then(|game| game.get_clock_times(now));
+        if let Some((white, black, running)) = times {
+            let clock = ServerMessage::Clock {
+                white,
+                black,
+                running,
+            };
+            self.send_to_game(game_id, &clock);
+        }
+    }
+
+    fn check_timeouts(&mut self, now: Instant) -> bool {
+        let timeouts = self.lobby.check_timeouts(now);
+        for (game_id, color) in timeouts.iter() {
+            self.log(
+                LogLevel::Info,
+                &format!("Game {}: {:?} ran out of time", game_id, color),
+            );
+            self.send_to_game(*game_id, &ServerMessage::TimeUp { color: *color });
+        }
+        return !timeouts.is_empty();
+    }
+
+    fn get_player_name(&self, id: Option<ClientId>) -> Option<String> {
+        let id = id?;
+        return self
+            .connections
+            .iter()
+            .find(|connection| connection.id == id)
+            .and_then(|connection| connection.name.clone());
+    }
+
+    fn get_game_info(&self, game: &OnlineGame) -> GameInfo {
+        let white = game.seats.get_player(ChessPieceColor::White);
+        let black = game.seats.get_player(ChessPieceColor::Black);
+        let players = white.iter().chain(black.iter()).count();
+        return GameInfo {
+            id: game.id,
+            time_control: game.get_time_control().cloned(),
+            white: self.get_player_name(white),
+            black: self.get_player_name(black),
+            spectators: (game.seats.get_clients().len() - players) as u32,
+            is_over: game.get_status().is_over(),
+        };
+    }
+
+    // Send the rest of what is queued, then close the connection
+    fn close(&mut self, id: ClientId) {
+        if let Some(connection) = self.get_connection(id) {
+            connection.closing = true;
+        }
+    }
+
+    fn remove(&mut self, id: ClientId, error: Option<io::Error>) {
+        let name = self.get_name(id);
+        if let Some(error) = error {
+            self.log(LogLevel::Info, &format!("{}: {}", name, error));
+        }
+        self.leave(id);
+        self.connections.retain(|connection| connection.id != id);
+        self.log(
+            LogLevel::Info,
+            &format!("Closed the connection with {}", name),
+        );
+    }
+
+    // Take a client out of its game or the queue. The others in the game are told when a
+    // player leaves, a game that has started is lost by the player leaving it
+    fn leave(&mut self, id: ClientId) -> Option<GameId> {
+        let now = Instant::now();
+        let seat = self
+            .lobby
+            .get_game_of(id)
+            .and_then(|game_id| self.lobby.get_game(game_id))
+            .and_then(|game| game.seats.get_seat(id));
+        let game_id = self.lobby.leave(id, now)?;
+        self.log(
+            LogLevel::Info,
+            &format!("{} left game {}", self.get_name(id), game_id),
+        );
+        if let Some(seat) = seat.filter(|seat| seat.get_color().is_some()) {
+            self.send_to_game(game_id, &ServerMessage::PlayerLeft { seat });
+            self.send_clock(game_id, now);
+        }
+        return Some(game_id);
+    }
+
+    fn handle_error(&mut self, id: ClientId, error: ProtocolError) {
+        let has_joined = match self.get_connection(id) {
+            Some(connection) => connection.name.is_some(),
+            None => return,
+        };
+        self.log(LogLevel::Info, &format!("{}: {}", self.get_name(id), error));
+        match error {
+            ProtocolError::Io(_) | ProtocolError::Closed => {}
+            _ => {
+                // tell clients that have not joined why, as in protocol::server_handshake
+                if !has_joined {
+                    let reason = error.to_string();
+                    self.send_to(id, &ServerMessage::Rejected { reason });
+                }
+            }
+        }
+        self.close(id);
+    }
+
+    fn handle_hello(&mut self, id: ClientId, message: ClientMessage) {
+        let name = match protocol::check_hello(message) {
+            Ok(name) => name,
+            Err(error) => return self.handle_error(id, error),
+        };
+        let welcome = ServerMessage::Welcome {
+            version: protocol::PROTOCOL_VERSION,
+        };
+        self.send_to(id, &welcome);
+        if let Some(connection) = self.get_connection(id) {
+            connection.name = Some(name);
+        }
+        self.log(
+            LogLevel::Info,
+            &format!("{} entered the lobby", self.get_name(id)),
+        );
+    }
+
+    fn handle_move(&mut self, id: ClientId, chess_move: ChessMove) {
+        let now = Instant::now();
+        // end the game before the move if the clock has run out, so the players are told why
+        self.check_timeouts(now);
+        let game_id = match self.lobby.get_game_of(id) {
+            Some(game_id) => game_id,
+            None => return self.send_lobby_error(id, LobbyError::NotInGame),
+        };
+
+        // only the player sitting on the color to move can move
+        let game = self.lobby.get_game_mut(game_id).unwrap();
+        let san = game
+            .game
+            .move_to_san(chess_move.board_move, chess_move.promote_piece);
+        let result = game
+            .play_move(id, chess_move, now)
+            .map(|_| game.game.to_fen());
+        match result {
+            Ok(fen) => {
+                self.log(
+                    LogLevel::Info,
+                    &format!("Game {}: {} played {}", game_id, self.get_name(id), san),
+                );
+                let moved = ServerMessage::Moved {
+                    chess_move,
+                    san,
+                    fen,
+                };
+                self.send_to_game(game_id, &moved);
+                self.send_clock(game_id, now);
+            }
+            Err(rejection) => {
+                self.log(
+                    LogLevel::Debug,
+                    &format!("{} {}: {}", self.get_name(id), san, rejection),
+                );
+                self.send_to(id, &ServerMessage::MoveRejected { rejection });
+            }
+        }
+    }
+
+    fn handle_message(&mut self, id: ClientId, message: ClientMessage) {
+        let has_joined = match self.get_connection(id) {
+            Some(connection) => connection.name.is_some(),
+            None => return,
+        };
+        if !has_joined {
+            return self.handle_hello(id, message);
+        }
+        self.log(
+            LogLevel::Debug,
+            &format!("{}: {:?}", self.get_name(id), message),
+        );
+
+        let now = Instant::now();
+        match message {
+            ClientMessage::Move { chess_move } => self.handle_move(id, chess_move),
+            ClientMessage::GetPosition => {
+                let fen = match self.lobby.get_game_of(id) {
+                    Some(game_id) => self.lobby.get_game(game_id).unwrap().game.to_fen(),
+                    None => return self.send_lobby_error(id, LobbyError::NotInGame),
+                };
+                self.send_to(id, &ServerMessage::Position { fen });
+            }
+            ClientMessage::Chat { text } => {
+                let name = self.get_player_name(Some(id)).unwrap_or_default();
+                let chat = ServerMessage::Chat { name, text };
+                match self.lobby.get_game_of(id) {
+                    Some(game_id) => self.send_to_game(game_id, &chat),
+                    None => self.send_to_lobby(&chat),
+                }
+            }
+            ClientMessage::ListGames => {
+                let games = self
+                    .lobby
+                    .get_games()
+                    .iter()
+                    .map(|game| self.get_game_info(game))
+                    .collect();
+                self.send_to(id, &ServerMessage::GameList { games });
+            }
+            ClientMessage::CreateGame {
+                time_control,
+                color,
+            } => {
+                self.leave(id);
+                let (game_id, seat) = self.lobby.create_game(id, time_control, color, now);
+                self.send_seated(id, game_id, seat);
+                self.send_clock(game_id, now);
+            }
+            ClientMessage::JoinGame { game_id, spectate } => {
+                let current_game = self.lobby.get_game_of(id);
+                if current_game.is_some() && current_game != Some(game_id) {
+                    self.leave(id);
+                }
+                match self.lobby.join_game(id, game_id, spectate, now) {
+                    Ok(seat) => {
+                        self.send_seated(id, game_id, seat);
+                        self.send_clock(game_id, now);
+                    }
+                    Err(error) => self.send_lobby_error(id, error),
+                }
+            }
+            ClientMessage::FindGame { time_control } => {
+                self.leave(id);
+                match self.lobby.find_game(id, time_control, now) {
+                    Some(game_id) => {
+                        let seats = self.lobby.get_game(game_id).unwrap().seats.clone();
+                        for player in seats.get_clients() {
+                            self.send_seated(player, game_id, seats.get_seat(player).unwrap());
+                        }
+                        self.send_clock(game_id, now);
+                    }
+                    None => {
+                        self.log(
+                            LogLevel::Info,
+                            &format!("{} is waiting for an opponent", self.get_name(id)),
+                        );
+                        self.send_to(id, &ServerMessage::Queued);
+                    }
+                }
+            }
+            ClientMessage::LeaveGame => {
+                let was_queued = self.lobby.is_queued(id);
+                if self.leave(id).is_none() && !was_queued {
+                    self.send_lobby_error(id, LobbyError::NotInGame);
+                }
+            }
+            ClientMessage::Hello { .. } => {
+                let error = ProtocolError::InvalidMessage("Hello sent twice".to_string());
+                self.handle_error(id, error);
+            }
+        }
+    }
+}
+
+// Read everything the client has sent so far and take the whole messages out of it.
+// An error is last, nothing more is read from the client after it
+fn receive(connection: &mut Connection) -> Vec<Result<ClientMessage, ProtocolError>> {
+    let mut read_error = None;
+    let mut buffer = [0; READ_SIZE];
+    loop {
+        match connection.stream.read(&mut buffer) {
+            Ok(0) => {
+                read_error = Some(ProtocolError::Closed);
+                break;
+            }
+            Ok(size) => connection.incoming.extend_from_slice(&buffer[..size]),
+            Err(error) if error.kind() == io::ErrorKind::WouldBlock => break,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+            Err(error) => {
+                read_error = Some(ProtocolError::from(error));
+                break;
+            }
+        }
+    }
+
+    let mut results = Vec::new();
+    loop {
+        match protocol::take_frame(&mut connection.incoming) {
+            Ok(Some(frame)) => {
+                let result = ClientMessage::decode(&frame);
+                let is_error = result.is_err();
+                results.push(result);
+                if is_error {
+                    return results;
+                }
+            }
+            Ok(None) => break,
+            Err(error) => {
+                results.push(Err(error));
+                return results;
+            }
+        }
+    }
+    if let Some(error) = read_error {
+        results.push(Err(error));
+    }
+    return results;
+}
+
+// Write as much of the queue as the socket takes without waiting
+fn flush(connection: &mut Connection) -> io::Result<()> {
+    if connection.outgoing.len() > MAX_OUTGOING {
+        return Err(io::Error::other("too many messages waiting to be sent"));
+    }
+    while !connection.outgoing.is_empty() {
+        match connection.stream.write(&connection.outgoing) {
+            Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+            Ok(size) => {
+                connection.outgoing.drain(..size);
+            }
+            Err(error) if error.kind() == io::ErrorKind::WouldBlock => break,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error),
+        }
+    }
+    return Ok(());
+}
+
+// The time of day in UTC, as hours:minutes:seconds.milliseconds
+fn get_timestamp() -> String {
+    let since_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let seconds = since_epoch.as_secs() % (24 * 60 * 60);
+    return format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60,
+        since_epoch.subsec_millis()
+    );
+}
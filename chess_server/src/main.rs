@@ -0,0 +1,72 @@
+// Host games without a window, ex. "chess_server --host 0.0.0.0 --port 6000 --log debug".
+// The GUI connects to it with the address of the machine and the port
+extern crate chess_server;
+use chess_server::{LogLevel, Server, DEFAULT_PORT};
+use std::process::exit;
+
+const USAGE: &str =
+    "Usage: chess_server [--host <address>] [--port <port>] [--log off|error|info|debug]";
+
+struct Options {
+    host: String,
+    port: u16,
+    log_level: LogLevel,
+}
+
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        host: "0.0.0.0".to_string(),
+        port: DEFAULT_PORT,
+        log_level: LogLevel::Info,
+    };
+    let mut i = 0;
+    while i < args.len() {
+        let value = match args.get(i + 1) {
+            Some(value) => value,
+            None => return Err(format!("{} needs a value", args[i])),
+        };
+        match args[i].as_str() {
+            "--host" => options.host = value.clone(),
+            "--port" => match value.parse() {
+                Ok(port) => options.port = port,
+                Err(_) => return Err(format!("Invalid port: {}", value)),
+            },
+            "--log" => match LogLevel::parse(value) {
+                Some(log_level) => options.log_level = log_level,
+                None => return Err(format!("Invalid log level: {}", value)),
+            },
+            arg => return Err(format!("Unknown option: {}", arg)),
+        }
+        i += 2;
+    }
+    return Ok(options);
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", USAGE);
+        return;
+    }
+    let options = match parse_args(&args) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{}", error);
+            eprintln!("{}", USAGE);
+            exit(2);
+        }
+    };
+
+    let address = (options.host.as_str(), options.port);
+    let mut server = match Server::bind(address, options.log_level) {
+        Ok(server) => server,
+        Err(error) => {
+            eprintln!(
+                "Could not listen on {}:{}: {}",
+                options.host, options.port, error
+            );
+            exit(1);
+        }
+    };
+    server.run();
+}
@@ -0,0 +1,240 @@
+#[cfg(test)]
+mod server_test {
+    pub extern crate chess_engine;
+    pub extern crate chess_server;
+    use chess_engine::chess_game::*;
+    use chess_engine::clock::*;
+    use chess_engine::online::*;
+    use chess_engine::protocol::*;
+    use chess_server::*;
+    use std::net::{SocketAddr, TcpStream};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::thread::{self, JoinHandle};
+    use std::time::Duration;
+
+    // Poll a server on its own thread until stopped, the clients in the test wait for answers
+    fn start_server() -> (SocketAddr, Arc<AtomicBool>, JoinHandle<Server>) {
+        let mut server = Server::bind("127.0.0.1:0", LogLevel::Off).unwrap();
+        let address = server.local_addr().unwrap();
+        let stop = Arc::new(AtomicBool::new(false));
+        let server_stop = stop.clone();
+        let handle = thread::spawn(move || {
+            while !server_stop.load(Ordering::Relaxed) {
+                if !server.poll() {
+                    thread::sleep(Duration::from_millis(1));
+                }
+            }
+            server
+        });
+        (address, stop, handle)
+    }
+
+    // Run a server on its own thread, it is stopped when the tests end
+    fn run_server() -> SocketAddr {
+        let mut server = Server::bind("127.0.0.1:0", LogLevel::Off).unwrap();
+        let address = server.local_addr().unwrap();
+        thread::spawn(move || server.run());
+        address
+    }
+
+    fn connect(address: SocketAddr, name: &str) -> TcpStream {
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        assert_eq!(
+            client_handshake(&mut stream, name).unwrap(),
+            PROTOCOL_VERSION
+        );
+        stream
+    }
+
+    fn send_move(stream: &mut TcpStream, text: &str) {
+        let chess_move = ChessMove::from_long_algebraic_notation(text).unwrap();
+        send_client_message(stream, &ClientMessage::Move { chess_move }).unwrap();
+    }
+
+    fn send(stream: &mut TcpStream, message: ClientMessage) {
+        send_client_message(stream, &message).unwrap();
+    }
+
+    // Read the Seated and Position a client gets when it sits down in a game
+    fn read_seated(stream: &mut TcpStream) -> (GameId, Seat) {
+        let seated = match read_server_message(stream).unwrap() {
+            ServerMessage::Seated { game_id, seat, .. } => (game_id, seat),
+            message => panic!("Expected Seated, got {:?}", message),
+        };
+        match read_server_message(stream).unwrap() {
+            ServerMessage::Position { .. } => {}
+            message => panic!("Expected Position, got {:?}", message),
+        }
+        seated
+    }
+
+    #[test]
+    fn server_game_test() {
+        let (address, stop, handle) = start_server();
+
+        // Moves are only played in a game
+        let mut white = connect(address, "Vincent");
+        send_move(&mut white, "e2e4");
+        assert_eq!(
+            read_server_message(&mut white).unwrap(),
+            ServerMessage::LobbyError {
+                error: LobbyError::NotInGame
+            }
+        );
+        let create = ClientMessage::CreateGame {
+            time_control: None,
+            color: Some(ChessPieceColor::White),
+        };
+        send(&mut white, create);
+        assert_eq!(read_seated(&mut white), (0, Seat::White));
+        send_move(&mut white, "e2e4");
+        assert_eq!(
+            read_server_message(&mut white).unwrap(),
+            ServerMessage::MoveRejected {
+                rejection: MoveRejection::NotStarted
+            }
+        );
+
+        // The open game is listed, and the next client joins it
+        let mut black = connect(address, "Victor");
+        send(&mut black, ClientMessage::ListGames);
+        match read_server_message(&mut black).unwrap() {
+            ServerMessage::GameList { games } => {
+                assert_eq!(games.len(), 1);
+                assert_eq!(games[0].white, Some("Vincent".to_string()));
+                assert_eq!(games[0].black, None);
+            }
+            message => panic!("Expected GameList, got {:?}", message),
+        }
+        let join = ClientMessage::JoinGame {
+            game_id: 0,
+            spectate: false,
+        };
+        send(&mut black, join.clone());
+        assert_eq!(read_seated(&mut black), (0, Seat::Black));
+
+        // Black has to wait for white, then both get the move
+        send_move(&mut black, "e7e5");
+        assert_eq!(
+            read_server_message(&mut black).unwrap(),
+            ServerMessage::MoveRejected {
+                rejection: MoveRejection::WrongSeat {
+                    turn: ChessPieceColor::White
+                }
+            }
+        );
+        send_move(&mut white, "e2e4");
+        for stream in [&mut white, &mut black].iter_mut() {
+            match read_server_message(stream).unwrap() {
+                ServerMessage::Moved { san, .. } => assert_eq!(san, "e4"),
+                message => panic!("Expected Moved, got {:?}", message),
+            }
+        }
+
+        // A player that leaves loses, the opponent is told and nobody takes over the seat
+        drop(white);
+        assert_eq!(
+            read_server_message(&mut black).unwrap(),
+            ServerMessage::PlayerLeft { seat: Seat::White }
+        );
+        let mut next = connect(address, "Next");
+        send(&mut next, join);
+        assert_eq!(read_seated(&mut next), (0, Seat::Spectator));
+
+        stop.store(true, Ordering::Relaxed);
+        let server = handle.join().unwrap();
+        let game = server.get_lobby().get_game(0).unwrap();
+        assert_eq!(
+            game.get_status(),
+            GameStatus::Resignation {
+                winner: ChessPieceColor::Black
+            }
+        );
+        assert_eq!(server.get_client_count(), 2);
+    }
+
+    #[test]
+    fn server_matchmaking_test() {
+        // run only wakes up for the clients and the clocks, time up is sent without traffic
+        let address = run_server();
+
+        // Clients are paired when they want the same time control, and lose when time is up
+        let find = ClientMessage::FindGame {
+            time_control: Some(TimeControl::sudden_death(Duration::from_secs(1))),
+        };
+        let mut first = connect(address, "Vincent");
+        send(&mut first, find.clone());
+        assert_eq!(
+            read_server_message(&mut first).unwrap(),
+            ServerMessage::Queued
+        );
+        let mut second = connect(address, "Victor");
+        send(&mut second, find);
+        assert_eq!(read_seated(&mut first), (0, Seat::White));
+        assert_eq!(read_seated(&mut second), (0, Seat::Black));
+        for stream in [&mut first, &mut second].iter_mut() {
+            match read_server_message(stream).unwrap() {
+                ServerMessage::Clock { white, running, .. } => {
+                    assert_eq!(white <= Duration::from_secs(1), true);
+                    assert_eq!(running, true);
+                }
+                message => panic!("Expected Clock, got {:?}", message),
+            }
+            assert_eq!(
+                read_server_message(stream).unwrap(),
+                ServerMessage::TimeUp {
+                    color: ChessPieceColor::White
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn server_handshake_test() {
+        let (address, stop, handle) = start_server();
+
+        // The first message must be Hello, the client is told why before it is closed
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        send_client_message(&mut stream, &ClientMessage::GetPosition).unwrap();
+        match read_server_message(&mut stream) {
+            Ok(ServerMessage::Rejected { .. }) => {}
+            result => panic!("Expected Rejected, got {:?}", result),
+        }
+        match read_server_message(&mut stream) {
+            Err(ProtocolError::Closed) => {}
+            result => panic!("Expected Closed, got {:?}", result),
+        }
+
+        // Hello can be split over many writes
+        let hello = ClientMessage::Hello {
+            version: PROTOCOL_VERSION,
+            name: "Slow".to_string(),
+        };
+        let mut frame = Vec::new();
+        send_client_message(&mut frame, &hello).unwrap();
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        for byte in frame.iter() {
+            std::io::Write::write_all(&mut stream, &[*byte]).unwrap();
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(
+            read_server_message(&mut stream).unwrap(),
+            ServerMessage::Welcome {
+                version: PROTOCOL_VERSION
+            }
+        );
+
+        stop.store(true, Ordering::Relaxed);
+        handle.join().unwrap();
+    }
+}